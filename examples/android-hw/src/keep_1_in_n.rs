@@ -1,5 +1,3 @@
-use std::mem::size_of;
-
 use futuresdr::anyhow::Result;
 use futuresdr::async_trait::async_trait;
 use futuresdr::runtime::Block;
@@ -25,8 +23,8 @@ impl Keep1InN {
         Block::new(
             BlockMetaBuilder::new("Keep1InN").build(),
             StreamIoBuilder::new()
-                .add_input("in", size_of::<f32>())
-                .add_output("out", size_of::<f32>())
+                .add_input::<f32>("in")
+                .add_output::<f32>("out")
                 .build(),
             MessageIoBuilder::new().build(),
             Self {
@@ -48,8 +46,8 @@ impl Kernel for Keep1InN {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let input = sio.input(0).slice::<f32>();
-        let output = sio.output(0).slice::<f32>();
+        let input = unsafe { sio.input(0).slice_unchecked::<f32>() };
+        let output = unsafe { sio.output(0).slice_unchecked::<f32>() };
 
         let mut consumed = 0;
         let mut produced = 0;
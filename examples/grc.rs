@@ -0,0 +1,21 @@
+use futuresdr::anyhow::{bail, Result};
+use futuresdr::runtime::grc::Grc;
+use futuresdr::runtime::Runtime;
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (path, rust) = match args.as_slice() {
+        [path] => (path, false),
+        [flag, path] if flag == "--rust" => (path, true),
+        _ => bail!("usage: grc [--rust] <flowgraph.grc>"),
+    };
+
+    let grc = Grc::load(path)?;
+    if rust {
+        print!("{}", grc.to_rust()?);
+    } else {
+        Runtime::new().run(grc.build()?)?;
+    }
+
+    Ok(())
+}
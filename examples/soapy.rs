@@ -1,5 +1,3 @@
-use std::mem::size_of;
-
 use futuresdr::anyhow::Result;
 use futuresdr::async_trait::async_trait;
 use futuresdr::blocks::Fft;
@@ -54,8 +52,8 @@ impl ComplexToMag {
         Block::new(
             BlockMetaBuilder::new("ComplexToMag").build(),
             StreamIoBuilder::new()
-                .add_input("in", size_of::<Complex<f32>>())
-                .add_output("out", size_of::<f32>())
+                .add_input::<Complex<f32>>("in")
+                .add_output::<f32>("out")
                 .build(),
             MessageIoBuilder::new().build(),
             Self {},
@@ -72,10 +70,10 @@ impl Kernel for ComplexToMag {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let i = sio.input(0).slice::<Complex<f32>>();
-        let o = sio.output(0).slice::<f32>();
+        let (i, o) = sio.slices::<Complex<f32>, f32>(0, 0);
+        let i_len = i.len();
 
-        let n = std::cmp::min(i.len(), o.len());
+        let n = std::cmp::min(i_len, o.len());
 
         for x in 0..n {
             let mut t = ((i[x].norm_sqr().log10() + 3.0) / 6.0).mul_add(255.0, 125.0) / 2.0;
@@ -83,7 +81,7 @@ impl Kernel for ComplexToMag {
             o[x] = t;
         }
 
-        if sio.input(0).finished() && n == i.len() {
+        if sio.input(0).finished() && n == i_len {
             io.finished = true;
         }
 
@@ -1,5 +1,4 @@
 use std::marker::PhantomData;
-use std::mem::size_of;
 
 use futuresdr::anyhow::Result;
 use futuresdr::async_trait::async_trait;
@@ -23,8 +22,8 @@ impl<T: Copy + Send + 'static> FftShift<T> {
         Block::new(
             BlockMetaBuilder::new("FftShift").build(),
             StreamIoBuilder::new()
-                .add_input("in", size_of::<T>())
-                .add_output("out", size_of::<T>())
+                .add_input::<T>("in")
+                .add_output::<T>("out")
                 .build(),
             MessageIoBuilder::new().build(),
             Self { _p: PhantomData },
@@ -41,8 +40,8 @@ impl<T: Copy + Send + 'static> Kernel for FftShift<T> {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let input = sio.input(0).slice::<T>();
-        let output = sio.output(0).slice::<T>();
+        let input = unsafe { sio.input(0).slice_unchecked::<T>() };
+        let output = unsafe { sio.output(0).slice_unchecked::<T>() };
 
         let n = std::cmp::min(input.len(), output.len()) / 2048;
 
@@ -15,8 +15,6 @@ use futuresdr::runtime::buffer::circular::Circular;
 use futuresdr::runtime::Flowgraph;
 use futuresdr::runtime::Pmt;
 use futuresdr::runtime::Runtime;
-use futuresdr::runtime::StreamInput;
-use futuresdr::runtime::StreamOutput;
 
 use wlan::Decoder;
 use wlan::Delay;
@@ -56,13 +54,12 @@ fn main() -> Result<()> {
     fg.connect_message(mac, "tx", encoder, "tx")?;
     let mapper = fg.add_block(Mapper::new());
     fg.connect_stream(encoder, "out", mapper, "in")?;
-    let mut fft = Fft::with_options(
+    let fft = Fft::with_options(
         64,
         FftDirection::Inverse,
         true,
         Some((1.0f32 / 52.0).sqrt()),
     );
-    fft.set_tag_propagation(Box::new(fft_tag_propagation));
     let fft = fg.add_block(fft);
     fg.connect_stream(mapper, "out", fft, "in")?;
     let prefix = fg.add_block(Prefix::new(PAD_FRONT, PAD_TAIL));
@@ -136,8 +133,7 @@ fn main() -> Result<()> {
     let sync_long = fg.add_block(SyncLong::new());
     fg.connect_stream(sync_short, "out", sync_long, "in")?;
 
-    let mut fft = Fft::new(64);
-    fft.set_tag_propagation(Box::new(fft_tag_propagation));
+    let fft = Fft::new(64);
     let fft = fg.add_block(fft);
     fg.connect_stream(sync_long, "out", fft, "in")?;
 
@@ -190,12 +186,3 @@ fn main() -> Result<()> {
 
     Ok(())
 }
-
-fn fft_tag_propagation(inputs: &mut [StreamInput], outputs: &mut [StreamOutput]) {
-    debug_assert_eq!(inputs[0].consumed().0, outputs[0].produced());
-    let (n, tags) = inputs[0].consumed();
-    // println!("fft produced {}   consumed {}   tags {:?}", outputs[0].produced(), n, tags);
-    for t in tags.iter().filter(|x| x.index < n) {
-        outputs[0].add_tag_abs(t.index, t.tag.clone());
-    }
-}
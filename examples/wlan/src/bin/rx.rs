@@ -13,8 +13,6 @@ use futuresdr::num_complex::Complex32;
 use futuresdr::runtime::Flowgraph;
 use futuresdr::runtime::Pmt;
 use futuresdr::runtime::Runtime;
-use futuresdr::runtime::StreamInput;
-use futuresdr::runtime::StreamOutput;
 
 use wlan::parse_channel;
 use wlan::Decoder;
@@ -87,8 +85,7 @@ fn main() -> Result<()> {
     let sync_long = fg.add_block(SyncLong::new());
     fg.connect_stream(sync_short, "out", sync_long, "in")?;
 
-    let mut fft = Fft::new(64);
-    fft.set_tag_propagation(Box::new(fft_tag_propagation));
+    let fft = Fft::new(64);
     let fft = fg.add_block(fft);
     fg.connect_stream(sync_long, "out", fft, "in")?;
 
@@ -121,12 +118,3 @@ fn main() -> Result<()> {
 
     Ok(())
 }
-
-fn fft_tag_propagation(inputs: &mut [StreamInput], outputs: &mut [StreamOutput]) {
-    debug_assert_eq!(inputs[0].consumed().0, outputs[0].produced());
-    let (n, tags) = inputs[0].consumed();
-    // println!("fft produced {}   consumed {}   tags {:?}", outputs[0].produced(), n, tags);
-    for t in tags.iter().filter(|x| x.index < n) {
-        outputs[0].add_tag_abs(t.index, t.tag.clone());
-    }
-}
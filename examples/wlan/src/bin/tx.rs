@@ -10,8 +10,6 @@ use futuresdr::runtime::buffer::circular::Circular;
 use futuresdr::runtime::Flowgraph;
 use futuresdr::runtime::Pmt;
 use futuresdr::runtime::Runtime;
-use futuresdr::runtime::StreamInput;
-use futuresdr::runtime::StreamOutput;
 
 use wlan::parse_channel;
 use wlan::Encoder;
@@ -69,13 +67,12 @@ fn main() -> Result<()> {
     fg.connect_message(mac, "tx", encoder, "tx")?;
     let mapper = fg.add_block(Mapper::new());
     fg.connect_stream(encoder, "out", mapper, "in")?;
-    let mut fft = Fft::with_options(
+    let fft = Fft::with_options(
         64,
         FftDirection::Inverse,
         true,
         Some((1.0f32 / 52.0).sqrt() * 0.6),
     );
-    fft.set_tag_propagation(Box::new(fft_tag_propagation));
     let fft = fg.add_block(fft);
     fg.connect_stream(mapper, "out", fft, "in")?;
     let prefix = fg.add_block(Prefix::new(PAD_FRONT, PAD_TAIL));
@@ -130,12 +127,3 @@ fn main() -> Result<()> {
 
     Ok(())
 }
-
-fn fft_tag_propagation(inputs: &mut [StreamInput], outputs: &mut [StreamOutput]) {
-    debug_assert_eq!(inputs[0].consumed().0, outputs[0].produced());
-    let (n, tags) = inputs[0].consumed();
-    // println!("fft produced {}   consumed {}   tags {:?}", outputs[0].produced(), n, tags);
-    for t in tags.iter().filter(|x| x.index < n) {
-        outputs[0].add_tag_abs(t.index, t.tag.clone());
-    }
-}
@@ -36,9 +36,7 @@ impl Decoder {
     pub fn new() -> Block {
         Block::new(
             BlockMetaBuilder::new("Decoder").build(),
-            StreamIoBuilder::new()
-                .add_input("in", std::mem::size_of::<u8>())
-                .build(),
+            StreamIoBuilder::new().add_input::<u8>("in").build(),
             MessageIoBuilder::new()
                 .add_output("rx_frames")
                 .add_output("rftap")
@@ -136,7 +134,7 @@ impl Kernel for Decoder {
         mio: &mut MessageIo<Self>,
         _b: &mut BlockMeta,
     ) -> Result<()> {
-        let mut input = sio.input(0).slice::<u8>();
+        let mut input = unsafe { sio.input(0).slice_unchecked::<u8>() };
 
         let tags = sio.input(0).tags();
         if let Some((index, any)) = tags.iter().find_map(|x| match x {
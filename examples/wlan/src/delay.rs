@@ -33,8 +33,8 @@ impl<T: Send + 'static> Delay<T> {
         Block::new(
             BlockMetaBuilder::new("Delay").build(),
             StreamIoBuilder::new()
-                .add_input("in", std::mem::size_of::<T>())
-                .add_output("out", std::mem::size_of::<T>())
+                .add_input::<T>("in")
+                .add_output::<T>("out")
                 .build(),
             MessageIoBuilder::new().build(),
             Self {
@@ -54,13 +54,13 @@ impl<T: Send + 'static> Kernel for Delay<T> {
         _m: &mut MessageIo<Self>,
         _b: &mut BlockMeta,
     ) -> Result<()> {
-        let i = sio.input(0).slice::<T>();
-        let o = sio.output(0).slice::<T>();
+        let i = unsafe { sio.input(0).slice_unchecked::<T>() };
+        let o = unsafe { sio.output(0).slice_unchecked::<T>() };
 
         match self.state {
             State::Pad(n) => {
                 let m = std::cmp::min(o.len(), n);
-                let o = sio.output(0).slice::<u8>();
+                let o = unsafe { sio.output(0).slice_unchecked::<u8>() };
                 o[0..m * std::mem::size_of::<T>()].fill(0);
                 sio.output(0).produce(m);
 
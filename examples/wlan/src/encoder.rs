@@ -44,7 +44,7 @@ impl Encoder {
     pub fn new(default_mcs: Mcs) -> Block {
         Block::new(
             BlockMetaBuilder::new("Encoder").build(),
-            StreamIoBuilder::new().add_output("out", 1).build(),
+            StreamIoBuilder::new().add_output::<u8>("out").build(),
             MessageIoBuilder::new()
                 .add_input("tx", Self::transmit)
                 .build(),
@@ -240,7 +240,7 @@ impl Kernel for Encoder {
         _b: &mut BlockMeta,
     ) -> Result<()> {
         loop {
-            let out = sio.output(0).slice::<u8>();
+            let out = unsafe { sio.output(0).slice_unchecked::<u8>() };
             if out.is_empty() {
                 break;
             }
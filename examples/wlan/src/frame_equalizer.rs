@@ -104,8 +104,8 @@ impl FrameEqualizer {
         Block::new(
             BlockMetaBuilder::new("FrameEqualizer").build(),
             StreamIoBuilder::new()
-                .add_input("in", std::mem::size_of::<Complex32>())
-                .add_output("out", std::mem::size_of::<u8>())
+                .add_input::<Complex32>("in")
+                .add_output::<u8>("out")
                 .build(),
             MessageIoBuilder::new().build(),
             Self {
@@ -183,8 +183,8 @@ impl Kernel for FrameEqualizer {
         _m: &mut MessageIo<Self>,
         _b: &mut BlockMeta,
     ) -> Result<()> {
-        let mut input = sio.input(0).slice::<Complex32>();
-        let out = sio.output(0).slice::<u8>();
+        let mut input = unsafe { sio.input(0).slice_unchecked::<Complex32>() };
+        let out = unsafe { sio.output(0).slice_unchecked::<u8>() };
 
         let tags = sio.input(0).tags();
         // info!("eq: input {} output {} tags {:?}", input.len(), out.len(), tags);
@@ -4,19 +4,18 @@ use crate::MAX_PSDU_SIZE;
 
 use futuresdr::anyhow::Result;
 use futuresdr::async_trait::async_trait;
-use futuresdr::futures::FutureExt;
 use futuresdr::log::{debug, warn};
+use futuresdr::macros::message_handler;
+use futuresdr::macros::Block;
 use futuresdr::runtime::Block;
 use futuresdr::runtime::BlockMeta;
-use futuresdr::runtime::BlockMetaBuilder;
 use futuresdr::runtime::Kernel;
 use futuresdr::runtime::MessageIo;
-use futuresdr::runtime::MessageIoBuilder;
 use futuresdr::runtime::Pmt;
-use futuresdr::runtime::StreamIoBuilder;
-use std::future::Future;
-use std::pin::Pin;
 
+#[derive(Block)]
+#[message_inputs(tx = transmit)]
+#[message_outputs(tx)]
 pub struct Mac {
     current_frame: [u8; MAX_PSDU_SIZE],
     sequence_number: u16,
@@ -35,29 +34,40 @@ impl Mac {
         current_frame[10..16].copy_from_slice(&dst_mac);
         current_frame[16..22].copy_from_slice(&bss_mac);
 
-        Block::new(
-            BlockMetaBuilder::new("Mac").build(),
-            StreamIoBuilder::new().build(),
-            MessageIoBuilder::new()
-                .add_input("tx", Self::transmit)
-                .add_output("tx")
-                .build(),
-            Mac {
-                current_frame,
-                sequence_number: 0,
-            },
-        )
+        Mac {
+            current_frame,
+            sequence_number: 0,
+        }
+        .into_block()
     }
 
-    fn transmit<'a>(
-        &'a mut self,
-        mio: &'a mut MessageIo<Mac>,
-        _meta: &'a mut BlockMeta,
+    #[message_handler]
+    async fn transmit(
+        &mut self,
+        mio: &mut MessageIo<Mac>,
+        _meta: &mut BlockMeta,
         p: Pmt,
-    ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
-        async move {
-            match p {
-                Pmt::Blob(data) => {
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::Blob(data) => {
+                if data.len() > MAX_PAYLOAD_SIZE {
+                    warn!(
+                        "WLAN Mac: TX frame too large ({}, max {}). Dropping.",
+                        data.len(),
+                        MAX_PAYLOAD_SIZE
+                    );
+                } else {
+                    let len = self.generate_mac_data_frame(&data);
+                    debug!("mac frame {:?}", &self.current_frame[0..len]);
+                    let mut vec = vec![0; len];
+                    vec.copy_from_slice(&self.current_frame[0..len]);
+                    mio.output_mut(0)
+                        .post(Pmt::Any(Box::new((vec, None as Option<Mcs>))))
+                        .await;
+                }
+            }
+            Pmt::Any(a) => {
+                if let Some((data, mcs)) = a.downcast_ref::<(Vec<u8>, Mcs)>() {
                     if data.len() > MAX_PAYLOAD_SIZE {
                         warn!(
                             "WLAN Mac: TX frame too large ({}, max {}). Dropping.",
@@ -65,41 +75,21 @@ impl Mac {
                             MAX_PAYLOAD_SIZE
                         );
                     } else {
-                        let len = self.generate_mac_data_frame(&data);
+                        let len = self.generate_mac_data_frame(data);
                         debug!("mac frame {:?}", &self.current_frame[0..len]);
                         let mut vec = vec![0; len];
                         vec.copy_from_slice(&self.current_frame[0..len]);
                         mio.output_mut(0)
-                            .post(Pmt::Any(Box::new((vec, None as Option<Mcs>))))
+                            .post(Pmt::Any(Box::new((vec, Some(*mcs)))))
                             .await;
                     }
                 }
-                Pmt::Any(a) => {
-                    if let Some((data, mcs)) = a.downcast_ref::<(Vec<u8>, Mcs)>() {
-                        if data.len() > MAX_PAYLOAD_SIZE {
-                            warn!(
-                                "WLAN Mac: TX frame too large ({}, max {}). Dropping.",
-                                data.len(),
-                                MAX_PAYLOAD_SIZE
-                            );
-                        } else {
-                            let len = self.generate_mac_data_frame(data);
-                            debug!("mac frame {:?}", &self.current_frame[0..len]);
-                            let mut vec = vec![0; len];
-                            vec.copy_from_slice(&self.current_frame[0..len]);
-                            mio.output_mut(0)
-                                .post(Pmt::Any(Box::new((vec, Some(*mcs)))))
-                                .await;
-                        }
-                    }
-                }
-                x => {
-                    warn!("WLAN Mac: received wrong PMT type in TX callback. {:?}", x);
-                }
             }
-            Ok(Pmt::Null)
+            x => {
+                warn!("WLAN Mac: received wrong PMT type in TX callback. {:?}", x);
+            }
         }
-        .boxed()
+        Ok(Pmt::Null)
     }
 
     fn generate_mac_data_frame(&mut self, data: &Vec<u8>) -> usize {
@@ -30,8 +30,8 @@ impl Mapper {
         Block::new(
             BlockMetaBuilder::new("Mapper").build(),
             StreamIoBuilder::new()
-                .add_input("in", 1)
-                .add_output("out", std::mem::size_of::<Complex32>())
+                .add_input::<u8>("in")
+                .add_output::<Complex32>("out")
                 .build(),
             MessageIoBuilder::new().build(),
             Mapper {
@@ -137,8 +137,8 @@ impl Kernel for Mapper {
         _m: &mut MessageIo<Self>,
         _b: &mut BlockMeta,
     ) -> Result<()> {
-        let mut input = sio.input(0).slice::<u8>();
-        let output = sio.output(0).slice::<Complex32>();
+        let mut input = unsafe { sio.input(0).slice_unchecked::<u8>() };
+        let output = unsafe { sio.output(0).slice_unchecked::<Complex32>() };
         if output.len() < 64 {
             return Ok(());
         }
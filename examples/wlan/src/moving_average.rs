@@ -49,8 +49,8 @@ impl<T: MovingAverageType + Send + 'static> MovingAverage<T> {
         Block::new(
             BlockMetaBuilder::new("MovingAverage").build(),
             StreamIoBuilder::new()
-                .add_input("in", std::mem::size_of::<T>())
-                .add_output("out", std::mem::size_of::<T>())
+                .add_input::<T>("in")
+                .add_output::<T>("out")
                 .build(),
             MessageIoBuilder::new().build(),
             Self {
@@ -71,8 +71,8 @@ impl<T: MovingAverageType + Send + 'static> Kernel for MovingAverage<T> {
         _m: &mut MessageIo<Self>,
         _b: &mut BlockMeta,
     ) -> Result<()> {
-        let input = sio.input(0).slice::<T>();
-        let out = sio.output(0).slice::<T>();
+        let input = unsafe { sio.input(0).slice_unchecked::<T>() };
+        let out = unsafe { sio.output(0).slice_unchecked::<T>() };
 
         if self.pad > 0 {
             let m = std::cmp::min(self.pad, out.len());
@@ -23,8 +23,8 @@ impl Prefix {
         Block::new(
             BlockMetaBuilder::new("Prefix").build(),
             StreamIoBuilder::new()
-                .add_input("in", std::mem::size_of::<Complex32>())
-                .add_output("out", std::mem::size_of::<Complex32>())
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
                 .build(),
             MessageIoBuilder::new().build(),
             Prefix {
@@ -44,8 +44,8 @@ impl Kernel for Prefix {
         _m: &mut MessageIo<Self>,
         _b: &mut BlockMeta,
     ) -> Result<()> {
-        let input = sio.input(0).slice::<Complex32>();
-        let output = sio.output(0).slice::<Complex32>();
+        let input = unsafe { sio.input(0).slice_unchecked::<Complex32>() };
+        let output = unsafe { sio.output(0).slice_unchecked::<Complex32>() };
 
         let tags = sio.input(0).tags().clone();
         if let Some((index, len)) = tags.iter().find_map(|x| match x {
@@ -33,8 +33,8 @@ impl SyncLong {
         Block::new(
             BlockMetaBuilder::new("SyncLong").build(),
             StreamIoBuilder::new()
-                .add_input("in", std::mem::size_of::<Complex32>())
-                .add_output("out", std::mem::size_of::<Complex32>())
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
                 .build(),
             MessageIoBuilder::new().build(),
             Self {
@@ -86,8 +86,8 @@ impl Kernel for SyncLong {
         _m: &mut MessageIo<Self>,
         _b: &mut BlockMeta,
     ) -> Result<()> {
-        let input = sio.input(0).slice::<Complex32>();
-        let out = sio.output(0).slice::<Complex32>();
+        let input = unsafe { sio.input(0).slice_unchecked::<Complex32>() };
+        let out = unsafe { sio.output(0).slice_unchecked::<Complex32>() };
 
         let mut m = std::cmp::min(input.len(), out.len());
 
@@ -32,10 +32,10 @@ impl SyncShort {
         Block::new(
             BlockMetaBuilder::new("SyncShort").build(),
             StreamIoBuilder::new()
-                .add_input("in_sig", std::mem::size_of::<Complex32>())
-                .add_input("in_abs", std::mem::size_of::<Complex32>())
-                .add_input("in_cor", std::mem::size_of::<f32>())
-                .add_output("out", std::mem::size_of::<Complex32>())
+                .add_input::<Complex32>("in_sig")
+                .add_input::<Complex32>("in_abs")
+                .add_input::<f32>("in_cor")
+                .add_output::<Complex32>("out")
                 .build(),
             MessageIoBuilder::new().build(),
             Self {
@@ -54,10 +54,10 @@ impl Kernel for SyncShort {
         _m: &mut MessageIo<Self>,
         _b: &mut BlockMeta,
     ) -> Result<()> {
-        let in_sig = sio.input(0).slice::<Complex32>();
-        let in_abs = sio.input(1).slice::<Complex32>();
-        let in_cor = sio.input(2).slice::<f32>();
-        let out = sio.output(0).slice::<Complex32>();
+        let in_sig = unsafe { sio.input(0).slice_unchecked::<Complex32>() };
+        let in_abs = unsafe { sio.input(1).slice_unchecked::<Complex32>() };
+        let in_cor = unsafe { sio.input(2).slice_unchecked::<f32>() };
+        let out = unsafe { sio.output(0).slice_unchecked::<Complex32>() };
 
         let n_input = std::cmp::min(std::cmp::min(in_sig.len(), in_abs.len()), in_cor.len());
 
@@ -32,8 +32,8 @@ impl ClockRecoveryMm {
         Block::new(
             BlockMetaBuilder::new("ClockRecoveryMm").build(),
             StreamIoBuilder::new()
-                .add_input("in", std::mem::size_of::<f32>())
-                .add_output("out", std::mem::size_of::<f32>())
+                .add_input::<f32>("in")
+                .add_output::<f32>("out")
                 .build(),
             MessageIoBuilder::<Self>::new().build(),
             Self {
@@ -67,8 +67,8 @@ impl Kernel for ClockRecoveryMm {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let i = sio.input(0).slice::<f32>();
-        let o = sio.output(0).slice::<f32>();
+        let i = unsafe { sio.input(0).slice_unchecked::<f32>() };
+        let o = unsafe { sio.output(0).slice_unchecked::<f32>() };
 
         let mut ii = 0;
         let mut oo = 0;
@@ -59,9 +59,7 @@ impl Decoder {
     pub fn new(threshold: u32) -> Block {
         Block::new(
             BlockMetaBuilder::new("Decoder").build(),
-            StreamIoBuilder::new()
-                .add_input("in", std::mem::size_of::<f32>())
-                .build(),
+            StreamIoBuilder::new().add_input::<f32>("in").build(),
             MessageIoBuilder::<Self>::new().add_output("out").build(),
             Self {
                 threshold,
@@ -88,7 +86,7 @@ impl Kernel for Decoder {
         mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let inbuf = sio.input(0).slice::<f32>();
+        let inbuf = unsafe { sio.input(0).slice_unchecked::<f32>() };
         let mut i = 0;
 
         while i < inbuf.len() {
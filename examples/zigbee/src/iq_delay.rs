@@ -34,8 +34,8 @@ impl IqDelay {
         Block::new(
             BlockMetaBuilder::new("IQ Delay").build(),
             StreamIoBuilder::new()
-                .add_input("in", std::mem::size_of::<Complex32>())
-                .add_output("out", std::mem::size_of::<Complex32>())
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
                 .build(),
             MessageIoBuilder::new().build(),
             Self {
@@ -55,8 +55,8 @@ impl Kernel for IqDelay {
         _m: &mut MessageIo<Self>,
         _b: &mut BlockMeta,
     ) -> Result<()> {
-        let i = sio.input(0).slice::<Complex32>();
-        let o = sio.output(0).slice::<Complex32>();
+        let i = unsafe { sio.input(0).slice_unchecked::<Complex32>() };
+        let o = unsafe { sio.output(0).slice_unchecked::<Complex32>() };
 
         let mut consumed = 0;
         let mut produced = 0;
@@ -63,7 +63,7 @@ impl Mac {
 
         Block::new(
             BlockMetaBuilder::new("Mac").build(),
-            StreamIoBuilder::new().add_output("out", 1).build(),
+            StreamIoBuilder::new().add_output::<u8>("out").build(),
             MessageIoBuilder::new()
                 .add_input("rx", Self::received)
                 .add_input("tx", Self::transmit)
@@ -114,8 +114,8 @@ impl Mac {
         p: Pmt,
     ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
         async move {
-            match p {
-                Pmt::Blob(data) => {
+            match Vec::<u8>::try_from(p) {
+                Ok(data) => {
                     if Self::check_crc(&data) && data.len() > 2 {
                         debug!("received frame, crc correct, payload length {}", data.len());
                         #[cfg(target_arch = "wasm32")]
@@ -140,10 +140,8 @@ impl Mac {
                         debug!("received frame, crc wrong");
                     }
                 }
-                _ => {
-                    warn!(
-                        "ZigBee Mac: received wrong PMT type in RX callback (expected Pmt::Blob)"
-                    );
+                Err(e) => {
+                    warn!("ZigBee Mac: received wrong PMT type in RX callback ({})", e);
                 }
             }
             Ok(Pmt::Null)
@@ -158,8 +156,8 @@ impl Mac {
         p: Pmt,
     ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
         async move {
-            match p {
-                Pmt::Blob(data) => {
+            match Vec::<u8>::try_from(p) {
+                Ok(data) => {
                     if self.tx_frames.len() >= MAX_FRAMES {
                         warn!(
                             "ZigBee Mac: max number of frames already in TX queue ({}). Dropping.",
@@ -178,10 +176,8 @@ impl Mac {
                         }
                     }
                 }
-                _ => {
-                    warn!(
-                        "ZigBee Mac: received wrong PMT type in TX callback (expected Pmt::Blob)"
-                    );
+                Err(e) => {
+                    warn!("ZigBee Mac: received wrong PMT type in TX callback ({})", e);
                 }
             }
             Ok(Pmt::Null)
@@ -195,7 +191,7 @@ impl Mac {
         _meta: &'a mut BlockMeta,
         _p: Pmt,
     ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
-        async move { Ok(Pmt::VecU64(vec![self.n_sent, self.n_received])) }.boxed()
+        async move { Ok(Pmt::from(vec![self.n_sent, self.n_received])) }.boxed()
     }
 }
 
@@ -209,7 +205,7 @@ impl Kernel for Mac {
         _b: &mut BlockMeta,
     ) -> Result<()> {
         loop {
-            let out = sio.output(0).slice::<u8>();
+            let out = unsafe { sio.output(0).slice_unchecked::<u8>() };
             if out.is_empty() {
                 break;
             }
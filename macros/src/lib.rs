@@ -0,0 +1,309 @@
+//! Macros to reduce the boilerplate of FutureSDR blocks.
+//!
+//! These macros are re-exported by `futuresdr::macros` and should be used through this path.
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::Data;
+use syn::DeriveInput;
+use syn::Error;
+use syn::Expr;
+use syn::FnArg;
+use syn::GenericArgument;
+use syn::GenericParam;
+use syn::Ident;
+use syn::ImplItemFn;
+use syn::Lifetime;
+use syn::LifetimeParam;
+use syn::Lit;
+use syn::Meta;
+use syn::PathArguments;
+use syn::ReturnType;
+use syn::Token;
+use syn::Type;
+
+/// Derive a constructor for a [`Block`](../futuresdr/runtime/struct.Block.html) from a kernel
+/// struct.
+///
+/// The derive adds an `into_block(self)` method that builds the block meta data, the stream ports,
+/// and the message ports, and wraps the kernel in a block. The kernel still has to implement
+/// `Kernel`.
+///
+/// * `#[input]`/`#[output]` on a `PhantomData<T>` field declares a stream port with item type `T`.
+///   The port is named after the field, unless it is set with `#[input(name = "in")]`.
+/// * `#[message_inputs(a, b = handler)]` declares message inputs. Handlers are methods with the
+///   name of the port or the given name, usually written with [`macro@message_handler`].
+/// * `#[message_outputs(a, b)]` declares message outputs.
+/// * `#[blocking]` marks the block as blocking.
+/// * `#[type_name(Name)]` sets the type name of the block. It defaults to the name of the struct.
+/// * `#[tag_propagation(OneToOne)]` sets the `TagPropagation` policy of the block.
+///
+/// ```ignore
+/// #[derive(Block)]
+/// #[message_inputs(r#in = forward)]
+/// #[message_outputs(out)]
+/// struct Forward {
+///     #[input(name = "in")]
+///     input: PhantomData<f32>,
+///     #[output]
+///     out: PhantomData<f32>,
+/// }
+/// ```
+#[proc_macro_derive(
+    Block,
+    attributes(
+        input,
+        output,
+        message_inputs,
+        message_outputs,
+        blocking,
+        type_name,
+        tag_propagation
+    )
+)]
+pub fn derive_block(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match block(input) {
+        Ok(t) => t.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn block(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let mut type_name = name.to_string();
+    let mut blocking = false;
+    let mut message_inputs = Vec::new();
+    let mut message_outputs = Vec::new();
+    let mut tag_propagation = None;
+
+    for attr in &input.attrs {
+        if attr.path().is_ident("blocking") {
+            attr.meta.require_path_only()?;
+            blocking = true;
+        } else if attr.path().is_ident("type_name") {
+            type_name = attr.parse_args::<Ident>()?.unraw().to_string();
+        } else if attr.path().is_ident("message_inputs") {
+            for meta in attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)? {
+                match meta {
+                    Meta::Path(p) => {
+                        let ident = p.require_ident()?.clone();
+                        message_inputs.push((ident.unraw().to_string(), ident));
+                    }
+                    Meta::NameValue(nv) => {
+                        let port = nv.path.require_ident()?.unraw().to_string();
+                        match nv.value {
+                            Expr::Path(p) => {
+                                message_inputs.push((port, p.path.require_ident()?.clone()))
+                            }
+                            e => return Err(Error::new(e.span(), "expected handler method")),
+                        }
+                    }
+                    m => return Err(Error::new(m.span(), "expected `port` or `port = handler`")),
+                }
+            }
+        } else if attr.path().is_ident("tag_propagation") {
+            let policy = attr.parse_args::<Expr>()?;
+            tag_propagation = Some(quote! {
+                .tag_propagation_policy(::futuresdr::runtime::TagPropagation::#policy)
+            });
+        } else if attr.path().is_ident("message_outputs") {
+            let ports = attr.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)?;
+            message_outputs.extend(ports.iter().map(|i| i.unraw().to_string()));
+        }
+    }
+
+    let fields = match &input.data {
+        Data::Struct(s) => &s.fields,
+        _ => {
+            return Err(Error::new(
+                name.span(),
+                "Block can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut stream_ports = Vec::new();
+    for field in fields {
+        for attr in &field.attrs {
+            let method = if attr.path().is_ident("input") {
+                quote!(add_input)
+            } else if attr.path().is_ident("output") {
+                quote!(add_output)
+            } else {
+                continue;
+            };
+
+            let port = match &attr.meta {
+                Meta::Path(_) => match &field.ident {
+                    Some(i) => i.unraw().to_string(),
+                    None => {
+                        return Err(Error::new(
+                            field.span(),
+                            "stream ports of tuple structs need a name",
+                        ))
+                    }
+                },
+                Meta::List(l) => {
+                    let nv = l.parse_args::<syn::MetaNameValue>()?;
+                    if !nv.path.is_ident("name") {
+                        return Err(Error::new(nv.path.span(), "expected `name = \"...\"`"));
+                    }
+                    match nv.value {
+                        Expr::Lit(syn::ExprLit {
+                            lit: Lit::Str(s), ..
+                        }) => s.value(),
+                        e => return Err(Error::new(e.span(), "expected string literal")),
+                    }
+                }
+                m => return Err(Error::new(m.span(), "expected `name = \"...\"`")),
+            };
+
+            let item = phantom_type(&field.ty)?;
+            stream_ports.push(quote!(.#method::<#item>(#port)));
+        }
+    }
+
+    let meta = if blocking {
+        quote!(::futuresdr::runtime::BlockMetaBuilder::new(#type_name).blocking().build())
+    } else {
+        quote!(::futuresdr::runtime::BlockMetaBuilder::new(#type_name).build())
+    };
+    let message_inputs = message_inputs
+        .iter()
+        .map(|(port, handler)| quote!(.add_input(#port, Self::#handler)));
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Create a block from the kernel.
+            pub fn into_block(self) -> ::futuresdr::runtime::Block {
+                ::futuresdr::runtime::Block::new(
+                    #meta,
+                    ::futuresdr::runtime::StreamIoBuilder::new()
+                        #(#stream_ports)*
+                        #tag_propagation
+                        .build(),
+                    ::futuresdr::runtime::MessageIoBuilder::<Self>::new()
+                        #(#message_inputs)*
+                        #(.add_output(#message_outputs))*
+                        .build(),
+                    self,
+                )
+            }
+        }
+    })
+}
+
+/// Get `T` from `PhantomData<T>`.
+fn phantom_type(ty: &Type) -> syn::Result<&Type> {
+    if let Type::Path(p) = ty {
+        if let Some(s) = p.path.segments.last() {
+            if s.ident == "PhantomData" {
+                if let PathArguments::AngleBracketed(a) = &s.arguments {
+                    if let Some(GenericArgument::Type(t)) = a.args.first() {
+                        return Ok(t);
+                    }
+                }
+            }
+        }
+    }
+    Err(Error::new(
+        ty.span(),
+        "stream ports have to be of type PhantomData<T>",
+    ))
+}
+
+/// Turn an async method into a message handler.
+///
+/// The method takes the kernel, the `MessageIo`, the `BlockMeta`, and the `Pmt` and returns a
+/// `Result<Pmt>`. It is rewritten to return a boxed future, as expected by `MessageIoBuilder`.
+///
+/// ```ignore
+/// #[message_handler]
+/// async fn forward(
+///     &mut self,
+///     mio: &mut MessageIo<Self>,
+///     _meta: &mut BlockMeta,
+///     p: Pmt,
+/// ) -> Result<Pmt> {
+///     mio.post(0, p).await;
+///     Ok(Pmt::Null)
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn message_handler(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        let e = Error::new(Span::call_site(), "message_handler takes no arguments");
+        return e.to_compile_error().into();
+    }
+    let item = syn::parse_macro_input!(item as ImplItemFn);
+    match handler(item) {
+        Ok(t) => t.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn handler(mut item: ImplItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let sig = &mut item.sig;
+    if sig.asyncness.take().is_none() {
+        return Err(Error::new(
+            sig.fn_token.span(),
+            "message handlers have to be async",
+        ));
+    }
+    if sig.inputs.len() != 4 {
+        return Err(Error::new(
+            sig.inputs.span(),
+            "message handlers take &mut self, &mut MessageIo<Self>, &mut BlockMeta, and Pmt",
+        ));
+    }
+
+    let lifetime = Lifetime::new("'a", Span::call_site());
+    sig.generics.params.insert(
+        0,
+        GenericParam::Lifetime(LifetimeParam::new(lifetime.clone())),
+    );
+
+    for arg in sig.inputs.iter_mut() {
+        match arg {
+            FnArg::Receiver(r) => match &mut r.reference {
+                Some((_, l)) => {
+                    *l = Some(lifetime.clone());
+                    if let Type::Reference(t) = r.ty.as_mut() {
+                        t.lifetime = Some(lifetime.clone());
+                    }
+                }
+                None => return Err(Error::new(r.span(), "expected &mut self")),
+            },
+            FnArg::Typed(t) => {
+                if let Type::Reference(r) = t.ty.as_mut() {
+                    r.lifetime = Some(lifetime.clone());
+                }
+            }
+        }
+    }
+
+    let output = match &sig.output {
+        ReturnType::Type(_, t) => t.clone(),
+        ReturnType::Default => return Err(Error::new(sig.span(), "expected Result<Pmt>")),
+    };
+    sig.output = syn::parse_quote! {
+        -> ::std::pin::Pin<::std::boxed::Box<
+            dyn ::std::future::Future<Output = #output> + ::std::marker::Send + #lifetime
+        >>
+    };
+
+    let block = &item.block;
+    item.block = syn::parse_quote!({
+        ::std::boxed::Box::pin(async move {
+            let ret: #output = #block;
+            ret
+        })
+    });
+
+    Ok(quote!(#item))
+}
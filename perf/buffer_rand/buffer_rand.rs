@@ -6,6 +6,8 @@ use futuresdr::blocks::CopyRandBuilder;
 use futuresdr::blocks::Head;
 use futuresdr::blocks::NullSink;
 use futuresdr::blocks::NullSource;
+use futuresdr::runtime::buffer::circular::Circular;
+use futuresdr::runtime::buffer::ring::Ring;
 use futuresdr::runtime::buffer::slab::Slab;
 use futuresdr::runtime::scheduler::FlowScheduler;
 use futuresdr::runtime::scheduler::SmolScheduler;
@@ -18,12 +20,13 @@ fn connect(
     src_port: &'static str,
     dst: usize,
     dst_port: &'static str,
-    slab: bool,
+    buffer: &str,
 ) -> Result<()> {
-    if slab {
-        fg.connect_stream_with_type(src, src_port, dst, dst_port, Slab::new())
-    } else {
-        fg.connect_stream(src, src_port, dst, dst_port)
+    match buffer {
+        "circ" => fg.connect_stream_with_type(src, src_port, dst, dst_port, Circular::new()),
+        "slab" => fg.connect_stream_with_type(src, src_port, dst, dst_port, Slab::new()),
+        "ring" => fg.connect_stream_with_type(src, src_port, dst, dst_port, Ring::new()),
+        _ => panic!("unknown buffer"),
     }
 }
 
@@ -84,11 +87,13 @@ fn main() -> Result<()> {
                 .help("Sets the scheduler."),
         )
         .arg(
-            Arg::new("slab")
+            Arg::new("buffer")
                 .short('b')
-                .long("slab")
-                .takes_value(false)
-                .help("Use Slab buffers."),
+                .long("buffer")
+                .takes_value(true)
+                .value_name("BUFFER")
+                .default_value("circ")
+                .help("Sets the buffer (circ, slab, or ring)."),
         )
         .get_matches();
 
@@ -98,7 +103,7 @@ fn main() -> Result<()> {
     let samples: usize = matches.value_of_t("samples").context("no samples")?;
     let max_copy: usize = matches.value_of_t("max_copy").context("no max_copy")?;
     let scheduler: String = matches.value_of_t("scheduler").context("no scheduler")?;
-    let slab: bool = matches.is_present("slab");
+    let buffer: String = matches.value_of_t("buffer").context("no buffer")?;
 
     let mut fg = Flowgraph::new();
     let mut snks = Vec::new();
@@ -106,19 +111,19 @@ fn main() -> Result<()> {
     for _ in 0..pipes {
         let src = fg.add_block(NullSource::<f32>::new());
         let head = fg.add_block(Head::<f32>::new(samples as u64));
-        connect(&mut fg, src, "out", head, "in", slab)?;
+        connect(&mut fg, src, "out", head, "in", &buffer)?;
 
         let mut last = fg.add_block(CopyRandBuilder::<f32>::new().max_copy(max_copy).build());
-        connect(&mut fg, head, "out", last, "in", slab)?;
+        connect(&mut fg, head, "out", last, "in", &buffer)?;
 
         for _ in 1..stages {
             let block = fg.add_block(CopyRandBuilder::<f32>::new().max_copy(max_copy).build());
-            connect(&mut fg, last, "out", block, "in", slab)?;
+            connect(&mut fg, last, "out", block, "in", &buffer)?;
             last = block;
         }
 
         let snk = fg.add_block(NullSink::<f32>::new());
-        connect(&mut fg, last, "out", snk, "in", slab)?;
+        connect(&mut fg, last, "out", snk, "in", &buffer)?;
         snks.push(snk);
     }
 
@@ -157,7 +162,7 @@ fn main() -> Result<()> {
         samples,
         max_copy,
         scheduler,
-        if slab { "slab" } else { "circ" },
+        buffer,
         elapsed.as_secs_f64()
     );
 
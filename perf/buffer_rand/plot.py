@@ -42,6 +42,17 @@ ax.errorbar(t['stages'], t[('time', 'mean')], yerr=t[('time', 'conf_int')], labe
 t = d.loc[('slab', 'flow')].reset_index();
 ax.errorbar(t['stages'], t[('time', 'mean')], yerr=t[('time', 'conf_int')], label='Slab/Flow', ls=':')
 
+ax.set_prop_cycle(None)
+
+t = d.loc[('ring', 'smol1')].reset_index();
+ax.errorbar(t['stages'], t[('time', 'mean')], yerr=t[('time', 'conf_int')], label='Ring/Smol-1', ls='--')
+
+t = d.loc[('ring', 'smoln')].reset_index();
+ax.errorbar(t['stages'], t[('time', 'mean')], yerr=t[('time', 'conf_int')], label='Ring/Smol-N', ls='--')
+
+t = d.loc[('ring', 'flow')].reset_index();
+ax.errorbar(t['stages'], t[('time', 'mean')], yerr=t[('time', 'conf_int')], label='Ring/Flow', ls='--')
+
 plt.setp(ax.get_yticklabels(), rotation=90, va="center")
 ax.set_xlabel('\#\,Stages')
 ax.set_ylabel('Execution Time (in s)')
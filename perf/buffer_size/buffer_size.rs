@@ -8,6 +8,7 @@ use futuresdr::blocks::Head;
 use futuresdr::blocks::NullSink;
 use futuresdr::blocks::NullSource;
 use futuresdr::runtime::buffer::circular::Circular;
+use futuresdr::runtime::buffer::ring::Ring;
 use futuresdr::runtime::buffer::slab::Slab;
 use futuresdr::runtime::scheduler::FlowScheduler;
 use futuresdr::runtime::scheduler::SmolScheduler;
@@ -20,13 +21,24 @@ fn connect(
     src_port: &'static str,
     dst: usize,
     dst_port: &'static str,
-    slab: bool,
+    buffer: &str,
     min_bytes: usize,
 ) -> Result<()> {
-    if slab {
-        fg.connect_stream_with_type(src, src_port, dst, dst_port, Slab::with_size(min_bytes))
-    } else {
-        fg.connect_stream_with_type(src, src_port, dst, dst_port, Circular::with_size(min_bytes))
+    match buffer {
+        "circ" => fg.connect_stream_with_type(
+            src,
+            src_port,
+            dst,
+            dst_port,
+            Circular::with_size(min_bytes),
+        ),
+        "slab" => {
+            fg.connect_stream_with_type(src, src_port, dst, dst_port, Slab::with_size(min_bytes))
+        }
+        "ring" => {
+            fg.connect_stream_with_type(src, src_port, dst, dst_port, Ring::with_size(min_bytes))
+        }
+        _ => panic!("unknown buffer"),
     }
 }
 
@@ -86,10 +98,12 @@ fn main() -> Result<()> {
                 .help("Sets the scheduler."),
         )
         .arg(
-            Arg::new("slab")
-                .long("slab")
-                .takes_value(false)
-                .help("Use Slab buffers."),
+            Arg::new("buffer")
+                .long("buffer")
+                .takes_value(true)
+                .value_name("BUFFER")
+                .default_value("circ")
+                .help("Sets the buffer (circ, slab, or ring)."),
         )
         .get_matches();
 
@@ -101,7 +115,7 @@ fn main() -> Result<()> {
         .value_of_t("buffer_size")
         .context("no buffer_size")?;
     let scheduler: String = matches.value_of_t("scheduler").context("no scheduler")?;
-    let slab: bool = matches.is_present("slab");
+    let buffer: String = matches.value_of_t("buffer").context("no buffer")?;
 
     let mut fg = Flowgraph::new();
     let mut snks = Vec::new();
@@ -109,19 +123,19 @@ fn main() -> Result<()> {
     for _ in 0..pipes {
         let src = fg.add_block(NullSource::<f32>::new());
         let head = fg.add_block(Head::<f32>::new(samples as u64));
-        connect(&mut fg, src, "out", head, "in", slab, buffer_size)?;
+        connect(&mut fg, src, "out", head, "in", &buffer, buffer_size)?;
 
         let mut last = fg.add_block(CopyRand::<f32>::new(1024));
-        connect(&mut fg, head, "out", last, "in", slab, buffer_size)?;
+        connect(&mut fg, head, "out", last, "in", &buffer, buffer_size)?;
 
         for _ in 1..stages {
             let block = fg.add_block(CopyRand::<f32>::new(1024));
-            connect(&mut fg, last, "out", block, "in", slab, buffer_size)?;
+            connect(&mut fg, last, "out", block, "in", &buffer, buffer_size)?;
             last = block;
         }
 
         let snk = fg.add_block(NullSink::<f32>::new());
-        connect(&mut fg, last, "out", snk, "in", slab, buffer_size)?;
+        connect(&mut fg, last, "out", snk, "in", &buffer, buffer_size)?;
         snks.push(snk);
     }
 
@@ -160,7 +174,7 @@ fn main() -> Result<()> {
         samples,
         buffer_size,
         scheduler,
-        if slab { "slab" } else { "circ" },
+        buffer,
         elapsed.as_secs_f64()
     );
 
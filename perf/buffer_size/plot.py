@@ -27,6 +27,9 @@ ax.errorbar(t['buffer_size'], t[('time', 'mean')], yerr=t[('time', 'conf_int')],
 t = d.loc[('slab')].reset_index();
 ax.errorbar(t['buffer_size'], t[('time', 'mean')], yerr=t[('time', 'conf_int')], label='Slab')
 
+t = d.loc[('ring')].reset_index();
+ax.errorbar(t['buffer_size'], t[('time', 'mean')], yerr=t[('time', 'conf_int')], label='Ring')
+
 plt.setp(ax.get_yticklabels(), rotation=90, va="center")
 ax.set_xlabel('Buffer Size (in bytes)')
 ax.set_ylabel('Execution Time (in s)')
@@ -0,0 +1,218 @@
+use num_complex::Complex32;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Map;
+use serde_json::Number;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::Pmt;
+use crate::PmtKind;
+
+/// Error converting a [`Pmt`] from or to a Rust type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PmtConversionError {
+    /// The Pmt is of a different kind.
+    Kind { expected: PmtKind, found: PmtKind },
+    /// The value could not be (de-)serialized.
+    Serde(String),
+}
+
+impl fmt::Display for PmtConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PmtConversionError::Kind { expected, found } => {
+                write!(f, "expected Pmt of kind {expected:?}, found {found:?}")
+            }
+            PmtConversionError::Serde(e) => write!(f, "Pmt serialization failed: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PmtConversionError {}
+
+fn kind_error(expected: PmtKind, p: &Pmt) -> PmtConversionError {
+    PmtConversionError::Kind {
+        expected,
+        found: p.kind(),
+    }
+}
+
+macro_rules! impl_from {
+    ($t:ty, $v:ident) => {
+        impl From<$t> for Pmt {
+            fn from(v: $t) -> Self {
+                Pmt::$v(v)
+            }
+        }
+    };
+}
+
+impl_from!(String, String);
+impl_from!(bool, Bool);
+impl_from!(u32, U32);
+impl_from!(u64, U64);
+impl_from!(i32, I32);
+impl_from!(i64, I64);
+impl_from!(f32, F32);
+impl_from!(f64, F64);
+impl_from!(Vec<f32>, VecF32);
+impl_from!(Vec<u64>, VecU64);
+impl_from!(Vec<Complex32>, VecCF32);
+impl_from!(Vec<Pmt>, VecPmt);
+impl_from!(HashMap<String, Pmt>, MapStrPmt);
+impl_from!(Vec<u8>, Blob);
+
+impl From<()> for Pmt {
+    fn from(_: ()) -> Self {
+        Pmt::Null
+    }
+}
+
+impl From<&str> for Pmt {
+    fn from(v: &str) -> Self {
+        Pmt::String(v.to_string())
+    }
+}
+
+/// Scalars also convert from Pmts of narrower types, if this is lossless.
+///
+/// Floats also convert from 64-bit integers, e.g., frequencies that were deserialized from JSON,
+/// which rounds values above 2^53.
+macro_rules! impl_try_from_scalar {
+    ($t:ty, $kind:ident, $($v:ident),+ $(; lossy $($l:ident),+)?) => {
+        impl TryFrom<&Pmt> for $t {
+            type Error = PmtConversionError;
+
+            fn try_from(p: &Pmt) -> Result<$t, Self::Error> {
+                match p {
+                    $(Pmt::$v(v) => Ok(<$t>::from(*v)),)+
+                    $($(Pmt::$l(v) => Ok(*v as $t),)+)?
+                    _ => Err(kind_error(PmtKind::$kind, p)),
+                }
+            }
+        }
+
+        impl TryFrom<Pmt> for $t {
+            type Error = PmtConversionError;
+
+            fn try_from(p: Pmt) -> Result<$t, Self::Error> {
+                <$t>::try_from(&p)
+            }
+        }
+    };
+}
+
+impl_try_from_scalar!(bool, Bool, Bool);
+impl_try_from_scalar!(u32, U32, U32);
+impl_try_from_scalar!(u64, U64, U32, U64);
+impl_try_from_scalar!(i32, I32, I32);
+impl_try_from_scalar!(i64, I64, U32, I32, I64);
+impl_try_from_scalar!(f32, F32, F32);
+impl_try_from_scalar!(f64, F64, U32, I32, F32, F64; lossy U64, I64);
+
+macro_rules! impl_try_from {
+    ($t:ty, $v:ident) => {
+        impl TryFrom<Pmt> for $t {
+            type Error = PmtConversionError;
+
+            fn try_from(p: Pmt) -> Result<$t, Self::Error> {
+                match p {
+                    Pmt::$v(v) => Ok(v),
+                    p => Err(kind_error(PmtKind::$v, &p)),
+                }
+            }
+        }
+    };
+}
+
+impl_try_from!(String, String);
+impl_try_from!(Vec<f32>, VecF32);
+impl_try_from!(Vec<u64>, VecU64);
+impl_try_from!(Vec<Complex32>, VecCF32);
+impl_try_from!(Vec<Pmt>, VecPmt);
+impl_try_from!(HashMap<String, Pmt>, MapStrPmt);
+impl_try_from!(Vec<u8>, Blob);
+
+impl Pmt {
+    /// Convert a serializable value into a Pmt.
+    ///
+    /// Structs and maps become [`Pmt::MapStrPmt`], sequences [`Pmt::VecPmt`]. Unsigned integers
+    /// become [`Pmt::U64`], negative integers [`Pmt::I64`], and floats [`Pmt::F64`].
+    pub fn from_serializable<T: Serialize + ?Sized>(v: &T) -> Result<Pmt, PmtConversionError> {
+        let v = serde_json::to_value(v).map_err(|e| PmtConversionError::Serde(e.to_string()))?;
+        Ok(from_value(v))
+    }
+
+    /// Deserialize the Pmt into a Rust value, e.g., a struct created with
+    /// [`Pmt::from_serializable`].
+    pub fn deserialize_into<T: DeserializeOwned>(&self) -> Result<T, PmtConversionError> {
+        serde_json::from_value(to_value(self)?)
+            .map_err(|e| PmtConversionError::Serde(e.to_string()))
+    }
+}
+
+fn from_value(v: Value) -> Pmt {
+    match v {
+        Value::Null => Pmt::Null,
+        Value::Bool(b) => Pmt::Bool(b),
+        Value::Number(n) => {
+            if let Some(u) = n.as_u64() {
+                Pmt::U64(u)
+            } else if let Some(i) = n.as_i64() {
+                Pmt::I64(i)
+            } else {
+                Pmt::F64(n.as_f64().unwrap_or(f64::NAN))
+            }
+        }
+        Value::String(s) => Pmt::String(s),
+        Value::Array(a) => Pmt::VecPmt(a.into_iter().map(from_value).collect()),
+        Value::Object(o) => {
+            Pmt::MapStrPmt(o.into_iter().map(|(k, v)| (k, from_value(v))).collect())
+        }
+    }
+}
+
+fn float(f: f64) -> Result<Value, PmtConversionError> {
+    Number::from_f64(f)
+        .map(Value::Number)
+        .ok_or_else(|| PmtConversionError::Serde(format!("cannot represent {f}")))
+}
+
+fn to_value(p: &Pmt) -> Result<Value, PmtConversionError> {
+    Ok(match p {
+        Pmt::Null => Value::Null,
+        Pmt::String(s) => Value::from(s.clone()),
+        Pmt::Bool(b) => Value::from(*b),
+        Pmt::U32(v) => Value::from(*v),
+        Pmt::U64(v) => Value::from(*v),
+        Pmt::I32(v) => Value::from(*v),
+        Pmt::I64(v) => Value::from(*v),
+        Pmt::F32(v) => float(*v as f64)?,
+        Pmt::F64(v) => float(*v)?,
+        Pmt::VecF32(v) => Value::Array(
+            v.iter()
+                .map(|x| float(*x as f64))
+                .collect::<Result<_, _>>()?,
+        ),
+        Pmt::VecU64(v) => Value::from(v.clone()),
+        Pmt::VecCF32(v) => Value::Array(
+            v.iter()
+                .map(|c| Ok(Value::Array(vec![float(c.re as f64)?, float(c.im as f64)?])))
+                .collect::<Result<_, _>>()?,
+        ),
+        Pmt::VecPmt(v) => Value::Array(v.iter().map(to_value).collect::<Result<_, _>>()?),
+        Pmt::MapStrPmt(m) => Value::Object(
+            m.iter()
+                .map(|(k, v)| Ok((k.clone(), to_value(v)?)))
+                .collect::<Result<Map<_, _>, _>>()?,
+        ),
+        Pmt::Blob(v) => Value::from(v.clone()),
+        Pmt::Any(_) => {
+            return Err(PmtConversionError::Serde(
+                "Pmt::Any cannot be deserialized".to_string(),
+            ))
+        }
+    })
+}
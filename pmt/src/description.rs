@@ -1,10 +1,27 @@
 use serde::{Deserialize, Serialize};
 
+use crate::Pmt;
+use crate::PmtKind;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FlowgraphDescription {
     pub blocks: Vec<BlockDescription>,
     pub stream_edges: Vec<(usize, usize, usize, usize)>,
     pub message_edges: Vec<(usize, usize, usize, usize)>,
+    /// Buffers of the stream edges, in the order of `stream_edges`.
+    #[serde(default)]
+    pub stream_edge_buffers: Vec<BufferDescription>,
+}
+
+/// Buffer of a stream edge.
+///
+/// The capacity is the maximum number of items that can be queued in the buffer, which bounds
+/// the latency of the edge. It is `None`, if the buffer does not report it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BufferDescription {
+    pub type_name: String,
+    pub item_size: usize,
+    pub capacity: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,7 +31,60 @@ pub struct BlockDescription {
     pub instance_name: String,
     pub stream_inputs: Vec<String>,
     pub stream_outputs: Vec<String>,
+    /// Item types of the stream inputs, in the order of `stream_inputs`.
+    #[serde(default)]
+    pub stream_input_types: Vec<String>,
+    /// Item types of the stream outputs, in the order of `stream_outputs`.
+    #[serde(default)]
+    pub stream_output_types: Vec<String>,
     pub message_inputs: Vec<String>,
     pub message_outputs: Vec<String>,
     pub blocking: bool,
+    pub stats: BlockStats,
+}
+
+/// Performance counters of a running block.
+///
+/// Times are in nanoseconds. Port statistics are in the order of the ports in the
+/// [BlockDescription]. The fill level is the number of items available in the input buffer, when
+/// the block last accessed it. The message queue depth is the number of messages that were
+/// queued in the inbox of the block, when it last processed it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BlockStats {
+    pub work_calls: u64,
+    pub work_time: u64,
+    pub avg_work_time: u64,
+    pub blocked_time: u64,
+    pub items_consumed: Vec<u64>,
+    pub items_produced: Vec<u64>,
+    pub input_fill: Vec<usize>,
+    pub handler_calls: Vec<u64>,
+    pub message_queue_depth: usize,
+}
+
+/// Type of block that can be instantiated through a block registry.
+///
+/// Generic blocks list the supported stream item types, which are selected with the `item`
+/// parameter. The first item type is the default.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BlockSchema {
+    pub type_name: String,
+    pub item_types: Vec<String>,
+    pub stream_inputs: Vec<String>,
+    pub stream_outputs: Vec<String>,
+    pub message_inputs: Vec<String>,
+    pub message_outputs: Vec<String>,
+    pub parameters: Vec<ParameterSchema>,
+}
+
+/// Parameter of a [BlockSchema].
+///
+/// Parameters without default value are required. Numeric parameters can be restricted to an
+/// inclusive range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterSchema {
+    pub name: String,
+    pub kind: PmtKind,
+    pub default: Option<Pmt>,
+    pub range: Option<(f64, f64)>,
 }
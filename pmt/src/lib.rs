@@ -1,11 +1,20 @@
 use dyn_clone::DynClone;
+use num_complex::Complex32;
 use serde::{Deserialize, Serialize};
 use std::any::Any;
+use std::collections::HashMap;
 use std::fmt;
 
+mod convert;
+pub use convert::PmtConversionError;
+
 mod description;
 pub use description::BlockDescription;
+pub use description::BlockSchema;
+pub use description::BlockStats;
+pub use description::BufferDescription;
 pub use description::FlowgraphDescription;
+pub use description::ParameterSchema;
 
 pub trait PmtAny: Any + DynClone + Send + Sync + 'static {
     fn as_any(&self) -> &dyn Any;
@@ -49,6 +58,12 @@ pub enum Pmt {
     VecF32(Vec<f32>),
     VecU64(Vec<u64>),
     Blob(Vec<u8>),
+    Bool(bool),
+    I32(i32),
+    I64(i64),
+    VecCF32(Vec<Complex32>),
+    VecPmt(Vec<Pmt>),
+    MapStrPmt(HashMap<String, Pmt>),
     #[serde(skip)]
     Any(Box<dyn PmtAny>),
 }
@@ -65,6 +80,12 @@ impl PartialEq for Pmt {
             (Pmt::VecF32(x), Pmt::VecF32(y)) => x == y,
             (Pmt::VecU64(x), Pmt::VecU64(y)) => x == y,
             (Pmt::Blob(x), Pmt::Blob(y)) => x == y,
+            (Pmt::Bool(x), Pmt::Bool(y)) => x == y,
+            (Pmt::I32(x), Pmt::I32(y)) => x == y,
+            (Pmt::I64(x), Pmt::I64(y)) => x == y,
+            (Pmt::VecCF32(x), Pmt::VecCF32(y)) => x == y,
+            (Pmt::VecPmt(x), Pmt::VecPmt(y)) => x == y,
+            (Pmt::MapStrPmt(x), Pmt::MapStrPmt(y)) => x == y,
             _ => false,
         }
     }
@@ -82,44 +103,65 @@ impl Pmt {
         }
     }
 
+    /// Kind of the Pmt.
+    pub fn kind(&self) -> PmtKind {
+        match self {
+            Pmt::Null => PmtKind::Null,
+            Pmt::String(_) => PmtKind::String,
+            Pmt::U32(_) => PmtKind::U32,
+            Pmt::U64(_) => PmtKind::U64,
+            Pmt::F32(_) => PmtKind::F32,
+            Pmt::F64(_) => PmtKind::F64,
+            Pmt::VecF32(_) => PmtKind::VecF32,
+            Pmt::VecU64(_) => PmtKind::VecU64,
+            Pmt::Blob(_) => PmtKind::Blob,
+            Pmt::Bool(_) => PmtKind::Bool,
+            Pmt::I32(_) => PmtKind::I32,
+            Pmt::I64(_) => PmtKind::I64,
+            Pmt::VecCF32(_) => PmtKind::VecCF32,
+            Pmt::VecPmt(_) => PmtKind::VecPmt,
+            Pmt::MapStrPmt(_) => PmtKind::MapStrPmt,
+            Pmt::Any(_) => PmtKind::Any,
+        }
+    }
+
+    /// Parse a Pmt of the given kind.
+    ///
+    /// Vectors are comma-separated lists of their elements, complex values are written as
+    /// `1.5+2i`. `VecPmt`, `MapStrPmt`, and `Any` cannot be parsed.
     pub fn from_string(s: &str, t: &PmtKind) -> Option<Pmt> {
         match t {
-            PmtKind::U32 => {
-                if let Ok(v) = s.parse::<u32>() {
-                    Some(Pmt::U32(v))
-                } else {
-                    None
-                }
-            }
-            PmtKind::U64 => {
-                if let Ok(v) = s.parse::<u64>() {
-                    Some(Pmt::U64(v))
-                } else {
-                    None
-                }
-            }
-            PmtKind::F32 => {
-                if let Ok(v) = s.parse::<f32>() {
-                    Some(Pmt::F32(v))
-                } else {
-                    None
-                }
-            }
-            PmtKind::F64 => {
-                if let Ok(v) = s.parse::<f64>() {
-                    Some(Pmt::F64(v))
-                } else {
-                    None
-                }
-            }
+            PmtKind::Null if s.trim().is_empty() => Some(Pmt::Null),
             PmtKind::String => Some(Pmt::String(s.to_string())),
+            PmtKind::U32 => s.trim().parse().ok().map(Pmt::U32),
+            PmtKind::U64 => s.trim().parse().ok().map(Pmt::U64),
+            PmtKind::F32 => s.trim().parse().ok().map(Pmt::F32),
+            PmtKind::F64 => s.trim().parse().ok().map(Pmt::F64),
+            PmtKind::VecF32 => parse_vec(s).map(Pmt::VecF32),
+            PmtKind::VecU64 => parse_vec(s).map(Pmt::VecU64),
+            PmtKind::Blob => parse_vec(s).map(Pmt::Blob),
+            PmtKind::Bool => s.trim().parse().ok().map(Pmt::Bool),
+            PmtKind::I32 => s.trim().parse().ok().map(Pmt::I32),
+            PmtKind::I64 => s.trim().parse().ok().map(Pmt::I64),
+            PmtKind::VecCF32 => parse_vec(s).map(Pmt::VecCF32),
             _ => None,
         }
     }
 }
 
+fn parse_vec<T: std::str::FromStr>(s: &str) -> Option<Vec<T>> {
+    if s.trim().is_empty() {
+        return Some(Vec::new());
+    }
+    s.split(',').map(|v| v.trim().parse().ok()).collect()
+}
+
+/// Kind of a [`Pmt`], e.g., to describe the type of a parameter.
+///
+/// There is one kind for each variant of [`Pmt`]. `VecF64` has no [`Pmt`] counterpart and is
+/// only used to describe parameters.
 #[non_exhaustive]
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PmtKind {
     Null,
     String,
@@ -130,6 +172,13 @@ pub enum PmtKind {
     VecF32,
     VecF64,
     Blob,
+    Bool,
+    I32,
+    I64,
+    VecU64,
+    VecCF32,
+    VecPmt,
+    MapStrPmt,
     Any,
 }
 
@@ -159,6 +208,132 @@ mod test {
         assert_eq!(p, p2);
     }
 
+    #[test]
+    fn pmt_serde_nested() {
+        let p = Pmt::MapStrPmt(HashMap::from([
+            ("freq".to_string(), Pmt::F64(2.4e9)),
+            ("enabled".to_string(), Pmt::Bool(true)),
+            (
+                "frame".to_string(),
+                Pmt::VecPmt(vec![
+                    Pmt::I32(-1),
+                    Pmt::VecCF32(vec![Complex32::new(1.0, -1.0)]),
+                ]),
+            ),
+        ]));
+        let mut s = flexbuffers::FlexbufferSerializer::new();
+        p.serialize(&mut s).unwrap();
+
+        let r = flexbuffers::Reader::get_root(s.view()).unwrap();
+        let p2 = Pmt::deserialize(r).unwrap();
+        assert_eq!(p, p2);
+
+        let json = serde_json::to_string(&Pmt::VecCF32(vec![Complex32::new(1.0, 2.0)])).unwrap();
+        assert_eq!(json, r#"{"VecCF32":[[1.0,2.0]]}"#);
+        let p3: Pmt = serde_json::from_str(r#"{"VecPmt":[{"I64":-5},"Null"]}"#).unwrap();
+        assert_eq!(p3, Pmt::VecPmt(vec![Pmt::I64(-5), Pmt::Null]));
+    }
+
+    #[test]
+    fn pmt_kind() {
+        let pmts = [
+            Pmt::Null,
+            Pmt::String("foo".to_string()),
+            Pmt::Bool(false),
+            Pmt::U32(1),
+            Pmt::U64(1),
+            Pmt::I32(-1),
+            Pmt::I64(-1),
+            Pmt::F32(1.0),
+            Pmt::F64(1.0),
+            Pmt::VecF32(vec![1.0, 2.0]),
+            Pmt::VecU64(vec![1, 2]),
+            Pmt::VecCF32(vec![Complex32::new(1.0, -2.5)]),
+            Pmt::Blob(vec![1, 2]),
+        ];
+        let strings = [
+            "", "foo", "false", "1", "1", "-1", "-1", "1", "1.0", "1, 2", "1,2", "1-2.5i", "1,2",
+        ];
+        for (p, s) in pmts.iter().zip(strings) {
+            assert_eq!(Pmt::from_string(s, &p.kind()).as_ref(), Some(p));
+        }
+
+        assert_eq!(Pmt::VecPmt(Vec::new()).kind(), PmtKind::VecPmt);
+        assert_eq!(Pmt::MapStrPmt(HashMap::new()).kind(), PmtKind::MapStrPmt);
+        assert_eq!(Pmt::from_string("1", &PmtKind::VecPmt), None);
+        assert_eq!(Pmt::from_string("-1", &PmtKind::U32), None);
+        assert_eq!(Pmt::from_string("1, x", &PmtKind::VecF32), None);
+        assert_eq!(Pmt::from_string("yes", &PmtKind::Bool), None);
+    }
+
+    #[test]
+    fn pmt_convert() {
+        assert_eq!(Pmt::from(3u32), Pmt::U32(3));
+        assert_eq!(Pmt::from("foo"), Pmt::String("foo".to_string()));
+        assert_eq!(Pmt::from(vec![1u8, 2]), Pmt::Blob(vec![1, 2]));
+        assert_eq!(u32::try_from(Pmt::U32(3)), Ok(3));
+        assert_eq!(u64::try_from(&Pmt::U32(3)), Ok(3));
+        assert_eq!(f64::try_from(Pmt::U32(3)), Ok(3.0));
+        assert_eq!(f64::try_from(Pmt::U64(3)), Ok(3.0));
+        assert_eq!(f64::try_from(Pmt::I64(-3)), Ok(-3.0));
+        assert_eq!(i64::try_from(Pmt::I32(-3)), Ok(-3));
+        assert_eq!(
+            u32::try_from(Pmt::U64(3)),
+            Err(PmtConversionError::Kind {
+                expected: PmtKind::U32,
+                found: PmtKind::U64
+            })
+        );
+        assert_eq!(Vec::<u8>::try_from(Pmt::Blob(vec![1, 2])), Ok(vec![1, 2]));
+        assert!(Vec::<f32>::try_from(Pmt::Null).is_err());
+    }
+
+    #[test]
+    fn pmt_serializable() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Tune {
+            freq: f64,
+            gain: Option<u32>,
+            antenna: String,
+            taps: Vec<f32>,
+        }
+
+        let t = Tune {
+            freq: 2.4e9,
+            gain: Some(30),
+            antenna: "RX2".to_string(),
+            taps: vec![0.5, -0.25],
+        };
+        let p = Pmt::from_serializable(&t).unwrap();
+        match &p {
+            Pmt::MapStrPmt(m) => {
+                assert_eq!(m.get("freq"), Some(&Pmt::F64(2.4e9)));
+                assert_eq!(m.get("gain"), Some(&Pmt::U64(30)));
+            }
+            _ => panic!("expected map"),
+        }
+        assert_eq!(p.deserialize_into::<Tune>().unwrap(), t);
+
+        let p = Pmt::MapStrPmt(HashMap::from([
+            ("freq".to_string(), Pmt::U32(100)),
+            ("gain".to_string(), Pmt::Null),
+            ("antenna".to_string(), Pmt::from("A")),
+            ("taps".to_string(), Pmt::VecF32(vec![1.0])),
+        ]));
+        let t = p.deserialize_into::<Tune>().unwrap();
+        assert_eq!(t.freq, 100.0);
+        assert_eq!(t.gain, None);
+        assert_eq!(t.taps, vec![1.0]);
+        assert!(Pmt::Null.deserialize_into::<Tune>().is_err());
+
+        // integers, e.g., from the control port, are accepted for floats
+        let p: Pmt = serde_json::from_str(r#"{"U64": 100000000}"#).unwrap();
+        assert_eq!(f64::try_from(&p), Ok(1e8));
+        let p = Pmt::from_serializable(&serde_json::json!(-5)).unwrap();
+        assert_eq!(p, Pmt::I64(-5));
+        assert_eq!(f64::try_from(p), Ok(-5.0));
+    }
+
     #[allow(clippy::many_single_char_names)]
     #[test]
     fn pmt_eq() {
@@ -1,6 +1,5 @@
-use std::mem;
-
 use crate::anyhow::Result;
+use crate::runtime::buffer::inplace;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -9,6 +8,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 
 /// Apply a function to each sample.
@@ -21,6 +21,10 @@ use crate::runtime::WorkIo;
 ///
 /// `out`: Output after function applied
 ///
+/// If both ports are connected with [in-place buffers](crate::runtime::buffer::inplace::InPlace)
+/// and the output type is not larger than the input type, the function is applied in place and
+/// the buffers are forwarded without copying.
+///
 /// # Usage
 /// ```
 /// use futuresdr::blocks::Apply;
@@ -69,8 +73,9 @@ where
         Block::new(
             BlockMetaBuilder::new("Apply").build(),
             StreamIoBuilder::new()
-                .add_input("in", mem::size_of::<A>())
-                .add_output("out", mem::size_of::<B>())
+                .add_input::<A>("in")
+                .add_output::<B>("out")
+                .tag_propagation_policy(TagPropagation::OneToOne)
                 .build(),
             MessageIoBuilder::<Self>::new().build(),
             Apply {
@@ -97,10 +102,31 @@ where
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let i = sio.input(0).slice::<A>();
-        let o = sio.output(0).slice::<B>();
+        // buffers are taken as a whole, so use the slices, while a scheduled message limits the
+        // input
+        if std::mem::size_of::<B>() <= std::mem::size_of::<A>()
+            && !sio.input(0).limited()
+            && sio.input(0).try_as::<inplace::Reader>().is_some()
+            && sio.output(0).try_as::<inplace::Writer>().is_some()
+        {
+            while let Some(mut b) = sio.input(0).try_as::<inplace::Reader>().unwrap().take() {
+                let n = b.len::<A>();
+                // the ports are typed, i.e., the items of the input are of type A
+                unsafe { b.map(&mut self.f) };
+                sio.input(0).add_consumed(n);
+                sio.output(0).add_produced(n);
+                sio.output(0).try_as::<inplace::Writer>().unwrap().submit(b);
+            }
+            if sio.input(0).finished() {
+                io.finished = true;
+            }
+            return Ok(());
+        }
+
+        let (i, o) = sio.slices::<A, B>(0, 0);
+        let i_len = i.len();
 
-        let m = std::cmp::min(i.len(), o.len());
+        let m = std::cmp::min(i_len, o.len());
         if m > 0 {
             for (v, r) in i.iter().zip(o.iter_mut()) {
                 *r = (self.f)(v);
@@ -110,7 +136,7 @@ where
             sio.output(0).produce(m);
         }
 
-        if sio.input(0).finished() && m == i.len() {
+        if sio.input(0).finished() && m == i_len {
             io.finished = true;
         }
 
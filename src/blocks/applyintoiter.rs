@@ -1,5 +1,3 @@
-use std::mem;
-
 use crate::anyhow::Result;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
@@ -36,8 +34,8 @@ where
         Block::new(
             BlockMetaBuilder::new("ApplyIntoIter").build(),
             StreamIoBuilder::new()
-                .add_input("in", mem::size_of::<A>())
-                .add_output("out", mem::size_of::<B::Item>())
+                .add_input::<A>("in")
+                .add_output::<B::Item>("out")
                 .build(),
             MessageIoBuilder::<Self>::new().build(),
             ApplyIntoIter {
@@ -66,9 +64,12 @@ where
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let i = sio.input(0).slice::<A>();
-        let o = sio.output(0).slice::<B::Item>();
+        let (inputs, outputs) = sio.ports_mut();
+        let (i, tags) = inputs[0].slice_with_tags::<A>();
+        let o = outputs[0].slice::<B::Item>();
+        let (i_len, o_len) = (i.len(), o.len());
         let mut i_iter = i.iter();
+        let mut out_tags = Vec::new();
 
         let mut consumed = 0;
         let mut produced = 0;
@@ -79,14 +80,10 @@ where
                 produced += 1;
             } else if let Some(v) = i_iter.next() {
                 self.current_it = Box::new(((self.f)(v)).into_iter());
-                if let Some(ItemTag { tag, .. }) = sio
-                    .input(0)
-                    .tags()
-                    .iter()
-                    .find(|x| x.index == consumed)
-                    .cloned()
+                if let Some(ItemTag { tag, .. }) =
+                    tags.iter().find(|x| x.index == consumed).cloned()
                 {
-                    sio.output(0).add_tag(produced, tag);
+                    out_tags.push((produced, tag));
                 }
                 consumed += 1;
             } else {
@@ -94,9 +91,12 @@ where
             }
         }
 
+        for (index, tag) in out_tags {
+            sio.output(0).add_tag(index, tag);
+        }
         sio.input(0).consume(consumed);
         sio.output(0).produce(produced);
-        if sio.input(0).finished() && consumed == i.len() && produced < o.len() {
+        if sio.input(0).finished() && consumed == i_len && produced < o_len {
             io.finished = true;
         }
 
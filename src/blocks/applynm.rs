@@ -1,5 +1,3 @@
-use std::mem;
-
 use crate::anyhow::Result;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
@@ -9,6 +7,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 
 /// Apply a function to each N input samples, producing M output samples.
@@ -64,8 +63,12 @@ where
         Block::new(
             BlockMetaBuilder::new(format!("ApplyNM {} {}", N, M)).build(),
             StreamIoBuilder::new()
-                .add_input("in", mem::size_of::<A>())
-                .add_output("out", mem::size_of::<B>())
+                .add_input::<A>("in")
+                .add_output::<B>("out")
+                .tag_propagation_policy(TagPropagation::Rate {
+                    interp: M,
+                    decim: N,
+                })
                 .build(),
             MessageIoBuilder::<Self>::new().build(),
             ApplyNM {
@@ -92,8 +95,8 @@ where
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let i = sio.input(0).slice::<A>();
-        let o = sio.output(0).slice::<B>();
+        let (i, o) = sio.slices::<A, B>(0, 0);
+        let i_len = i.len();
 
         // See https://www.nickwilcox.com/blog/autovec/ for a discussion
         // on auto-vectorization of these types of functions.
@@ -107,7 +110,7 @@ where
             sio.output(0).produce(M * m);
         }
 
-        if sio.input(0).finished() && m == i.len() {
+        if sio.input(0).finished() && m == i_len {
             io.finished = true;
         }
 
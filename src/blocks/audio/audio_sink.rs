@@ -39,7 +39,7 @@ impl AudioSink {
     pub fn new(sample_rate: u32, channels: u16) -> Block {
         Block::new(
             BlockMetaBuilder::new("AudioSink").build(),
-            StreamIoBuilder::new().add_input("in", 4).build(),
+            StreamIoBuilder::new().add_input::<f32>("in").build(),
             MessageIoBuilder::new().build(),
             AudioSink {
                 sample_rate,
@@ -170,6 +170,7 @@ impl Kernel for AudioSink {
         _meta: &mut BlockMeta,
     ) -> Result<()> {
         let i = sio.input(0).slice::<f32>();
+        let n = i.len();
         self.vec.extend_from_slice(i);
 
         if self.vec.len() >= self.min_buffer_size {
@@ -180,7 +181,7 @@ impl Kernel for AudioSink {
                 .await?;
         }
 
-        sio.input(0).consume(i.len());
+        sio.input(0).consume(n);
 
         if sio.input(0).finished() {
             io.finished = true;
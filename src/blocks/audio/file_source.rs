@@ -27,7 +27,7 @@ impl FileSource {
 
         Block::new(
             BlockMetaBuilder::new("FileSource").build(),
-            StreamIoBuilder::new().add_output("out", 4).build(),
+            StreamIoBuilder::new().add_output::<f32>("out").build(),
             MessageIoBuilder::new().build(),
             FileSource {
                 src: Box::new(source.convert_samples()),
@@ -56,10 +56,12 @@ impl Kernel for FileSource {
     ) -> Result<()> {
         let out = sio.output(0).slice::<f32>();
 
-        for (i, v) in self.src.by_ref().take(out.len()).enumerate() {
+        let n = out.len();
+
+        for (i, v) in self.src.by_ref().take(n).enumerate() {
             out[i] = v;
         }
-        sio.output(0).produce(out.len());
+        sio.output(0).produce(n);
 
         Ok(())
     }
@@ -1,5 +1,3 @@
-use std::mem;
-
 use crate::anyhow::Result;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
@@ -9,6 +7,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 
 /// Apply a function to combine two streams into one.
@@ -59,9 +58,10 @@ where
         Block::new(
             BlockMetaBuilder::new("Combine").build(),
             StreamIoBuilder::new()
-                .add_input("in0", mem::size_of::<A>())
-                .add_input("in1", mem::size_of::<B>())
-                .add_output("out", mem::size_of::<C>())
+                .add_input::<A>("in0")
+                .add_input::<B>("in1")
+                .add_output::<C>("out")
+                .tag_propagation_policy(TagPropagation::AllToAll)
                 .build(),
             MessageIoBuilder::<Self>::new().build(),
             Combine {
@@ -90,9 +90,12 @@ where
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let i0 = sio.input(0).slice::<A>();
-        let i1 = sio.input(1).slice::<B>();
-        let o0 = sio.output(0).slice::<C>();
+        let (inputs, outputs) = sio.ports_mut();
+        let (in0, in1) = inputs.split_at_mut(1);
+        let i0 = in0[0].slice::<A>();
+        let i1 = in1[0].slice::<B>();
+        let o0 = outputs[0].slice::<C>();
+        let (i0_len, i1_len) = (i0.len(), i1.len());
 
         let m = std::cmp::min(i0.len(), i1.len());
         let m = std::cmp::min(m, o0.len());
@@ -107,11 +110,11 @@ where
             sio.output(0).produce(m);
         }
 
-        if sio.input(0).finished() && m == i0.len() {
+        if sio.input(0).finished() && m == i0_len {
             io.finished = true;
         }
 
-        if sio.input(1).finished() && m == i1.len() {
+        if sio.input(1).finished() && m == i1_len {
             io.finished = true;
         }
 
@@ -1,35 +1,33 @@
 use std::cmp;
+use std::marker::PhantomData;
 use std::ptr;
 
 use crate::anyhow::Result;
+use crate::macros::Block;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
-use crate::runtime::BlockMetaBuilder;
 use crate::runtime::Kernel;
 use crate::runtime::MessageIo;
-use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
-use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
 
 /// Copy input samples to the output.
+#[derive(Block)]
+#[tag_propagation(OneToOne)]
 pub struct Copy<T: Send + 'static> {
-    _type: std::marker::PhantomData<T>,
+    #[input(name = "in")]
+    input: PhantomData<T>,
+    #[output]
+    out: PhantomData<T>,
 }
 
 impl<T: Send + 'static> Copy<T> {
     pub fn new() -> Block {
-        Block::new(
-            BlockMetaBuilder::new("Copy").build(),
-            StreamIoBuilder::new()
-                .add_input("in", std::mem::size_of::<T>())
-                .add_output("out", std::mem::size_of::<T>())
-                .build(),
-            MessageIoBuilder::<Self>::new().build(),
-            Copy::<T> {
-                _type: std::marker::PhantomData,
-            },
-        )
+        Copy::<T> {
+            input: PhantomData,
+            out: PhantomData,
+        }
+        .into_block()
     }
 }
 
@@ -43,21 +41,20 @@ impl<T: Send + 'static> Kernel for Copy<T> {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let i = sio.input(0).slice::<u8>();
-        let o = sio.output(0).slice::<u8>();
-        let item_size = std::mem::size_of::<T>();
+        let (i, o) = sio.slices::<T, T>(0, 0);
+        let i_len = i.len();
 
-        let m = cmp::min(i.len(), o.len());
+        let m = cmp::min(i_len, o.len());
         if m > 0 {
             unsafe {
                 ptr::copy_nonoverlapping(i.as_ptr(), o.as_mut_ptr(), m);
             }
 
-            sio.input(0).consume(m / item_size);
-            sio.output(0).produce(m / item_size);
+            sio.input(0).consume(m);
+            sio.output(0).produce(m);
         }
 
-        if sio.input(0).finished() && m == i.len() {
+        if sio.input(0).finished() && m == i_len {
             io.finished = true;
         }
 
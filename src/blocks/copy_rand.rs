@@ -11,6 +11,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 
 /// Copy input samples to the output, forwarding only a randomly selected number of samples.
@@ -24,8 +25,9 @@ impl<T: Send + 'static> CopyRand<T> {
         Block::new(
             BlockMetaBuilder::new("CopyRand").build(),
             StreamIoBuilder::new()
-                .add_input("in", std::mem::size_of::<T>())
-                .add_output("out", std::mem::size_of::<T>())
+                .add_input::<T>("in")
+                .add_output::<T>("out")
+                .tag_propagation_policy(TagPropagation::OneToOne)
                 .build(),
             MessageIoBuilder::<Self>::new().build(),
             CopyRand::<T> {
@@ -46,20 +48,17 @@ impl<T: Send + 'static> Kernel for CopyRand<T> {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let i = sio.input(0).slice::<u8>();
-        let o = sio.output(0).slice::<u8>();
-        let item_size = std::mem::size_of::<T>();
-
-        let mut m = cmp::min(i.len(), o.len());
-        m /= item_size;
+        let (i, o) = sio.slices::<T, T>(0, 0);
+        let i_len = i.len();
 
+        let mut m = cmp::min(i_len, o.len());
         m = cmp::min(m, self.max_copy);
 
         if m > 0 {
             m = rand::random::<usize>() % m + 1;
 
             unsafe {
-                ptr::copy_nonoverlapping(i.as_ptr(), o.as_mut_ptr(), m * item_size);
+                ptr::copy_nonoverlapping(i.as_ptr(), o.as_mut_ptr(), m);
             }
 
             sio.input(0).consume(m);
@@ -67,7 +66,7 @@ impl<T: Send + 'static> Kernel for CopyRand<T> {
             io.call_again = true;
         }
 
-        if sio.input(0).finished() && m * item_size == i.len() {
+        if sio.input(0).finished() && m == i_len {
             io.finished = true;
         }
 
@@ -1,7 +1,6 @@
 use rustfft::num_complex::Complex32;
 use rustfft::{self, FftPlanner};
 use std::cmp;
-use std::mem::size_of;
 use std::sync::Arc;
 
 use crate::anyhow::Result;
@@ -13,6 +12,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 
 /// Compute an FFT.
@@ -71,12 +71,15 @@ impl Fft {
             FftDirection::Forward => planner.plan_fft_forward(len),
             FftDirection::Inverse => planner.plan_fft_inverse(len),
         };
+        let scratch =
+            vec![Complex32::new(0.0, 0.0); plan.get_inplace_scratch_len()].into_boxed_slice();
 
         Block::new(
-            BlockMetaBuilder::new("Fft").build(),
+            BlockMetaBuilder::new("Fft").min_items(len).build(),
             StreamIoBuilder::new()
-                .add_input("in", size_of::<Complex32>())
-                .add_output("out", size_of::<Complex32>())
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .tag_propagation_policy(TagPropagation::OneToOne)
                 .build(),
             MessageIoBuilder::<Fft>::new().build(),
             Fft {
@@ -85,7 +88,7 @@ impl Fft {
                 direction,
                 fft_shift,
                 normalize,
-                scratch: vec![Complex32::new(0.0, 0.0); len * 10].into_boxed_slice(),
+                scratch,
             },
         )
     }
@@ -101,8 +104,7 @@ impl Kernel for Fft {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let i = unsafe { sio.input(0).slice_mut::<Complex32>() };
-        let o = sio.output(0).slice::<Complex32>();
+        let (i, o) = sio.slices::<Complex32, Complex32>(0, 0);
 
         let m = cmp::min(i.len(), o.len());
         let m = (m / self.len) * self.len;
@@ -110,19 +112,17 @@ impl Kernel for Fft {
         if m > 0 {
             if matches!(self.direction, FftDirection::Inverse) && self.fft_shift {
                 for f in 0..(m / self.len) {
-                    let mut sym = vec![Complex32::new(0.0, 0.0); self.len];
-                    sym.copy_from_slice(&i[f * self.len..(f + 1) * self.len]);
+                    let sym = &i[f * self.len..(f + 1) * self.len];
                     for k in 0..self.len {
-                        i[f * self.len + k] = sym[(k + self.len / 2) % self.len]
+                        o[f * self.len + k] = sym[(k + self.len / 2) % self.len]
                     }
                 }
+            } else {
+                o[0..m].copy_from_slice(&i[0..m]);
             }
 
-            self.plan.process_outofplace_with_scratch(
-                &mut i[0..m],
-                &mut o[0..m],
-                &mut self.scratch,
-            );
+            self.plan
+                .process_with_scratch(&mut o[0..m], &mut self.scratch);
 
             if matches!(self.direction, FftDirection::Forward) && self.fft_shift {
                 for f in 0..(m / self.len) {
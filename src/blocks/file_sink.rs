@@ -11,6 +11,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
 use crate::runtime::WorkIo;
 
 /// Write samples to a file.
@@ -20,6 +21,9 @@ use crate::runtime::WorkIo;
 /// endian. Complex numbers are written with the real component coming before
 /// the complex component.
 ///
+/// Once the stream carries burst tags ([`Tag::TxSob`], [`Tag::TxEob`]), only samples that are
+/// part of a burst are written.
+///
 /// # Inputs
 ///
 /// `in`: Input
@@ -41,6 +45,8 @@ use crate::runtime::WorkIo;
 pub struct FileSink<T: Send + 'static> {
     file_name: String,
     file: Option<File>,
+    bursts: bool,
+    in_burst: bool,
     _type: std::marker::PhantomData<T>,
 }
 
@@ -48,13 +54,13 @@ impl<T: Send + 'static> FileSink<T> {
     pub fn new<S: Into<String>>(file_name: S) -> Block {
         Block::new(
             BlockMetaBuilder::new("FileSink").build(),
-            StreamIoBuilder::new()
-                .add_input("in", std::mem::size_of::<T>())
-                .build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
             MessageIoBuilder::new().build(),
             FileSink::<T> {
                 file_name: file_name.into(),
                 file: None,
+                bursts: false,
+                in_burst: false,
                 _type: std::marker::PhantomData,
             },
         )
@@ -71,13 +77,32 @@ impl<T: Send + 'static> Kernel for FileSink<T> {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let i = sio.input(0).slice::<u8>();
+        let (i, tags) = sio.input(0).slice_with_tags::<u8>();
 
         let item_size = std::mem::size_of::<T>();
         let items = i.len() / item_size;
 
-        if items > 0 {
-            let i = &i[..items * item_size];
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        for t in tags.iter().filter(|t| t.index < items) {
+            let end = match t.tag {
+                Tag::TxSob => t.index,
+                Tag::TxEob => t.index + 1,
+                _ => continue,
+            };
+            if !self.bursts || self.in_burst {
+                ranges.push(start..end);
+            }
+            self.bursts = true;
+            self.in_burst = matches!(t.tag, Tag::TxSob);
+            start = end;
+        }
+        if !self.bursts || self.in_burst {
+            ranges.push(start..items);
+        }
+
+        for r in ranges.into_iter().filter(|r| !r.is_empty()) {
+            let i = &i[r.start * item_size..r.end * item_size];
             match self.file.as_mut().unwrap().write_all(i).await {
                 Ok(()) => {}
                 Err(e) => panic!("FileSink: writing to {:?} failed: {:?}", self.file_name, e),
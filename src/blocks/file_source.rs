@@ -1,4 +1,5 @@
 use futures::AsyncReadExt;
+use std::time::Duration;
 
 use crate::anyhow::Result;
 use crate::runtime::Block;
@@ -9,6 +10,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
 use crate::runtime::WorkIo;
 
 /// Read samples from a file.
@@ -41,21 +43,41 @@ pub struct FileSource<T: Send + 'static> {
     file_name: String,
     file: Option<async_fs::File>,
     repeat: bool,
+    start: Option<(Duration, f64)>,
     _type: std::marker::PhantomData<T>,
 }
 
 impl<T: Send + 'static> FileSource<T> {
     pub fn new<S: Into<String>>(file_name: S, repeat: bool) -> Block {
+        Self::with_start(file_name, repeat, None)
+    }
+
+    /// Create a source for a recording that started at `start_time` with `sample_rate`.
+    ///
+    /// The first sample is tagged with [`Tag::RxTime`] and [`Tag::RxRate`].
+    pub fn with_start_time<S: Into<String>>(
+        file_name: S,
+        repeat: bool,
+        start_time: Duration,
+        sample_rate: f64,
+    ) -> Block {
+        Self::with_start(file_name, repeat, Some((start_time, sample_rate)))
+    }
+
+    fn with_start<S: Into<String>>(
+        file_name: S,
+        repeat: bool,
+        start: Option<(Duration, f64)>,
+    ) -> Block {
         Block::new(
             BlockMetaBuilder::new("FileSource").build(),
-            StreamIoBuilder::new()
-                .add_output("out", std::mem::size_of::<T>())
-                .build(),
+            StreamIoBuilder::new().add_output::<T>("out").build(),
             MessageIoBuilder::new().build(),
             FileSource::<T> {
                 file_name: file_name.into(),
                 file: None,
                 repeat,
+                start,
                 _type: std::marker::PhantomData,
             },
         )
@@ -97,6 +119,13 @@ impl<T: Send + 'static> Kernel for FileSource<T> {
             }
         }
 
+        if i >= item_size {
+            if let Some((time, rate)) = self.start.take() {
+                sio.output(0).add_tag(0, Tag::RxTime(time));
+                sio.output(0).add_tag(0, Tag::RxRate(rate));
+            }
+        }
+
         sio.output(0).produce(i / item_size);
 
         Ok(())
@@ -1,5 +1,3 @@
-use std::mem;
-
 use crate::anyhow::Result;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
@@ -55,8 +53,8 @@ where
         Block::new(
             BlockMetaBuilder::new("Filter").build(),
             StreamIoBuilder::new()
-                .add_input("in", mem::size_of::<A>())
-                .add_output("out", mem::size_of::<B>())
+                .add_input::<A>("in")
+                .add_output::<B>("out")
                 .build(),
             MessageIoBuilder::<Filter<A, B>>::new().build(),
             Filter { f: Box::new(f) },
@@ -78,8 +76,8 @@ where
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let i = sio.input(0).slice::<A>();
-        let o = sio.output(0).slice::<B>();
+        let (i, o) = sio.slices::<A, B>(0, 0);
+        let i_len = i.len();
 
         let mut consumed = 0;
         let mut produced = 0;
@@ -98,7 +96,7 @@ where
         sio.input(0).consume(consumed);
         sio.output(0).produce(produced);
 
-        if sio.input(0).finished() && consumed == i.len() {
+        if sio.input(0).finished() && consumed == i_len {
             io.finished = true;
         }
 
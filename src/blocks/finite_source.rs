@@ -1,5 +1,3 @@
-use std::mem;
-
 use crate::anyhow::Result;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
@@ -29,9 +27,7 @@ where
     pub fn new(f: F) -> Block {
         Block::new(
             BlockMetaBuilder::new("FiniteSource").build(),
-            StreamIoBuilder::new()
-                .add_output("out", mem::size_of::<A>())
-                .build(),
+            StreamIoBuilder::new().add_output::<A>("out").build(),
             MessageIoBuilder::<Self>::new().build(),
             FiniteSource {
                 f,
@@ -57,17 +53,19 @@ where
     ) -> Result<()> {
         let o = sio.output(0).slice::<A>();
 
-        for (i, v) in o.iter_mut().enumerate() {
+        let mut n = 0;
+
+        for v in o.iter_mut() {
             if let Some(x) = (self.f)() {
                 *v = x;
+                n += 1;
             } else {
-                sio.output(0).produce(i);
                 io.finished = true;
-                return Ok(());
+                break;
             }
         }
 
-        sio.output(0).produce(o.len());
+        sio.output(0).produce(n);
 
         Ok(())
     }
@@ -10,6 +10,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 
 /// Copies only a given number of samples and stops.
@@ -41,8 +42,9 @@ impl<T: Send + 'static> Head<T> {
         Block::new(
             BlockMetaBuilder::new("Head").build(),
             StreamIoBuilder::new()
-                .add_input("in", std::mem::size_of::<T>())
-                .add_output("out", std::mem::size_of::<T>())
+                .add_input::<T>("in")
+                .add_output::<T>("out")
+                .tag_propagation_policy(TagPropagation::OneToOne)
                 .build(),
             MessageIoBuilder::new().build(),
             Head::<T> {
@@ -63,16 +65,14 @@ impl<T: Send + 'static> Kernel for Head<T> {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let i = sio.input(0).slice::<u8>();
-        let o = sio.output(0).slice::<u8>();
-        let item_size = std::mem::size_of::<T>();
+        let (i, o) = sio.slices::<T, T>(0, 0);
 
-        let mut m = cmp::min(self.n_items as usize, i.len() / item_size);
-        m = cmp::min(m, o.len() / item_size);
+        let mut m = cmp::min(self.n_items as usize, i.len());
+        m = cmp::min(m, o.len());
 
         if m > 0 {
             unsafe {
-                ptr::copy_nonoverlapping(i.as_ptr(), o.as_mut_ptr(), m * item_size);
+                ptr::copy_nonoverlapping(i.as_ptr(), o.as_mut_ptr(), m);
             }
 
             self.n_items -= m as u64;
@@ -1,5 +1,3 @@
-use std::mem;
-
 use crate::anyhow::Result;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
@@ -9,6 +7,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 use futuredsp::iir::IirKernel;
 use futuredsp::{StatefulUnaryKernel, TapsAccessor};
@@ -47,8 +46,9 @@ where
         Block::new(
             BlockMetaBuilder::new("Iir").build(),
             StreamIoBuilder::new()
-                .add_input("in", mem::size_of::<InputType>())
-                .add_output("out", mem::size_of::<OutputType>())
+                .add_input::<InputType>("in")
+                .add_output::<OutputType>("out")
+                .tag_propagation_policy(TagPropagation::OneToOne)
                 .build(),
             MessageIoBuilder::<Iir<InputType, OutputType, TapType, Core>>::new().build(),
             Iir {
@@ -77,8 +77,7 @@ where
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let i = sio.input(0).slice::<InputType>();
-        let o = sio.output(0).slice::<OutputType>();
+        let (i, o) = sio.slices::<InputType, OutputType>(0, 0);
 
         let (consumed, produced, status) = self.core.work(i, o);
 
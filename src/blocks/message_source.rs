@@ -1,8 +1,8 @@
-use async_io::Timer;
 use std::time::Duration;
 use std::time::Instant;
 
 use crate::anyhow::Result;
+use crate::runtime::time;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -36,10 +36,6 @@ impl MessageSource {
             },
         )
     }
-
-    async fn sleep(dur: Duration) {
-        Timer::after(dur).await;
-    }
 }
 
 #[doc(hidden)]
@@ -52,7 +48,7 @@ impl Kernel for MessageSource {
         mio: &mut MessageIo<Self>,
         _b: &mut BlockMeta,
     ) -> Result<()> {
-        let now = Instant::now();
+        let now = time::now();
 
         if now >= self.t_last + self.interval {
             mio.post(0, self.message.clone()).await;
@@ -65,9 +61,7 @@ impl Kernel for MessageSource {
             }
         }
 
-        io.block_on(MessageSource::sleep(
-            self.t_last + self.interval - Instant::now(),
-        ));
+        io.block_on(time::sleep_until(self.t_last + self.interval));
 
         Ok(())
     }
@@ -78,7 +72,7 @@ impl Kernel for MessageSource {
         _mio: &mut MessageIo<Self>,
         _b: &mut BlockMeta,
     ) -> Result<()> {
-        self.t_last = Instant::now();
+        self.t_last = time::now();
         Ok(())
     }
 }
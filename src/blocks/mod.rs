@@ -53,6 +53,7 @@
 //! | [BlobToUdp] | Push [Blobs](crate::runtime::Pmt::Blob) into a UDP socket.| ❌ |
 //! | [FileSink] | Write samples to a file. | ❌ |
 //! | [FileSource] | Read samples from a file. | ❌ |
+//! | [ShmSource] | Read samples from a shared memory buffer of another process (Linux only). | ❌ |
 //! | [TcpSource] | Reads samples from a TCP socket. | ❌ |
 //! | [TcpSink] | Push samples into a TCP socket. | ❌ |
 //! | [WebsocketSink] | Push samples in a WebSocket. | ❌ |
@@ -177,6 +178,11 @@ mod soapy_src;
 #[cfg(feature = "soapy")]
 pub use soapy_src::{SoapySource, SoapySourceBuilder};
 
+#[cfg(target_os = "linux")]
+mod shm_source;
+#[cfg(target_os = "linux")]
+pub use shm_source::ShmSource;
+
 mod source;
 pub use source::Source;
 mod split;
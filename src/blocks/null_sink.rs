@@ -38,9 +38,7 @@ impl<T: Send + 'static> NullSink<T> {
     pub fn new() -> Block {
         Block::new(
             BlockMetaBuilder::new("NullSink").build(),
-            StreamIoBuilder::new()
-                .add_input("in", std::mem::size_of::<T>())
-                .build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
             MessageIoBuilder::new().build(),
             NullSink::<T> {
                 n_received: 0,
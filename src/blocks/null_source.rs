@@ -37,9 +37,7 @@ impl<T: Send + 'static> NullSource<T> {
     pub fn new() -> Block {
         Block::new(
             BlockMetaBuilder::new("NullSource").build(),
-            StreamIoBuilder::new()
-                .add_output("out", std::mem::size_of::<T>())
-                .build(),
+            StreamIoBuilder::new().add_output::<T>("out").build(),
             MessageIoBuilder::new().build(),
             NullSource::<T> {
                 _type: std::marker::PhantomData,
@@ -65,7 +63,8 @@ impl<T: Send + 'static> Kernel for NullSource<T> {
             std::ptr::write_bytes(o.as_mut_ptr(), 0, o.len());
         }
 
-        sio.output(0).produce(o.len() / std::mem::size_of::<T>());
+        let n = o.len() / std::mem::size_of::<T>();
+        sio.output(0).produce(n);
 
         Ok(())
     }
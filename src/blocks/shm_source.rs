@@ -0,0 +1,125 @@
+use std::marker::PhantomData;
+use std::mem::size_of;
+
+use crate::anyhow::{Context, Result};
+use crate::runtime::buffer::shm::Reader;
+use crate::runtime::buffer::BufferReaderHost;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Read samples and tags from a [Shm](crate::runtime::buffer::shm::Shm) buffer of another process.
+///
+/// The block attaches to the buffer, when the flowgraph is initialized, and finishes, once the
+/// writer finished.
+///
+/// # Inputs
+///
+/// No inputs
+///
+/// # Outputs
+///
+/// `out`: Samples of the shared stream
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::ShmSource;
+/// use futuresdr::runtime::Flowgraph;
+/// use num_complex::Complex;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let source = fg.add_block(ShmSource::<Complex<f32>>::new("rx"));
+/// ```
+pub struct ShmSource<T: Send + 'static> {
+    name: String,
+    reader: Option<Reader>,
+    _type: PhantomData<T>,
+}
+
+impl<T: Copy + Send + 'static> ShmSource<T> {
+    pub fn new(name: impl Into<String>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ShmSource").build(),
+            StreamIoBuilder::new().add_output::<T>("out").build(),
+            MessageIoBuilder::new().build(),
+            ShmSource::<T> {
+                name: name.into(),
+                reader: None,
+                _type: PhantomData,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Copy + Send + 'static> Kernel for ShmSource<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let reader = self.reader.as_mut().context("not attached")?;
+        let (ptr, len, tags) = reader.bytes();
+        let available = len / size_of::<T>();
+
+        let o = sio.output(0).slice::<T>();
+        let n = std::cmp::min(available, o.len());
+        if n > 0 {
+            unsafe {
+                std::ptr::copy_nonoverlapping(ptr as *const T, o.as_mut_ptr(), n);
+            }
+            for t in tags.into_iter().filter(|t| t.index < n) {
+                sio.output(0).add_tag(t.index, t.tag);
+            }
+            reader.consume(n);
+            sio.output(0).produce(n);
+        }
+
+        if n == available {
+            if reader.finished() {
+                io.finished = true;
+            } else {
+                if n == 0 {
+                    reader.wait().await;
+                }
+                io.call_again = true;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.reader = Some(
+            Reader::attach(&self.name, size_of::<T>())
+                .with_context(|| format!("ShmSource cannot attach to {}", self.name))?,
+        );
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        // detach, so that the writer does not wait for this reader
+        self.reader = None;
+        Ok(())
+    }
+}
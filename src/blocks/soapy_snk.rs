@@ -1,9 +1,9 @@
 use futures::FutureExt;
 use soapysdr::Direction::Tx;
 use std::cmp;
-use std::mem;
 
 use crate::anyhow::{Context, Result};
+use crate::blocks::soapy_src::set_frequency;
 use crate::num_complex::Complex;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
@@ -14,15 +14,25 @@ use crate::runtime::MessageIoBuilder;
 use crate::runtime::Pmt;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
 use crate::runtime::WorkIo;
 
 /// Transmit samples with a Soapy SDR device.
 ///
 /// # Inputs
-/// * **Message**: `freq`: set the SDR's frequency; accepts a [`Pmt::U32`] value
-/// * **Message**: `sample_rate`: set the SDR's sample rate; accepts a [`Pmt::U32`] value
+/// * **Message**: `freq`: set the SDR's frequency; accepts a Pmt that converts to `f64` or a
+///   map `{ "freq": f64, "time": i64 }` to tune at the given hardware time (in ns)
+/// * **Message**: `sample_rate`: set the SDR's sample rate; accepts a Pmt that converts to `f64`
 /// * **Stream**: `in`: stream of [`Complex<f32>`] values
 ///
+/// `freq` and `sample_rate` are persistent parameters, included in a
+/// [`FlowgraphSnapshot`](crate::runtime::FlowgraphSnapshot).
+///
+/// Timed tuning uses the command time of the device. If it is not supported, the message
+/// handler fails.
+///
+/// Bursts are delimited by [`Tag::TxSob`] and [`Tag::TxEob`] tags. The last sample of a burst
+/// is written with the end-of-burst flag set.
 pub struct SoapySink {
     dev: Option<soapysdr::Device>,
     stream: Option<soapysdr::TxStream<Complex<f32>>>,
@@ -47,42 +57,41 @@ impl SoapySink {
         Block::new(
             BlockMetaBuilder::new("SoapySink").blocking().build(),
             StreamIoBuilder::new()
-                .add_input("in", mem::size_of::<Complex<f32>>())
+                .add_input::<Complex<f32>>("in")
                 .build(),
             MessageIoBuilder::new()
-                .add_input(
+                .add_parameter(
                     "freq",
+                    |block: &SoapySink| Pmt::F64(block.freq),
                     |block: &mut SoapySink,
                      _mio: &mut MessageIo<SoapySink>,
                      _meta: &mut BlockMeta,
                      p: Pmt| {
                         async move {
-                            if let Pmt::U32(ref f) = &p {
-                                block.dev.as_mut().context("no dev")?.set_frequency(
-                                    Tx,
-                                    0,
-                                    *f as f64,
-                                    (),
-                                )?;
+                            let dev = block.dev.as_ref().context("no dev")?;
+                            if let Some((f, _)) = set_frequency(dev, Tx, &p)? {
+                                block.freq = f;
                             }
                             Ok(p)
                         }
                         .boxed()
                     },
                 )
-                .add_input(
+                .add_parameter(
                     "sample_rate",
+                    |block: &SoapySink| Pmt::F64(block.sample_rate),
                     |block: &mut SoapySink,
                      _mio: &mut MessageIo<SoapySink>,
                      _meta: &mut BlockMeta,
                      p: Pmt| {
                         async move {
-                            if let Pmt::U32(ref r) = &p {
+                            if let Ok(r) = f64::try_from(&p) {
                                 block
                                     .dev
                                     .as_mut()
                                     .context("no dev")?
-                                    .set_sample_rate(Tx, 0, *r as f64)?;
+                                    .set_sample_rate(Tx, 0, r)?;
+                                block.sample_rate = r;
                             }
                             Ok(p)
                         }
@@ -113,19 +122,42 @@ impl Kernel for SoapySink {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let i = sio.input(0).slice::<Complex<f32>>();
+        let (i, tags) = sio.input(0).slice_with_tags::<Complex<f32>>();
         let stream = self.stream.as_mut().unwrap();
-        let n = cmp::min(i.len(), stream.mtu().unwrap());
+        let i_len = i.len();
+        let mut n = cmp::min(i_len, stream.mtu().unwrap());
         if n == 0 {
             return Ok(());
         }
 
-        let len = stream.write(&[&i[..n]], None, false, 1_000_000)?;
+        // a write must not span burst boundaries
+        let mut end_burst = false;
+        for t in tags.iter() {
+            match t.tag {
+                Tag::TxSob if t.index > 0 && t.index < n => {
+                    n = t.index;
+                    break;
+                }
+                Tag::TxEob if t.index < n => {
+                    n = t.index + 1;
+                    end_burst = true;
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        let len = if end_burst {
+            stream.write_all(&[&i[..n]], None, true, 1_000_000)?;
+            n
+        } else {
+            stream.write(&[&i[..n]], None, false, 1_000_000)?
+        };
         sio.input(0).consume(len);
-        if len != i.len() {
+        if len != i_len {
             io.call_again = true;
         }
-        if sio.input(0).finished() && len == i.len() {
+        if sio.input(0).finished() && len == i_len {
             io.finished = true;
         }
 
@@ -175,7 +207,7 @@ unsafe impl Sync for SoapySink {}
 ///
 /// # Inputs
 ///
-/// **Message** `freq`: a Pmt::u32 to change the frequency to.
+/// **Message** `freq`: a Pmt that converts to `f64` to change the frequency to.
 /// **Stream** `in`: Stream of [`Complex<f32>`] to transmit.
 ///
 /// # Usage
@@ -1,9 +1,14 @@
 use futures::FutureExt;
+use serde::Deserialize;
+use soapysdr::Direction;
 use soapysdr::Direction::Rx;
+use soapysdr::ErrorCode;
 use std::cmp;
-use std::mem;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
-use crate::anyhow::{Context, Result};
+use crate::anyhow::{bail, Context, Result};
 use crate::num_complex::Complex;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
@@ -12,19 +17,35 @@ use crate::runtime::Kernel;
 use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::Pmt;
+use crate::runtime::SampleClock;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
 use crate::runtime::WorkIo;
 
 /// Receive samples from a Soapy SDR device.
 ///
 /// # Inputs
-/// * **Message**: `freq`: set the SDR's frequency; accepts a [`Pmt::U32`] value
-/// * **Message**: `sample_rate`: set the SDR's sample rate; accepts a [`Pmt::U32`] value
+/// * **Message**: `freq`: set the SDR's frequency; accepts a Pmt that converts to `f64` or a
+///   map `{ "freq": f64, "time": i64 }` to tune at the given hardware time (in ns)
+/// * **Message**: `sample_rate`: set the SDR's sample rate; accepts a Pmt that converts to `f64`
+///
+/// `freq` and `sample_rate` are persistent parameters, included in a
+/// [`FlowgraphSnapshot`](crate::runtime::FlowgraphSnapshot).
+///
+/// Timed tuning uses the command time of the device. If it is not supported, the message
+/// handler fails. To retune at a sample index, schedule the message with
+/// [`CallTime`](crate::runtime::CallTime).
 ///
 /// # Outputs
 /// * **Stream**: `out`: stream of [`Complex<f32>`] values
 ///
+/// The first sample and the first sample after a retune are tagged with [`Tag::RxTime`],
+/// [`Tag::RxFreq`], and [`Tag::RxRate`]. For timed tuning, this is the sample at the command
+/// time. If the device has a hardware time, the stream is
+/// started at a known hardware time and the time of a sample follows from its index. After an
+/// overflow or a change of the sample rate, the time is only approximate. Without hardware time,
+/// the time since the UNIX epoch is used, which is approximate as well.
 pub struct SoapySource {
     dev: Option<soapysdr::Device>,
     stream: Option<soapysdr::RxStream<Complex<f32>>>,
@@ -33,8 +54,17 @@ pub struct SoapySource {
     gain: f64,
     filter: String,
     antenna: Option<String>,
+    tag_pending: bool,
+    // samples produced and their time
+    items: u64,
+    clock: SampleClock,
+    // sample index and frequency of timed retunes
+    retunes: Vec<(u64, f64)>,
 }
 
+/// Delay until the stream is started, so that the device is ready to stream at this time.
+const START_DELAY: Duration = Duration::from_millis(100);
+
 impl SoapySource {
     pub fn new<S>(
         freq: f64,
@@ -49,30 +79,26 @@ impl SoapySource {
         Block::new(
             BlockMetaBuilder::new("SoapySource").blocking().build(),
             StreamIoBuilder::new()
-                .add_output("out", mem::size_of::<Complex<f32>>())
+                .add_output::<Complex<f32>>("out")
                 .build(),
             MessageIoBuilder::new()
-                .add_input(
+                .add_parameter(
                     "freq",
+                    |block: &SoapySource| Pmt::F64(block.freq),
                     |block: &mut SoapySource,
                      _mio: &mut MessageIo<SoapySource>,
                      _meta: &mut BlockMeta,
                      p: Pmt| {
                         async move {
-                            if let Pmt::U32(ref f) = &p {
-                                block.dev.as_mut().context("no dev")?.set_frequency(
-                                    Rx,
-                                    0,
-                                    *f as f64,
-                                    (),
-                                )?;
-                            } else if let Pmt::F64(ref f) = &p {
-                                block.dev.as_mut().context("no dev")?.set_frequency(
-                                    Rx,
-                                    0,
-                                    *f,
-                                    (),
-                                )?;
+                            let dev = block.dev.as_ref().context("no dev")?;
+                            if let Some((f, time)) = set_frequency(dev, Rx, &p)? {
+                                block.freq = f;
+                                match time
+                                    .and_then(|t| block.clock.index(Duration::from_nanos(t as u64)))
+                                {
+                                    Some(index) => block.retunes.push((index, f)),
+                                    None => block.tag_pending = true,
+                                }
                             } else {
                                 warn!("SoapySource/freq Handler received wrong PMT {:?}", &p);
                             }
@@ -81,19 +107,23 @@ impl SoapySource {
                         .boxed()
                     },
                 )
-                .add_input(
+                .add_parameter(
                     "sample_rate",
+                    |block: &SoapySource| Pmt::F64(block.sample_rate),
                     |block: &mut SoapySource,
                      _mio: &mut MessageIo<SoapySource>,
                      _meta: &mut BlockMeta,
                      p: Pmt| {
                         async move {
-                            if let Pmt::U32(ref r) = &p {
+                            if let Ok(r) = f64::try_from(&p) {
                                 block
                                     .dev
                                     .as_mut()
                                     .context("no dev")?
-                                    .set_sample_rate(Rx, 0, *r as f64)?;
+                                    .set_sample_rate(Rx, 0, r)?;
+                                block.sample_rate = r;
+                                block.clock.update(block.items, &Tag::RxRate(r));
+                                block.tag_pending = true;
                             }
                             Ok(p)
                         }
@@ -109,9 +139,62 @@ impl SoapySource {
                 gain,
                 filter,
                 antenna: antenna.map(Into::into),
+                tag_pending: true,
+                items: 0,
+                clock: SampleClock::new(),
+                retunes: Vec::new(),
             },
         )
     }
+
+    /// Hardware time of the device or, if not supported, the system time.
+    fn now(&self) -> Duration {
+        self.dev
+            .as_ref()
+            .filter(|d| d.has_hardware_time(None).unwrap_or(false))
+            .and_then(|d| d.get_hardware_time(None).ok())
+            .map(|ns| Duration::from_nanos(ns as u64))
+            .unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+            })
+    }
+}
+
+/// Frequency change at a hardware time of the device (in ns).
+#[derive(Deserialize)]
+struct TimedFreq {
+    freq: f64,
+    time: i64,
+}
+
+/// Handle a `freq` message, i.e., a frequency or a [`TimedFreq`]. Returns the new frequency and,
+/// for a [`TimedFreq`], its hardware time or `None` if the message is invalid. Fails, if the
+/// device does not support timed commands.
+pub(crate) fn set_frequency(
+    dev: &soapysdr::Device,
+    dir: Direction,
+    p: &Pmt,
+) -> Result<Option<(f64, Option<i64>)>> {
+    if let Ok(f) = f64::try_from(p) {
+        dev.set_frequency(dir, 0, f, ())?;
+        return Ok(Some((f, None)));
+    }
+
+    let t = match p.deserialize_into::<TimedFreq>() {
+        Ok(t) => t,
+        Err(_) => return Ok(None),
+    };
+    if !dev.has_hardware_time(Some("CMD"))? {
+        bail!("Soapy: device does not support timed commands");
+    }
+    dev.set_hardware_time(Some("CMD"), t.time)?;
+    let res = dev.set_frequency(dir, 0, t.freq, ());
+    // clear the command time
+    let _ = dev.set_hardware_time(Some("CMD"), 0);
+    res?;
+    Ok(Some((t.freq, Some(t.time))))
 }
 
 #[doc(hidden)]
@@ -131,8 +214,43 @@ impl Kernel for SoapySource {
             return Ok(());
         }
 
-        if let Ok(len) = stream.read(&[&mut out[..n]], 1_000_000) {
-            sio.output(0).produce(len);
+        match stream.read(&[&mut out[..n]], 1_000_000) {
+            Ok(len) if len > 0 => {
+                if self.clock.time(self.items).is_none() {
+                    // no hardware time, the last sample was just received
+                    let now = self.now();
+                    self.clock
+                        .update(self.items + len as u64, &Tag::RxTime(now));
+                }
+                if self.tag_pending {
+                    if let Some(time) = self.clock.time(self.items) {
+                        sio.output(0).add_tag(0, Tag::RxTime(time));
+                    }
+                    sio.output(0).add_tag(0, Tag::RxFreq(self.freq));
+                    sio.output(0).add_tag(0, Tag::RxRate(self.sample_rate));
+                    self.tag_pending = false;
+                }
+                let end = self.items + len as u64;
+                for (index, freq) in self.retunes.iter().filter(|(i, _)| *i < end) {
+                    // the command time might have passed already
+                    let index = cmp::max(*index, self.items);
+                    let offset = (index - self.items) as usize;
+                    if let Some(time) = self.clock.time(index) {
+                        sio.output(0).add_tag(offset, Tag::RxTime(time));
+                    }
+                    sio.output(0).add_tag(offset, Tag::RxFreq(*freq));
+                }
+                self.retunes.retain(|(i, _)| *i >= end);
+                self.items = end;
+                sio.output(0).produce(len);
+            }
+            Err(e) if e.code == ErrorCode::Overflow => {
+                // samples were lost, take the time of the next sample from the device
+                let now = self.now();
+                self.clock.update(self.items, &Tag::RxTime(now));
+                self.tag_pending = true;
+            }
+            _ => {}
         }
         io.call_again = true;
         Ok(())
@@ -156,7 +274,17 @@ impl Kernel for SoapySource {
         }
 
         self.stream = Some(dev.rx_stream::<Complex<f32>>(&[channel])?);
-        self.stream.as_mut().context("no stream")?.activate(None)?;
+        let stream = self.stream.as_mut().context("no stream")?;
+        self.clock.update(0, &Tag::RxRate(self.sample_rate));
+        if dev.has_hardware_time(None).unwrap_or(false) {
+            // start at a known time, so that the time of a sample follows from its index
+            let start = dev.get_hardware_time(None)? + START_DELAY.as_nanos() as i64;
+            stream.activate(Some(start))?;
+            self.clock
+                .update(0, &Tag::RxTime(Duration::from_nanos(start as u64)));
+        } else {
+            stream.activate(None)?;
+        }
 
         Ok(())
     }
@@ -181,7 +309,7 @@ unsafe impl Sync for SoapySource {}
 ///
 /// # Inputs
 ///
-/// **Message** `freq`: a Pmt::u32 to change the frequency to.
+/// **Message** `freq`: a Pmt that converts to `f64` to change the frequency to.
 ///
 /// # Outputs
 ///
@@ -1,5 +1,3 @@
-use std::mem;
-
 use crate::anyhow::Result;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
@@ -9,6 +7,7 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 
 /// Apply a function to split a stream.
@@ -36,9 +35,10 @@ where
         Block::new(
             BlockMetaBuilder::new("Split").build(),
             StreamIoBuilder::new()
-                .add_input("in", mem::size_of::<A>())
-                .add_output("out0", mem::size_of::<B>())
-                .add_output("out1", mem::size_of::<C>())
+                .add_input::<A>("in")
+                .add_output::<B>("out0")
+                .add_output::<C>("out1")
+                .tag_propagation_policy(TagPropagation::AllToAll)
                 .build(),
             MessageIoBuilder::<Self>::new().build(),
             Split {
@@ -67,9 +67,12 @@ where
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let i0 = sio.input(0).slice::<A>();
-        let o0 = sio.output(0).slice::<B>();
-        let o1 = sio.output(1).slice::<C>();
+        let (inputs, outputs) = sio.ports_mut();
+        let (out0, out1) = outputs.split_at_mut(1);
+        let i0 = inputs[0].slice::<A>();
+        let o0 = out0[0].slice::<B>();
+        let o1 = out1[0].slice::<C>();
+        let i0_len = i0.len();
 
         let m = std::cmp::min(i0.len(), o0.len());
         let m = std::cmp::min(m, o1.len());
@@ -86,7 +89,7 @@ where
             sio.output(1).produce(m);
         }
 
-        if sio.input(0).finished() && m == i0.len() {
+        if sio.input(0).finished() && m == i0_len {
             io.finished = true;
         }
 
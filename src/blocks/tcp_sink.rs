@@ -23,7 +23,7 @@ impl TcpSink {
     pub fn new(port: u32) -> Block {
         Block::new(
             BlockMetaBuilder::new("TcpSink").build(),
-            StreamIoBuilder::new().add_input("in", 1).build(),
+            StreamIoBuilder::new().add_input::<u8>("in").build(),
             MessageIoBuilder::new().build(),
             TcpSink {
                 port,
@@ -56,6 +56,7 @@ impl Kernel for TcpSink {
         }
 
         let i = sio.input(0).slice::<u8>();
+        let n = i.len();
 
         match self
             .socket
@@ -72,8 +73,8 @@ impl Kernel for TcpSink {
             io.finished = true;
         }
 
-        debug!("tcp sink wrote bytes {}", i.len());
-        sio.input(0).consume(i.len());
+        debug!("tcp sink wrote bytes {}", n);
+        sio.input(0).consume(n);
 
         Ok(())
     }
@@ -23,7 +23,7 @@ impl TcpSource {
     pub fn new(port: u32) -> Block {
         Block::new(
             BlockMetaBuilder::new("TcpSource").build(),
-            StreamIoBuilder::new().add_output("out", 1).build(),
+            StreamIoBuilder::new().add_output::<u8>("out").build(),
             MessageIoBuilder::new().build(),
             TcpSource {
                 port,
@@ -56,7 +56,8 @@ impl Kernel for TcpSource {
         }
 
         let out = sio.output(0).slice::<u8>();
-        if out.is_empty() {
+        let n = out.len();
+        if n == 0 {
             return Ok(());
         }
 
@@ -68,8 +69,8 @@ impl Kernel for TcpSource {
             .await
         {
             Ok(_) => {
-                debug!("tcp source read bytes {}", out.len());
-                sio.output(0).produce(out.len());
+                debug!("tcp source read bytes {}", n);
+                sio.output(0).produce(n);
             }
             Err(_) => {
                 debug!("tcp source socket closed");
@@ -1,10 +1,11 @@
-use async_io::Timer;
 use std::cmp;
 use std::ptr;
 use std::time::Duration;
 use std::time::Instant;
+use std::time::UNIX_EPOCH;
 
 use crate::anyhow::Result;
+use crate::runtime::time;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -13,6 +14,8 @@ use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
+use crate::runtime::TagPropagation;
 use crate::runtime::WorkIo;
 
 /// Limit sample rate.
@@ -39,24 +42,37 @@ use crate::runtime::WorkIo;
 pub struct Throttle<T: Send + 'static> {
     rate: f64,
     t_init: Instant,
+    t_start: Duration,
     n_items: usize,
+    timestamps: bool,
     _type: std::marker::PhantomData<T>,
 }
 
 impl<T: Send + 'static> Throttle<T> {
     /// Creates a new Throttle block which will throttle to the specified rate.
     pub fn new(rate: f64) -> Block {
+        Self::with_timestamps(rate, false)
+    }
+
+    /// Creates a new Throttle block that, optionally, simulates a timestamped stream.
+    ///
+    /// With `timestamps`, the first output sample is tagged with [`Tag::RxTime`] (time since the
+    /// UNIX epoch) and [`Tag::RxRate`].
+    pub fn with_timestamps(rate: f64, timestamps: bool) -> Block {
         Block::new(
             BlockMetaBuilder::new("Throttle").build(),
             StreamIoBuilder::new()
-                .add_input("in", std::mem::size_of::<T>())
-                .add_output("out", std::mem::size_of::<T>())
+                .add_input::<T>("in")
+                .add_output::<T>("out")
+                .tag_propagation_policy(TagPropagation::OneToOne)
                 .build(),
             MessageIoBuilder::<Self>::new().build(),
             Throttle::<T> {
                 rate,
                 t_init: Instant::now(),
+                t_start: Duration::ZERO,
                 n_items: 0,
+                timestamps,
                 _type: std::marker::PhantomData,
             },
         )
@@ -73,35 +89,36 @@ impl<T: Send + 'static> Kernel for Throttle<T> {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let i = sio.input(0).slice::<u8>();
-        let o = sio.output(0).slice::<u8>();
-        let item_size = std::mem::size_of::<T>();
+        let (i, o) = sio.slices::<T, T>(0, 0);
+        let i_len = i.len();
 
-        let mut m = cmp::min(i.len(), o.len());
+        let mut m = cmp::min(i_len, o.len());
 
-        let now = Instant::now();
+        let now = time::now();
         let target_items = (now - self.t_init).as_secs_f64() * self.rate;
         let target_items = target_items.floor() as usize;
 
-        m = cmp::min(m, (target_items - self.n_items) * item_size) as usize;
+        m = cmp::min(m, target_items - self.n_items);
         if m != 0 {
             unsafe {
                 ptr::copy_nonoverlapping(i.as_ptr(), o.as_mut_ptr(), m);
             }
 
-            let m = m / item_size;
+            if self.timestamps && self.n_items == 0 {
+                sio.output(0).add_tag(0, Tag::RxTime(self.t_start));
+                sio.output(0).add_tag(0, Tag::RxRate(self.rate));
+            }
+
             self.n_items += m;
             sio.input(0).consume(m);
             sio.output(0).produce(m);
         }
 
-        if sio.input(0).finished() && i.len() == m * item_size {
+        if sio.input(0).finished() && i_len == m {
             io.finished = true;
         }
 
-        io.block_on(async {
-            Timer::after(Duration::from_millis(100)).await;
-        });
+        io.block_on(time::sleep(Duration::from_millis(100)));
 
         Ok(())
     }
@@ -112,7 +129,10 @@ impl<T: Send + 'static> Kernel for Throttle<T> {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        self.t_init = Instant::now();
+        self.t_init = time::now();
+        self.t_start = time::system_time()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
         self.n_items = 0;
         Ok(())
     }
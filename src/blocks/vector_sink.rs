@@ -1,5 +1,4 @@
 use std::marker::PhantomData;
-use std::mem;
 
 use crate::anyhow::Result;
 use crate::runtime::Block;
@@ -21,9 +20,7 @@ impl<T: Clone + std::fmt::Debug + Send + Sync + 'static> VectorSink<T> {
     pub fn new(capacity: usize) -> Block {
         Block::new(
             BlockMetaBuilder::new("VectorSink").build(),
-            StreamIoBuilder::new()
-                .add_input("in", mem::size_of::<T>())
-                .build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
             MessageIoBuilder::<Self>::new().build(),
             VectorSink {
                 items: Vec::<T>::with_capacity(capacity),
@@ -48,9 +45,10 @@ impl<T: Clone + std::fmt::Debug + Send + Sync + 'static> Kernel for VectorSink<T
     ) -> Result<()> {
         let i = sio.input(0).slice::<T>();
 
+        let n = i.len();
         self.items.extend_from_slice(i);
 
-        sio.input(0).consume(i.len());
+        sio.input(0).consume(n);
 
         if sio.input(0).finished() {
             io.finished = true;
@@ -61,8 +61,8 @@ impl Vulkan {
         Block::new(
             BlockMetaBuilder::new("Vulkan").build(),
             StreamIoBuilder::new()
-                .add_input("in", 4)
-                .add_output("out", 4)
+                .add_input::<f32>("in")
+                .add_output::<f32>("out")
                 .build(),
             MessageIoBuilder::<Vulkan>::new().build(),
             Vulkan {
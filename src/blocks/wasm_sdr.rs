@@ -2,7 +2,6 @@ use futures::channel::mpsc;
 use futures::SinkExt;
 use futures::StreamExt;
 use once_cell::sync::OnceCell;
-use std::mem::size_of;
 use std::sync::Mutex;
 use wasm_bindgen::prelude::*;
 
@@ -54,7 +53,7 @@ impl WasmSdr {
         Block::new(
             BlockMetaBuilder::new("WasmSDR").build(),
             StreamIoBuilder::new()
-                .add_output("out", size_of::<Complex32>())
+                .add_output::<Complex32>("out")
                 .build(),
             MessageIoBuilder::new().build(),
             Self {
@@ -77,13 +76,14 @@ impl Kernel for WasmSdr {
         _meta: &mut BlockMeta,
     ) -> Result<()> {
         let output = sio.output(0).slice::<Complex32>();
+        let output_len = output.len();
 
         if self.index == self.samples.len() {
             self.samples = self.receiver.next().await.unwrap();
             self.index = 0;
         }
 
-        let n = std::cmp::min((self.samples.len() - self.index) / 2, output.len());
+        let n = std::cmp::min((self.samples.len() - self.index) / 2, output_len);
 
         for (i, o) in output.iter_mut().enumerate().take(n) {
             *o = Complex32::new(
@@ -95,7 +95,7 @@ impl Kernel for WasmSdr {
         self.index += 2 * n;
 
         sio.output(0).produce(n);
-        if n < output.len() {
+        if n < output_len {
             io.call_again = true;
         }
 
@@ -64,9 +64,7 @@ impl<T: Send + Sync + 'static> WasmWsSink<T> {
 
         Block::new(
             BlockMetaBuilder::new("WasmWsSink").build(),
-            StreamIoBuilder::new()
-                .add_input("in", size_of::<T>())
-                .build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
             MessageIoBuilder::<Self>::new().build(),
             WasmWsSink {
                 data_sender: sender,
@@ -93,6 +91,7 @@ impl<T: Send + Sync + 'static> Kernel for WasmWsSink<T> {
         if *self.ws_error.read().expect("Lock is poisoned") {
             anyhow::bail!("WebSocket Error");
         }
+        let finished = sio.input(0).finished();
         let i = sio.input(0).slice::<u8>();
         debug_assert_eq!(i.len() % size_of::<T>(), 0);
 
@@ -104,7 +103,7 @@ impl<T: Send + Sync + 'static> Kernel for WasmWsSink<T> {
             return Ok(());
         }
 
-        if sio.input(0).finished() {
+        if finished {
             io.finished = true;
         }
 
@@ -47,9 +47,7 @@ impl<T: Send + Sync + 'static> WebsocketSink<T> {
     pub fn new(port: u32, mode: WebsocketSinkMode) -> Block {
         Block::new(
             BlockMetaBuilder::new("WebsocketSink").build(),
-            StreamIoBuilder::new()
-                .add_input("in", size_of::<T>())
-                .build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
             MessageIoBuilder::<Self>::new().build(),
             WebsocketSink {
                 port,
@@ -72,10 +70,11 @@ impl<T: Send + Sync + 'static> Kernel for WebsocketSink<T> {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
+        let finished = sio.input(0).finished();
         let i = sio.input(0).slice::<u8>();
         debug_assert_eq!(i.len() % size_of::<T>(), 0);
 
-        if sio.input(0).finished() {
+        if finished {
             io.finished = true;
         }
 
@@ -92,7 +91,7 @@ impl<T: Send + Sync + 'static> Kernel for WebsocketSink<T> {
             match &self.mode {
                 WebsocketSinkMode::Blocking => {
                     v.extend_from_slice(i);
-                    sio.input(0).consume(i.len() / size_of::<T>());
+                    sio.input(0).consume(items);
                 }
                 WebsocketSinkMode::FixedBlocking(block_size) => {
                     if *block_size <= items {
@@ -54,8 +54,8 @@ impl Wgpu {
         Block::new(
             BlockMetaBuilder::new("Wgpu").build(),
             StreamIoBuilder::new()
-                .add_input("in", 4)
-                .add_output("out", 4)
+                .add_input::<f32>("in")
+                .add_output::<f32>("out")
                 .build(),
             MessageIoBuilder::<Wgpu>::new().build(),
             Wgpu {
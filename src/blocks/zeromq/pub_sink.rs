@@ -20,7 +20,9 @@ impl PubSink {
     pub fn new(item_size: usize, address: impl Into<String>) -> Block {
         Block::new(
             BlockMetaBuilder::new("PubSink").blocking().build(),
-            StreamIoBuilder::new().add_input("in", item_size).build(),
+            StreamIoBuilder::new()
+                .add_raw_input("in", item_size)
+                .build(),
             MessageIoBuilder::new().build(),
             PubSink {
                 item_size,
@@ -20,7 +20,9 @@ impl SubSource {
     pub fn new(item_size: usize, address: impl Into<String>) -> Block {
         Block::new(
             BlockMetaBuilder::new("SubSource").blocking().build(),
-            StreamIoBuilder::new().add_output("out", item_size).build(),
+            StreamIoBuilder::new()
+                .add_raw_output("out", item_size)
+                .build(),
             MessageIoBuilder::new().build(),
             SubSource {
                 item_size,
@@ -38,6 +38,9 @@
 pub mod blocks;
 pub mod runtime;
 
+// allows the macros to refer to `::futuresdr` inside this crate
+extern crate self as futuresdr;
+
 // re-exports
 #[cfg(not(target_arch = "wasm32"))]
 pub use async_io;
@@ -47,6 +50,7 @@ pub use async_net;
 pub extern crate async_trait;
 pub use futures;
 pub use futures_lite;
+pub use futuresdr_macros as macros;
 #[macro_use]
 pub extern crate log;
 
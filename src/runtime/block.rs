@@ -5,12 +5,14 @@ use std::pin::Pin;
 
 use crate::anyhow::Result;
 use crate::runtime::BlockMeta;
+use crate::runtime::ErrorPolicy;
 use crate::runtime::MessageIo;
 use crate::runtime::MessageOutput;
 use crate::runtime::Pmt;
 use crate::runtime::StreamInput;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamOutput;
+use crate::runtime::TagPropagation;
 
 pub struct WorkIo {
     pub call_again: bool,
@@ -73,6 +75,8 @@ pub trait BlockT: Send + Any {
     fn set_instance_name(&mut self, name: &str);
     fn type_name(&self) -> &str;
     fn is_blocking(&self) -> bool;
+    fn error_policy(&self) -> ErrorPolicy;
+    fn min_items(&self) -> usize;
 
     // ##### KERNEL
     async fn work(&mut self, io: &mut WorkIo) -> Result<()>;
@@ -100,6 +104,7 @@ pub trait BlockT: Send + Any {
     // ##### MESSAGE IO
     fn message_input_name_to_id(&self, name: &str) -> Option<usize>;
     fn message_input_names(&self) -> Vec<String>;
+    fn parameters(&self) -> Vec<(String, Pmt)>;
     fn message_outputs(&self) -> &Vec<MessageOutput>;
     fn message_outputs_mut(&mut self) -> &mut Vec<MessageOutput>;
     fn message_output(&self, id: usize) -> &MessageOutput;
@@ -140,6 +145,12 @@ impl<T: Kernel + Send + 'static> BlockT for TypedBlock<T> {
     fn is_blocking(&self) -> bool {
         self.meta.is_blocking()
     }
+    fn error_policy(&self) -> ErrorPolicy {
+        self.meta.error_policy()
+    }
+    fn min_items(&self) -> usize {
+        self.meta.min_items()
+    }
 
     // ##### KERNEL
     async fn work(&mut self, io: &mut WorkIo) -> Result<()> {
@@ -206,6 +217,9 @@ impl<T: Kernel + Send + 'static> BlockT for TypedBlock<T> {
     fn message_input_names(&self) -> Vec<String> {
         self.mio.input_names()
     }
+    fn parameters(&self) -> Vec<(String, Pmt)> {
+        self.mio.parameters(&self.kernel)
+    }
     fn message_outputs(&self) -> &Vec<MessageOutput> {
         self.mio.outputs()
     }
@@ -276,6 +290,12 @@ impl Block {
     pub fn is_blocking(&self) -> bool {
         self.0.is_blocking()
     }
+    pub fn error_policy(&self) -> ErrorPolicy {
+        self.0.error_policy()
+    }
+    pub fn min_items(&self) -> usize {
+        self.0.min_items()
+    }
 
     // ##### KERNEL
     pub async fn init(&mut self) -> Result<()> {
@@ -299,6 +319,10 @@ impl Block {
     ) {
         self.0.set_tag_propagation(f);
     }
+    pub fn set_tag_propagation_policy(&mut self, policy: TagPropagation) {
+        self.0
+            .set_tag_propagation(Box::new(move |i, o| policy.propagate(i, o)));
+    }
     pub fn stream_inputs(&self) -> &Vec<StreamInput> {
         self.0.stream_inputs()
     }
@@ -337,6 +361,11 @@ impl Block {
     pub fn message_input_names(&self) -> Vec<String> {
         self.0.message_input_names()
     }
+    /// Current values of the persistent parameters, declared with
+    /// [`MessageIoBuilder::add_parameter`](crate::runtime::MessageIoBuilder::add_parameter).
+    pub fn parameters(&self) -> Vec<(String, Pmt)> {
+        self.0.parameters()
+    }
     pub fn message_outputs(&self) -> &Vec<MessageOutput> {
         self.0.message_outputs()
     }
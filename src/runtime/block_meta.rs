@@ -1,15 +1,42 @@
+/// How the runtime reacts to errors of [Kernel::work](crate::runtime::Kernel::work).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Terminate the flowgraph.
+    Terminate,
+    /// Log the error and keep the block running.
+    Continue,
+    /// Re-run `deinit()` and `init()` and keep the block running, at most the given number of
+    /// times. Afterwards, terminate the flowgraph.
+    Restart(usize),
+}
+
+impl Default for ErrorPolicy {
+    fn default() -> Self {
+        ErrorPolicy::Terminate
+    }
+}
+
 pub struct BlockMeta {
     type_name: String,
     instance_name: Option<String>,
     blocking: bool,
+    error_policy: ErrorPolicy,
+    min_items: usize,
 }
 
 impl BlockMeta {
-    fn new(type_name: String, blocking: bool) -> BlockMeta {
+    fn new(
+        type_name: String,
+        blocking: bool,
+        error_policy: ErrorPolicy,
+        min_items: usize,
+    ) -> BlockMeta {
         BlockMeta {
             type_name,
             instance_name: None,
             blocking,
+            error_policy,
+            min_items,
         }
     }
 
@@ -25,6 +52,15 @@ impl BlockMeta {
         self.blocking
     }
 
+    /// How the runtime handles errors of the block.
+    pub fn error_policy(&self) -> ErrorPolicy {
+        self.error_policy
+    }
+
+    pub fn min_items(&self) -> usize {
+        self.min_items
+    }
+
     pub fn set_instance_name(&mut self, name: impl Into<String>) {
         self.instance_name = Some(name.into());
     }
@@ -33,6 +69,8 @@ impl BlockMeta {
 pub struct BlockMetaBuilder {
     name: String,
     blocking: bool,
+    error_policy: ErrorPolicy,
+    min_items: usize,
 }
 
 impl BlockMetaBuilder {
@@ -40,6 +78,8 @@ impl BlockMetaBuilder {
         BlockMetaBuilder {
             name: name.into(),
             blocking: false,
+            error_policy: ErrorPolicy::default(),
+            min_items: 1,
         }
     }
 
@@ -49,6 +89,24 @@ impl BlockMetaBuilder {
         self
     }
 
+    /// Set how the runtime handles errors of the block, see [ErrorPolicy].
+    #[must_use]
+    pub fn error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.error_policy = policy;
+        self
+    }
+
+    /// Minimum number of items that the block needs on its stream ports per call to `work()`.
+    ///
+    /// Buffers that do not expose all items contiguously, e.g.,
+    /// [Ring](crate::runtime::buffer::ring::Ring), use this to make sure that the block can make
+    /// progress.
+    #[must_use]
+    pub fn min_items(mut self, min_items: usize) -> Self {
+        self.min_items = std::cmp::max(min_items, 1);
+        self
+    }
+
     #[must_use]
     pub fn name(mut self, name: &str) -> Self {
         self.name = name.to_string();
@@ -56,6 +114,6 @@ impl BlockMetaBuilder {
     }
 
     pub fn build(self) -> BlockMeta {
-        BlockMeta::new(self.name, self.blocking)
+        BlockMeta::new(self.name, self.blocking, self.error_policy, self.min_items)
     }
 }
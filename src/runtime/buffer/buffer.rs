@@ -1,27 +1,98 @@
 use futures::channel::mpsc::Sender;
+use std::any::type_name;
 use std::any::Any;
 use std::fmt::Debug;
+use std::time::Duration;
 use std::usize;
 
+use crate::anyhow::{bail, Result};
+use crate::runtime::ctrl_port::BufferDescription;
 use crate::runtime::BlockMessage;
 use crate::runtime::ItemTag;
 
 pub trait BufferBuilder: Send + Sync + Any {
+    /// Builds the buffer for a writer.
+    ///
+    /// Fails, if the buffer cannot be created, e.g., since it is backed by a system resource.
     fn build(
         &self,
         item_size: usize,
         writer_inbox: Sender<BlockMessage>,
         writer_output_id: usize,
-    ) -> BufferWriter;
+    ) -> Result<BufferWriter>;
+
+    /// Builds the buffer for a writer and readers that need at least `min_items` items per call to
+    /// `work()`.
+    ///
+    /// Buffers that do not expose all items contiguously have to make sure that the blocks can
+    /// access this many items at once.
+    fn build_with_min_items(
+        &self,
+        item_size: usize,
+        _min_items: usize,
+        writer_inbox: Sender<BlockMessage>,
+        writer_output_id: usize,
+    ) -> Result<BufferWriter> {
+        self.build(item_size, writer_inbox, writer_output_id)
+    }
+
+    /// The buffer supports only one reader, e.g., since it hands its memory to the reader.
+    fn sole_reader(&self) -> bool {
+        false
+    }
+
+    /// The buffer supports lossy readers, see [add_lossy_reader](BufferWriterHost::add_lossy_reader).
+    fn lossy_readers(&self) -> bool {
+        false
+    }
+
+    /// Description of the buffer for items of the given size, e.g., to report its capacity.
+    fn description(&self, item_size: usize) -> BufferDescription {
+        BufferDescription {
+            type_name: type_name::<Self>().to_string(),
+            item_size,
+            capacity: None,
+        }
+    }
+}
+
+/// Number of items that correspond to a latency at the given sample rate, at least one.
+pub(crate) fn latency_items(latency: Duration, sample_rate: f64) -> usize {
+    std::cmp::max((latency.as_secs_f64() * sample_rate).ceil() as usize, 1)
 }
 
 #[async_trait]
 pub trait BufferWriterHost: Send + Any + Debug {
+    /// Adds a reader.
+    ///
+    /// Fails, if the buffer cannot serve another reader.
     fn add_reader(
         &mut self,
         reader_inbox: Sender<BlockMessage>,
         reader_input_id: usize,
-    ) -> BufferReader;
+    ) -> Result<BufferReader>;
+
+    /// Adds a reader that never blocks the writer.
+    ///
+    /// If the reader falls behind, the oldest items that it did not read yet are dropped and the
+    /// reader gets a [Tag::Dropped](crate::runtime::Tag::Dropped) at the first item after the gap.
+    /// Fails, if the buffer does not support lossy readers.
+    fn add_lossy_reader(
+        &mut self,
+        _reader_inbox: Sender<BlockMessage>,
+        _reader_input_id: usize,
+    ) -> Result<BufferReader> {
+        bail!("buffer does not support lossy readers");
+    }
+
+    /// Removes a reader that was added with [add_reader](Self::add_reader).
+    ///
+    /// Used to disconnect stream edges of a running flowgraph.
+    fn remove_reader(
+        &mut self,
+        reader_inbox: &Sender<BlockMessage>,
+        reader_input_id: usize,
+    ) -> Result<()>;
 
     fn as_any(&mut self) -> &mut dyn Any;
 
@@ -38,11 +109,23 @@ pub trait BufferWriterHost: Send + Any + Debug {
 
 #[async_trait]
 pub trait BufferWriterCustom: Send + Any + Debug {
+    /// Adds a reader.
+    ///
+    /// Fails, if the buffer cannot serve another reader.
     fn add_reader(
         &mut self,
         reader_inbox: Sender<BlockMessage>,
         reader_input_id: usize,
-    ) -> BufferReader;
+    ) -> Result<BufferReader>;
+
+    /// Removes a reader that was added with [add_reader](Self::add_reader).
+    ///
+    /// Used to disconnect stream edges of a running flowgraph.
+    fn remove_reader(
+        &mut self,
+        reader_inbox: &Sender<BlockMessage>,
+        reader_input_id: usize,
+    ) -> Result<()>;
 
     fn as_any(&mut self) -> &mut dyn Any;
 
@@ -64,13 +147,35 @@ impl BufferWriter {
         &mut self,
         reader_inbox: Sender<BlockMessage>,
         reader_input_id: usize,
-    ) -> BufferReader {
+    ) -> Result<BufferReader> {
         match self {
             BufferWriter::Host(w) => w.add_reader(reader_inbox, reader_input_id),
             BufferWriter::Custom(w) => w.add_reader(reader_inbox, reader_input_id),
         }
     }
 
+    pub fn add_lossy_reader(
+        &mut self,
+        reader_inbox: Sender<BlockMessage>,
+        reader_input_id: usize,
+    ) -> Result<BufferReader> {
+        match self {
+            BufferWriter::Host(w) => w.add_lossy_reader(reader_inbox, reader_input_id),
+            BufferWriter::Custom(_) => bail!("buffer does not support lossy readers"),
+        }
+    }
+
+    pub fn remove_reader(
+        &mut self,
+        reader_inbox: &Sender<BlockMessage>,
+        reader_input_id: usize,
+    ) -> Result<()> {
+        match self {
+            BufferWriter::Host(w) => w.remove_reader(reader_inbox, reader_input_id),
+            BufferWriter::Custom(w) => w.remove_reader(reader_inbox, reader_input_id),
+        }
+    }
+
     pub fn try_as<W: 'static>(&mut self) -> Option<&mut W> {
         match self {
             BufferWriter::Host(w) => w.as_any().downcast_mut::<W>(),
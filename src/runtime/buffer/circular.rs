@@ -2,14 +2,19 @@ use futures::channel::mpsc::Sender;
 use futures::prelude::*;
 use std::any::Any;
 use std::fmt;
+use std::time::Duration;
 use vmcircbuffer::generic;
 
+use crate::anyhow::{bail, Result};
+use crate::runtime::buffer::latency_items;
+use crate::runtime::buffer::lossy::LossyQueue;
 use crate::runtime::buffer::BufferBuilder;
 use crate::runtime::buffer::BufferReader;
 use crate::runtime::buffer::BufferReaderHost;
 use crate::runtime::buffer::BufferWriter;
 use crate::runtime::buffer::BufferWriterHost;
 use crate::runtime::config;
+use crate::runtime::ctrl_port::BufferDescription;
 use crate::runtime::BlockMessage;
 use crate::runtime::ItemTag;
 
@@ -57,6 +62,8 @@ impl generic::Metadata for MyMetadata {
 #[derive(Clone, Debug, PartialEq, Hash)]
 pub struct Circular {
     min_bytes: usize,
+    min_items: usize,
+    max_items: Option<usize>,
 }
 
 impl Eq for Circular {}
@@ -65,10 +72,39 @@ impl Circular {
     pub fn new() -> Circular {
         Circular {
             min_bytes: config::config().buffer_size,
+            min_items: 0,
+            max_items: None,
         }
     }
     pub fn with_size(min_bytes: usize) -> Circular {
-        Circular { min_bytes }
+        Circular {
+            min_bytes,
+            min_items: 0,
+            max_items: None,
+        }
+    }
+    /// Buffer that holds at least `min_items` items.
+    pub fn with_items(min_items: usize) -> Circular {
+        Circular {
+            min_bytes: 0,
+            min_items,
+            max_items: None,
+        }
+    }
+    /// Buffer that queues at most the items of `latency` at the given sample rate.
+    ///
+    /// The memory is allocated in pages and might be larger, but the writer only gets space for
+    /// so many items. Blocks that need more output space per call to `work()` stall.
+    pub fn with_max_latency(latency: Duration, sample_rate: f64) -> Circular {
+        Circular {
+            min_bytes: 0,
+            min_items: 0,
+            max_items: Some(latency_items(latency, sample_rate)),
+        }
+    }
+
+    fn min_bytes(&self, item_size: usize) -> usize {
+        std::cmp::max(self.min_bytes, self.min_items * item_size)
     }
 }
 
@@ -84,19 +120,48 @@ impl BufferBuilder for Circular {
         item_size: usize,
         writer_inbox: Sender<BlockMessage>,
         writer_output_id: usize,
-    ) -> BufferWriter {
-        BufferWriter::Host(Box::new(Writer::new(
+    ) -> Result<BufferWriter> {
+        let mut writer = Writer::new(
             item_size,
-            self.min_bytes,
+            self.min_bytes(item_size),
             writer_inbox,
             writer_output_id,
-        )))
+        );
+        writer.max_items = self.max_items;
+        Ok(BufferWriter::Host(Box::new(writer)))
+    }
+
+    fn lossy_readers(&self) -> bool {
+        true
+    }
+
+    fn description(&self, item_size: usize) -> BufferDescription {
+        let capacity = buffer_size(item_size, self.min_bytes(item_size)) / item_size;
+        BufferDescription {
+            type_name: "Circular".to_string(),
+            item_size,
+            capacity: Some(self.max_items.map_or(capacity, |m| m.min(capacity))),
+        }
     }
 }
 
+/// Size of the buffer in bytes, a multiple of the page size and the item size.
+fn buffer_size(item_size: usize, min_bytes: usize) -> usize {
+    let page_size = vmcircbuffer::double_mapped_buffer::pagesize();
+    let mut buffer_size = page_size;
+
+    while (buffer_size < min_bytes) || (buffer_size % item_size != 0) {
+        buffer_size += page_size;
+    }
+    buffer_size
+}
+
 pub struct Writer {
     writer: generic::Writer<u8, MyNotifier, MyMetadata>,
     readers: Vec<(Sender<BlockMessage>, usize)>,
+    lossy_readers: Vec<LossyQueue>,
+    capacity: usize,
+    max_items: Option<usize>,
     item_size: usize,
     inbox: Sender<BlockMessage>,
     output_id: usize,
@@ -110,16 +175,14 @@ impl Writer {
         inbox: Sender<BlockMessage>,
         output_id: usize,
     ) -> Writer {
-        let page_size = vmcircbuffer::double_mapped_buffer::pagesize();
-        let mut buffer_size = page_size;
-
-        while (buffer_size < min_bytes) || (buffer_size % item_size != 0) {
-            buffer_size += page_size;
-        }
+        let buffer_size = buffer_size(item_size, min_bytes);
 
         Writer {
             writer: generic::Circular::with_capacity(buffer_size).unwrap(),
             readers: Vec::new(),
+            lossy_readers: Vec::new(),
+            capacity: buffer_size,
+            max_items: None,
             item_size,
             inbox,
             output_id,
@@ -131,6 +194,9 @@ impl Writer {
 impl fmt::Debug for Writer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("circular::Writer")
+            .field("capacity", &self.capacity)
+            .field("max_items", &self.max_items)
+            .field("lossy_readers", &self.lossy_readers.len())
             .field("item_size", &self.item_size)
             .field("output_id", &self.output_id)
             .field("finished", &self.finished)
@@ -140,7 +206,7 @@ impl fmt::Debug for Writer {
 
 #[async_trait]
 impl BufferWriterHost for Writer {
-    fn add_reader(&mut self, inbox: Sender<BlockMessage>, input_id: usize) -> BufferReader {
+    fn add_reader(&mut self, inbox: Sender<BlockMessage>, input_id: usize) -> Result<BufferReader> {
         let writer_notifier = MyNotifier {
             sender: self.inbox.clone(),
         };
@@ -153,13 +219,42 @@ impl BufferWriterHost for Writer {
 
         self.readers.push((inbox, input_id));
 
-        BufferReader::Host(Box::new(Reader {
+        Ok(BufferReader::Host(Box::new(Reader {
             reader,
             item_size: self.item_size,
             finished: false,
             writer_inbox: self.inbox.clone(),
             writer_output_id: self.output_id,
-        }))
+        })))
+    }
+
+    fn add_lossy_reader(
+        &mut self,
+        inbox: Sender<BlockMessage>,
+        input_id: usize,
+    ) -> Result<BufferReader> {
+        // lossy readers get a copy of the items, they are not registered with the buffer
+        let capacity = self.max_items.unwrap_or(self.capacity / self.item_size);
+        let (queue, reader) = LossyQueue::new(capacity, self.item_size, inbox, input_id);
+        self.lossy_readers.push(queue);
+        Ok(reader)
+    }
+
+    fn remove_reader(
+        &mut self,
+        reader_inbox: &Sender<BlockMessage>,
+        reader_input_id: usize,
+    ) -> Result<()> {
+        // the reader itself unregisters from the buffer, once it is dropped
+        let n = self.readers.len() + self.lossy_readers.len();
+        self.readers
+            .retain(|(inbox, id)| !(inbox.same_receiver(reader_inbox) && *id == reader_input_id));
+        self.lossy_readers
+            .retain(|q| !q.is_reader(reader_inbox, reader_input_id));
+        if self.readers.len() + self.lossy_readers.len() == n {
+            bail!("reader not connected");
+        }
+        Ok(())
     }
 
     fn as_any(&mut self) -> &mut dyn Any {
@@ -167,6 +262,12 @@ impl BufferWriterHost for Writer {
     }
 
     fn produce(&mut self, items: usize, mut tags: Vec<ItemTag>) {
+        if !self.lossy_readers.is_empty() {
+            let s = self.writer.slice(false);
+            for q in self.lossy_readers.iter_mut() {
+                q.push(&s[..items * self.item_size], &tags);
+            }
+        }
         for t in tags.iter_mut() {
             t.index *= self.item_size;
         }
@@ -175,7 +276,13 @@ impl BufferWriterHost for Writer {
 
     fn bytes(&mut self) -> (*mut u8, usize) {
         let s = self.writer.slice(false);
-        (s.as_mut_ptr(), s.len())
+        let mut len = s.len();
+        if let Some(max_items) = self.max_items {
+            // only expose the space that keeps the fill level within the bound
+            let fill = self.capacity - len;
+            len = std::cmp::min(len, (max_items * self.item_size).saturating_sub(fill));
+        }
+        (s.as_mut_ptr(), len)
     }
 
     async fn notify_finished(&mut self) {
@@ -188,6 +295,9 @@ impl BufferWriterHost for Writer {
                 i.0.send(BlockMessage::StreamInputDone { input_id: i.1 })
                     .await;
         }
+        for q in self.lossy_readers.iter_mut() {
+            q.notify_finished().await;
+        }
     }
 
     fn finish(&mut self) {
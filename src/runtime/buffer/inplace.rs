@@ -0,0 +1,538 @@
+use futures::channel::mpsc::Sender;
+use futures::prelude::*;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt;
+use std::mem;
+use std::slice;
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::anyhow::{bail, Result};
+use crate::runtime::buffer::BufferBuilder;
+use crate::runtime::buffer::BufferReader;
+use crate::runtime::buffer::BufferReaderHost;
+use crate::runtime::buffer::BufferWriter;
+use crate::runtime::buffer::BufferWriterHost;
+use crate::runtime::config;
+use crate::runtime::ctrl_port::BufferDescription;
+use crate::runtime::BlockMessage;
+use crate::runtime::ItemTag;
+
+/// Buffer that hands its memory to the reader, which can operate on it in place.
+///
+/// Like [Slab](crate::runtime::buffer::slab::Slab), the buffer passes blocks of memory from the
+/// writer to the reader. A block can [take](Reader::take) a full buffer from an in-place input,
+/// modify it in place, and [submit](Writer::submit) the same memory to an in-place output. The
+/// memory returns to the edge where it was allocated, once the last reader of the chain consumed
+/// it. Blocks that do not know about in-place buffers use them like any other buffer.
+///
+/// Since the reader owns the memory, the edge must have exactly one reader, which is checked when
+/// the flowgraph starts.
+#[derive(Debug, PartialEq, Hash)]
+pub struct InPlace {
+    min_bytes: usize,
+    n_buffer: usize,
+}
+
+impl Eq for InPlace {}
+
+impl InPlace {
+    pub fn new() -> InPlace {
+        InPlace {
+            min_bytes: config::config().buffer_size,
+            n_buffer: 4,
+        }
+    }
+
+    pub fn with_size(min_bytes: usize) -> InPlace {
+        InPlace {
+            min_bytes,
+            n_buffer: 4,
+        }
+    }
+
+    pub fn with_buffers(n_buffer: usize) -> InPlace {
+        InPlace {
+            min_bytes: config::config().buffer_size,
+            n_buffer,
+        }
+    }
+
+    fn buffer_size(&self, item_size: usize) -> usize {
+        let mut buffer_size = self.min_bytes;
+        while buffer_size % item_size != 0 {
+            buffer_size += 1;
+        }
+        buffer_size
+    }
+}
+
+impl Default for InPlace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BufferBuilder for InPlace {
+    fn build(
+        &self,
+        item_size: usize,
+        writer_inbox: Sender<BlockMessage>,
+        writer_output_id: usize,
+    ) -> Result<BufferWriter> {
+        let buffer_size = self.buffer_size(item_size);
+
+        Ok(BufferWriter::Host(Box::new(Writer {
+            current: None,
+            state: Arc::new(Mutex::new(State {
+                empty: Vec::new(),
+                full: VecDeque::new(),
+            })),
+            allocated: false,
+            buffer_size,
+            n_buffer: self.n_buffer,
+            item_size,
+            reader_inbox: None,
+            reader_input_id: None,
+            writer_inbox,
+            writer_output_id,
+            finished: false,
+        })))
+    }
+
+    fn sole_reader(&self) -> bool {
+        true
+    }
+
+    fn description(&self, item_size: usize) -> BufferDescription {
+        BufferDescription {
+            type_name: "InPlace".to_string(),
+            item_size,
+            capacity: Some(self.n_buffer * self.buffer_size(item_size) / item_size),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    empty: Vec<Memory>,
+    full: VecDeque<InPlaceBuffer>,
+}
+
+/// Alignment of the memory of a buffer, sufficient for all item types, incl. SIMD vectors.
+const ALIGN: usize = 64;
+
+#[derive(Clone, Copy)]
+#[repr(C, align(64))]
+struct Chunk([u8; ALIGN]);
+
+/// Zero-initialized memory of a buffer, aligned to [ALIGN].
+struct Memory {
+    chunks: Box<[Chunk]>,
+    len: usize,
+}
+
+impl Memory {
+    fn new(len: usize) -> Memory {
+        Memory {
+            chunks: vec![Chunk([0; ALIGN]); (len + ALIGN - 1) / ALIGN].into_boxed_slice(),
+            len,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn as_ptr(&self) -> *const u8 {
+        self.chunks.as_ptr() as *const u8
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.chunks.as_mut_ptr() as *mut u8
+    }
+}
+
+impl fmt::Debug for Memory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Memory").field("len", &self.len).finish()
+    }
+}
+
+/// Edge that allocated a buffer and gets it back, when it is dropped.
+#[derive(Clone)]
+struct Home {
+    state: Weak<Mutex<State>>,
+    writer_inbox: Sender<BlockMessage>,
+}
+
+impl fmt::Debug for Home {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Home").finish()
+    }
+}
+
+/// Block of memory, passed along a chain of in-place buffers.
+///
+/// Offsets are in bytes, tag indices in items. When the buffer is dropped, the memory returns to
+/// the edge that allocated it.
+#[derive(Debug)]
+pub struct InPlaceBuffer {
+    buffer: Option<Memory>,
+    start: usize,
+    end: usize,
+    tags: Vec<ItemTag>,
+    home: Home,
+}
+
+impl InPlaceBuffer {
+    /// Number of items of type `T` in the buffer.
+    pub fn len<T>(&self) -> usize {
+        (self.end - self.start) / mem::size_of::<T>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end == self.start
+    }
+
+    /// The items of the buffer.
+    ///
+    /// # Safety
+    ///
+    /// `T` has to match the item type of the buffer, i.e., the bytes have to be valid values of
+    /// `T`. Size and alignment are checked.
+    pub unsafe fn slice<T>(&mut self) -> &mut [T] {
+        let p = self.items_ptr::<T>();
+        slice::from_raw_parts_mut(p as *mut T, self.len::<T>())
+    }
+
+    /// Set the number of items of type `T`, e.g., after converting the items in place.
+    pub fn set_len<T>(&mut self, n: usize) {
+        let end = self.start + n * mem::size_of::<T>();
+        assert!(end <= self.buffer.as_ref().unwrap().len());
+        self.end = end;
+    }
+
+    /// Convert each item in place. The output type must not be larger than the input type.
+    ///
+    /// # Safety
+    ///
+    /// `A` has to match the item type of the buffer, i.e., the bytes have to be valid values of
+    /// `A`. Size and alignment of `A` and `B` are checked.
+    pub unsafe fn map<A, B>(&mut self, mut f: impl FnMut(&A) -> B) {
+        assert!(mem::size_of::<B>() <= mem::size_of::<A>());
+        let n = self.len::<A>();
+        let p = self.items_ptr::<A>();
+        assert_eq!(
+            p as usize % mem::align_of::<B>(),
+            0,
+            "misaligned output items"
+        );
+        // an output item never overlaps input items that are not yet read
+        for k in 0..n {
+            let a = (p as *const A).add(k).read();
+            (p as *mut B).add(k).write(f(&a));
+        }
+        self.set_len::<B>(n);
+    }
+
+    /// Pointer to the first item, checking that the buffer holds whole, aligned items of `T`.
+    fn items_ptr<T>(&mut self) -> *mut u8 {
+        assert_eq!(
+            (self.end - self.start) % mem::size_of::<T>(),
+            0,
+            "buffer does not hold whole items"
+        );
+        let p = unsafe { self.buffer.as_mut().unwrap().as_mut_ptr().add(self.start) };
+        assert_eq!(p as usize % mem::align_of::<T>(), 0, "misaligned items");
+        p
+    }
+
+    pub fn tags(&mut self) -> &mut Vec<ItemTag> {
+        &mut self.tags
+    }
+
+    /// Drop items from the start of the buffer.
+    fn advance(&mut self, bytes: usize, item_size: usize) {
+        self.start += bytes;
+        let items = bytes / item_size;
+        self.tags.retain(|t| t.index >= items);
+        self.tags.iter_mut().for_each(|t| t.index -= items);
+    }
+}
+
+impl Drop for InPlaceBuffer {
+    fn drop(&mut self) {
+        if let (Some(buffer), Some(state)) = (self.buffer.take(), self.home.state.upgrade()) {
+            state.lock().unwrap().empty.push(buffer);
+            let _ = self.home.writer_inbox.try_send(BlockMessage::Notify);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Writer {
+    current: Option<InPlaceBuffer>,
+    state: Arc<Mutex<State>>,
+    allocated: bool,
+    buffer_size: usize,
+    n_buffer: usize,
+    item_size: usize,
+    reader_inbox: Option<Sender<BlockMessage>>,
+    reader_input_id: Option<usize>,
+    writer_inbox: Sender<BlockMessage>,
+    writer_output_id: usize,
+    finished: bool,
+}
+
+impl Writer {
+    fn home(&self) -> Home {
+        Home {
+            state: Arc::downgrade(&self.state),
+            writer_inbox: self.writer_inbox.clone(),
+        }
+    }
+
+    /// Pass a full buffer to the reader, e.g., one that was taken from an in-place input.
+    pub fn submit(&mut self, buffer: InPlaceBuffer) {
+        assert_eq!((buffer.end - buffer.start) % self.item_size, 0);
+        if buffer.is_empty() {
+            return;
+        }
+
+        // keep the order of the items
+        self.flush();
+        self.state.lock().unwrap().full.push_back(buffer);
+        if let Some(inbox) = self.reader_inbox.as_mut() {
+            let _ = inbox.try_send(BlockMessage::Notify);
+        }
+    }
+
+    /// Hand the current, partially filled buffer to the reader.
+    fn flush(&mut self) {
+        if let Some(mut c) = self.current.take() {
+            if c.is_empty() {
+                self.state
+                    .lock()
+                    .unwrap()
+                    .empty
+                    .push(c.buffer.take().unwrap());
+            } else {
+                self.state.lock().unwrap().full.push_back(c);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl BufferWriterHost for Writer {
+    fn add_reader(
+        &mut self,
+        reader_inbox: Sender<BlockMessage>,
+        reader_input_id: usize,
+    ) -> Result<BufferReader> {
+        debug_assert!(self.reader_inbox.is_none());
+        debug_assert!(self.reader_input_id.is_none());
+
+        self.reader_inbox = Some(reader_inbox.clone());
+        self.reader_input_id = Some(reader_input_id);
+
+        Ok(BufferReader::Host(Box::new(Reader {
+            current: None,
+            state: self.state.clone(),
+            item_size: self.item_size,
+            reader_inbox,
+            writer_inbox: self.writer_inbox.clone(),
+            writer_output_id: self.writer_output_id,
+            finished: false,
+        })))
+    }
+
+    fn remove_reader(
+        &mut self,
+        reader_inbox: &Sender<BlockMessage>,
+        reader_input_id: usize,
+    ) -> Result<()> {
+        // full buffers stay queued for the next reader
+        match (self.reader_inbox.as_ref(), self.reader_input_id) {
+            (Some(inbox), Some(id))
+                if inbox.same_receiver(reader_inbox) && id == reader_input_id =>
+            {
+                self.reader_inbox = None;
+                self.reader_input_id = None;
+                Ok(())
+            }
+            _ => bail!("reader not connected"),
+        }
+    }
+
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn bytes(&mut self) -> (*mut u8, usize) {
+        if self.current.is_none() {
+            let mut state = self.state.lock().unwrap();
+            // buffers are only allocated, if the block does not just forward buffers
+            if !self.allocated {
+                self.allocated = true;
+                for _ in 0..self.n_buffer {
+                    state.empty.push(Memory::new(self.buffer_size));
+                }
+            }
+            if let Some(buffer) = state.empty.pop() {
+                drop(state);
+                self.current = Some(InPlaceBuffer {
+                    buffer: Some(buffer),
+                    start: 0,
+                    end: 0,
+                    tags: Vec::new(),
+                    home: self.home(),
+                });
+            } else {
+                return (std::ptr::null_mut::<u8>(), 0);
+            }
+        }
+
+        let c = self.current.as_mut().unwrap();
+        let buffer = c.buffer.as_mut().unwrap();
+        let len = buffer.len();
+        unsafe { (buffer.as_mut_ptr().add(c.end), len - c.end) }
+    }
+
+    fn produce(&mut self, amount: usize, mut tags: Vec<ItemTag>) {
+        debug_assert!(amount > 0);
+
+        let c = self.current.as_mut().unwrap();
+        let offset = c.end / self.item_size;
+        for t in tags.iter_mut() {
+            t.index += offset;
+        }
+        c.tags.append(&mut tags);
+        c.end += amount * self.item_size;
+        debug_assert!(c.end <= c.buffer.as_ref().unwrap().len());
+
+        if c.end == c.buffer.as_ref().unwrap().len() {
+            self.flush();
+            if let Some(inbox) = self.reader_inbox.as_mut() {
+                let _ = inbox.try_send(BlockMessage::Notify);
+            }
+
+            // make sure to be called again, if we have another buffer queued
+            if !self.state.lock().unwrap().empty.is_empty() {
+                let _ = self.writer_inbox.try_send(BlockMessage::Notify);
+            }
+        }
+    }
+
+    async fn notify_finished(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        self.flush();
+
+        if let (Some(inbox), Some(input_id)) = (self.reader_inbox.as_mut(), self.reader_input_id) {
+            let _ = inbox.send(BlockMessage::StreamInputDone { input_id }).await;
+        }
+    }
+
+    fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    fn finished(&self) -> bool {
+        self.finished
+    }
+}
+
+#[derive(Debug)]
+pub struct Reader {
+    current: Option<InPlaceBuffer>,
+    state: Arc<Mutex<State>>,
+    item_size: usize,
+    reader_inbox: Sender<BlockMessage>,
+    writer_inbox: Sender<BlockMessage>,
+    writer_output_id: usize,
+    finished: bool,
+}
+
+impl Reader {
+    /// Take the next full buffer, e.g., to process it in place and submit it to an in-place
+    /// output. Dropping the buffer consumes its items.
+    pub fn take(&mut self) -> Option<InPlaceBuffer> {
+        if let Some(c) = self.current.take() {
+            return Some(c);
+        }
+        let mut state = self.state.lock().unwrap();
+        let b = state.full.pop_front();
+        if b.is_some() && !state.full.is_empty() {
+            let _ = self.reader_inbox.try_send(BlockMessage::Notify);
+        }
+        b
+    }
+}
+
+#[async_trait]
+impl BufferReaderHost for Reader {
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn bytes(&mut self) -> (*const u8, usize, Vec<ItemTag>) {
+        if self.current.is_none() {
+            self.current = self.state.lock().unwrap().full.pop_front();
+        }
+
+        if let Some(c) = self.current.as_mut() {
+            unsafe {
+                (
+                    c.buffer.as_ref().unwrap().as_ptr().add(c.start),
+                    c.end - c.start,
+                    c.tags.clone(),
+                )
+            }
+        } else {
+            (std::ptr::null::<u8>(), 0, Vec::new())
+        }
+    }
+
+    fn consume(&mut self, amount: usize) {
+        debug_assert!(amount > 0);
+
+        let c = self.current.as_mut().unwrap();
+        debug_assert!(amount * self.item_size <= c.end - c.start);
+        c.advance(amount * self.item_size, self.item_size);
+
+        if c.is_empty() {
+            // returns the memory to the edge that allocated it
+            self.current = None;
+
+            // make sure to be called again, if we have another buffer queued
+            if !self.state.lock().unwrap().full.is_empty() {
+                let _ = self.reader_inbox.try_send(BlockMessage::Notify);
+            }
+        }
+    }
+
+    async fn notify_finished(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        let _ = self
+            .writer_inbox
+            .send(BlockMessage::StreamOutputDone {
+                output_id: self.writer_output_id,
+            })
+            .await;
+    }
+
+    fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    fn finished(&self) -> bool {
+        self.finished && self.state.lock().unwrap().full.is_empty()
+    }
+}
@@ -0,0 +1,215 @@
+use futures::channel::mpsc::Sender;
+use futures::prelude::*;
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+
+use crate::runtime::buffer::BufferReader;
+use crate::runtime::buffer::BufferReaderHost;
+use crate::runtime::BlockMessage;
+use crate::runtime::ItemTag;
+use crate::runtime::Tag;
+
+// everything is measured in items, e.g., offsets, capacity, dropped items
+
+#[derive(Debug)]
+struct State {
+    items: Vec<u8>,
+    tags: Vec<ItemTag>,
+    // items dropped in front of the queued items
+    dropped: usize,
+    closed: bool,
+}
+
+/// Queue of a lossy reader, filled by the writer.
+///
+/// The writer copies the items it produces to the queue. If the reader falls behind and the
+/// queue is full, the oldest queued items are dropped and the reader gets a [Tag::Dropped] at the
+/// first item after the gap. The reader never blocks the writer.
+#[derive(Debug)]
+pub(crate) struct LossyQueue {
+    state: Arc<Mutex<State>>,
+    capacity: usize,
+    item_size: usize,
+    inbox: Sender<BlockMessage>,
+    input_id: usize,
+}
+
+impl LossyQueue {
+    /// Creates a queue for `capacity` items and the corresponding reader.
+    pub(crate) fn new(
+        capacity: usize,
+        item_size: usize,
+        inbox: Sender<BlockMessage>,
+        input_id: usize,
+    ) -> (LossyQueue, BufferReader) {
+        let state = Arc::new(Mutex::new(State {
+            items: Vec::with_capacity(capacity * item_size),
+            tags: Vec::new(),
+            dropped: 0,
+            closed: false,
+        }));
+
+        let reader = BufferReader::Host(Box::new(Reader {
+            current: Vec::with_capacity(capacity * item_size),
+            offset: 0,
+            tags: Vec::new(),
+            state: state.clone(),
+            item_size,
+            inbox: inbox.clone(),
+            finished: false,
+        }));
+
+        (
+            LossyQueue {
+                state,
+                capacity: std::cmp::max(capacity, 1),
+                item_size,
+                inbox,
+                input_id,
+            },
+            reader,
+        )
+    }
+
+    pub(crate) fn is_reader(&self, inbox: &Sender<BlockMessage>, input_id: usize) -> bool {
+        self.inbox.same_receiver(inbox) && self.input_id == input_id
+    }
+
+    /// Queues produced items, dropping the oldest items that do not fit.
+    pub(crate) fn push(&mut self, data: &[u8], tags: &[ItemTag]) {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return;
+        }
+
+        let n = data.len() / self.item_size;
+        let queued = state.items.len() / self.item_size;
+        let overflow = (queued + n).saturating_sub(self.capacity);
+        let from_queue = std::cmp::min(overflow, queued);
+        let skip = overflow - from_queue;
+
+        if overflow > 0 {
+            state.items.drain(..from_queue * self.item_size);
+            state.tags.retain(|t| t.index >= from_queue);
+            for t in state.tags.iter_mut() {
+                t.index -= from_queue;
+            }
+            state.dropped += overflow;
+        }
+
+        let offset = state.items.len() / self.item_size;
+        state
+            .items
+            .extend_from_slice(&data[skip * self.item_size..n * self.item_size]);
+        state
+            .tags
+            .extend(tags.iter().filter(|t| t.index >= skip).map(|t| ItemTag {
+                index: t.index - skip + offset,
+                tag: t.tag.clone(),
+            }));
+        drop(state);
+
+        let _ = self.inbox.try_send(BlockMessage::Notify);
+    }
+
+    pub(crate) async fn notify_finished(&mut self) {
+        let _ = self
+            .inbox
+            .send(BlockMessage::StreamInputDone {
+                input_id: self.input_id,
+            })
+            .await;
+    }
+}
+
+/// Reader of a [LossyQueue].
+///
+/// The reader takes all queued items at once, so that the writer can continue to fill the queue
+/// while the block processes them. When the reader finishes, the writer just stops queueing
+/// items, i.e., a lossy reader does not finish the writer.
+#[derive(Debug)]
+pub struct Reader {
+    current: Vec<u8>,
+    offset: usize,
+    tags: Vec<ItemTag>,
+    state: Arc<Mutex<State>>,
+    item_size: usize,
+    inbox: Sender<BlockMessage>,
+    finished: bool,
+}
+
+#[async_trait]
+impl BufferReaderHost for Reader {
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn bytes(&mut self) -> (*const u8, usize, Vec<ItemTag>) {
+        if self.offset * self.item_size == self.current.len() {
+            let mut state = self.state.lock().unwrap();
+            if !state.items.is_empty() {
+                self.current.clear();
+                std::mem::swap(&mut self.current, &mut state.items);
+                self.tags = std::mem::take(&mut state.tags);
+                if state.dropped > 0 {
+                    self.tags.insert(
+                        0,
+                        ItemTag {
+                            index: 0,
+                            tag: Tag::Dropped(state.dropped),
+                        },
+                    );
+                    state.dropped = 0;
+                }
+                self.offset = 0;
+            }
+        }
+
+        unsafe {
+            (
+                self.current.as_ptr().add(self.offset * self.item_size),
+                self.current.len() - self.offset * self.item_size,
+                self.tags.clone(),
+            )
+        }
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.offset += amount;
+        debug_assert!(self.offset * self.item_size <= self.current.len());
+
+        self.tags.retain(|t| t.index >= amount);
+        for t in self.tags.iter_mut() {
+            t.index -= amount;
+        }
+
+        // make sure to be called again, if the writer queued more items
+        if self.offset * self.item_size == self.current.len()
+            && !self.state.lock().unwrap().items.is_empty()
+        {
+            let _ = self.inbox.try_send(BlockMessage::Notify);
+        }
+    }
+
+    async fn notify_finished(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        state.items.clear();
+        state.tags.clear();
+    }
+
+    fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    fn finished(&self) -> bool {
+        // items that were taken from the queue have to be consumed, too
+        self.finished
+            && self.offset * self.item_size == self.current.len()
+            && self.state.lock().unwrap().items.is_empty()
+    }
+}
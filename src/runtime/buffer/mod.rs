@@ -1,5 +1,6 @@
 #[allow(clippy::module_inception)]
 mod buffer;
+pub(crate) use buffer::latency_items;
 pub use buffer::BufferBuilder;
 pub use buffer::BufferReader;
 pub use buffer::BufferReaderCustom;
@@ -8,12 +9,24 @@ pub use buffer::BufferWriter;
 pub use buffer::BufferWriterCustom;
 pub use buffer::BufferWriterHost;
 
+mod lossy;
+
 #[cfg(not(target_arch = "wasm32"))]
 pub mod circular;
 
 // ===================== SLAB ========================
 pub mod slab;
 
+// ====================== RING =======================
+pub mod ring;
+
+// ==================== IN PLACE =====================
+pub mod inplace;
+
+// =============== SHARED MEMORY =====================
+#[cfg(target_os = "linux")]
+pub mod shm;
+
 // ==================== VULKAN =======================
 #[cfg(feature = "vulkan")]
 pub mod vulkan;
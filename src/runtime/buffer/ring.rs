@@ -0,0 +1,450 @@
+use futures::channel::mpsc::Sender;
+use futures::prelude::*;
+use std::any::Any;
+use std::fmt;
+use std::ptr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::anyhow::{bail, Result};
+use crate::runtime::buffer::BufferBuilder;
+use crate::runtime::buffer::BufferReader;
+use crate::runtime::buffer::BufferReaderHost;
+use crate::runtime::buffer::BufferWriter;
+use crate::runtime::buffer::BufferWriterHost;
+use crate::runtime::config;
+use crate::runtime::ctrl_port::BufferDescription;
+use crate::runtime::BlockMessage;
+use crate::runtime::ItemTag;
+
+// positions are absolute byte counts, they only increase and are mapped to the memory modulo the
+// capacity
+
+/// Ring buffer with one writer and one or more readers.
+///
+/// Unlike [Circular](crate::runtime::buffer::circular::Circular), the buffer does not need
+/// double-mapped memory. At the end of the memory, blocks only see the items up to the
+/// wraparound, unless the buffer has a window. The window is additional memory behind the end of
+/// the ring that mirrors its start, allowing blocks to access up to `window` bytes contiguously
+/// across the wraparound at the cost of copying the items that are written to the start of the
+/// ring. The window is at least as large as the
+/// [min_items](crate::runtime::BlockMetaBuilder::min_items) of the connected blocks, so that they
+/// can always make progress. Readers that are connected to a running flowgraph after the buffer
+/// was created, have to fit in the existing window.
+///
+/// Writer and readers exchange their positions through atomics, while the tags are kept in a
+/// list per reader that is protected by a mutex. The lock is only taken if there are tags.
+#[derive(Debug, PartialEq, Hash)]
+pub struct Ring {
+    min_bytes: usize,
+    window: usize,
+}
+
+impl Eq for Ring {}
+
+impl Ring {
+    pub fn new() -> Ring {
+        Ring {
+            min_bytes: config::config().buffer_size,
+            window: 0,
+        }
+    }
+
+    pub fn with_size(min_bytes: usize) -> Ring {
+        Ring {
+            min_bytes,
+            window: 0,
+        }
+    }
+
+    pub fn with_window(min_bytes: usize, window: usize) -> Ring {
+        Ring { min_bytes, window }
+    }
+}
+
+impl Default for Ring {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BufferBuilder for Ring {
+    fn build(
+        &self,
+        item_size: usize,
+        writer_inbox: Sender<BlockMessage>,
+        writer_output_id: usize,
+    ) -> Result<BufferWriter> {
+        self.build_with_min_items(item_size, 1, writer_inbox, writer_output_id)
+    }
+
+    fn build_with_min_items(
+        &self,
+        item_size: usize,
+        min_items: usize,
+        writer_inbox: Sender<BlockMessage>,
+        writer_output_id: usize,
+    ) -> Result<BufferWriter> {
+        Ok(BufferWriter::Host(Box::new(Writer::new(
+            item_size,
+            self.min_bytes,
+            std::cmp::max(self.window, min_items * item_size),
+            writer_inbox,
+            writer_output_id,
+        ))))
+    }
+
+    fn description(&self, item_size: usize) -> BufferDescription {
+        BufferDescription {
+            type_name: "Ring".to_string(),
+            item_size,
+            capacity: Some(capacity(item_size, self.min_bytes) / item_size),
+        }
+    }
+}
+
+/// Capacity of the ring in bytes, a multiple of the item size.
+fn capacity(item_size: usize, min_bytes: usize) -> usize {
+    let mut capacity = std::cmp::max(min_bytes, item_size);
+    while capacity % item_size != 0 {
+        capacity += 1;
+    }
+    capacity
+}
+
+/// Memory of the ring, followed by the window.
+struct Memory {
+    ptr: *mut u8,
+    len: usize,
+}
+
+impl Memory {
+    fn new(len: usize) -> Memory {
+        let b = vec![0u8; len].into_boxed_slice();
+        Memory {
+            ptr: Box::into_raw(b) as *mut u8,
+            len,
+        }
+    }
+}
+
+impl Drop for Memory {
+    fn drop(&mut self) {
+        unsafe {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+                self.ptr, self.len,
+            )));
+        }
+    }
+}
+
+// the writer and the readers only access disjoint regions of the memory
+unsafe impl Send for Memory {}
+unsafe impl Sync for Memory {}
+
+#[derive(Debug)]
+struct ReaderState {
+    position: AtomicU64,
+    // tags with absolute item indices
+    tags: Mutex<Vec<ItemTag>>,
+    n_tags: AtomicUsize,
+}
+
+pub struct Writer {
+    memory: Arc<Memory>,
+    capacity: usize,
+    window: usize,
+    position: Arc<AtomicU64>,
+    readers: Vec<(Arc<ReaderState>, Sender<BlockMessage>, usize)>,
+    item_size: usize,
+    inbox: Sender<BlockMessage>,
+    output_id: usize,
+    finished: bool,
+}
+
+impl Writer {
+    pub fn new(
+        item_size: usize,
+        min_bytes: usize,
+        window: usize,
+        inbox: Sender<BlockMessage>,
+        output_id: usize,
+    ) -> Writer {
+        let capacity = capacity(item_size, min_bytes);
+        let mut window = std::cmp::min(window, capacity);
+        while window % item_size != 0 {
+            window += 1;
+        }
+
+        Writer {
+            memory: Arc::new(Memory::new(capacity + window)),
+            capacity,
+            window,
+            position: Arc::new(AtomicU64::new(0)),
+            readers: Vec::new(),
+            item_size,
+            inbox,
+            output_id,
+            finished: false,
+        }
+    }
+
+    /// Copy bytes that were written to the start of the ring to the window and vice versa.
+    fn mirror(&mut self, offset: usize, bytes: usize) {
+        let base = self.memory.ptr;
+        unsafe {
+            if offset + bytes > self.capacity {
+                let from = std::cmp::max(offset, self.capacity);
+                ptr::copy_nonoverlapping(
+                    base.add(from),
+                    base.add(from - self.capacity),
+                    offset + bytes - from,
+                );
+            }
+            if offset < self.window {
+                let end = std::cmp::min(offset + bytes, self.window);
+                ptr::copy_nonoverlapping(
+                    base.add(offset),
+                    base.add(self.capacity + offset),
+                    end - offset,
+                );
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Writer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ring::Writer")
+            .field("capacity", &self.capacity)
+            .field("window", &self.window)
+            .field("item_size", &self.item_size)
+            .field("output_id", &self.output_id)
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl BufferWriterHost for Writer {
+    fn add_reader(&mut self, inbox: Sender<BlockMessage>, input_id: usize) -> Result<BufferReader> {
+        let state = Arc::new(ReaderState {
+            position: AtomicU64::new(self.position.load(Ordering::Acquire)),
+            tags: Mutex::new(Vec::new()),
+            n_tags: AtomicUsize::new(0),
+        });
+
+        self.readers.push((state.clone(), inbox.clone(), input_id));
+
+        Ok(BufferReader::Host(Box::new(Reader {
+            memory: self.memory.clone(),
+            capacity: self.capacity,
+            window: self.window,
+            writer_position: self.position.clone(),
+            state,
+            item_size: self.item_size,
+            finished: false,
+            inbox,
+            writer_inbox: self.inbox.clone(),
+            writer_output_id: self.output_id,
+        })))
+    }
+
+    fn remove_reader(
+        &mut self,
+        reader_inbox: &Sender<BlockMessage>,
+        reader_input_id: usize,
+    ) -> Result<()> {
+        let n = self.readers.len();
+        self.readers.retain(|(_, inbox, id)| {
+            !(inbox.same_receiver(reader_inbox) && *id == reader_input_id)
+        });
+        if self.readers.len() == n {
+            bail!("reader not connected");
+        }
+        Ok(())
+    }
+
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn produce(&mut self, items: usize, tags: Vec<ItemTag>) {
+        let position = self.position.load(Ordering::Relaxed);
+        let offset = (position % self.capacity as u64) as usize;
+        let bytes = items * self.item_size;
+        self.mirror(offset, bytes);
+
+        if !tags.is_empty() {
+            let index = (position / self.item_size as u64) as usize;
+            for (r, _, _) in self.readers.iter() {
+                let mut t = r.tags.lock().unwrap();
+                t.extend(tags.iter().map(|x| ItemTag {
+                    index: x.index + index,
+                    tag: x.tag.clone(),
+                }));
+                r.n_tags.store(t.len(), Ordering::Release);
+            }
+        }
+
+        self.position
+            .store(position + bytes as u64, Ordering::Release);
+
+        for (_, inbox, _) in self.readers.iter_mut() {
+            let _ = inbox.try_send(BlockMessage::Notify);
+        }
+    }
+
+    fn bytes(&mut self) -> (*mut u8, usize) {
+        let position = self.position.load(Ordering::Relaxed);
+        let slowest = self
+            .readers
+            .iter()
+            .map(|(r, _, _)| r.position.load(Ordering::Acquire))
+            .min()
+            .unwrap_or(position);
+
+        let space = self.capacity - (position - slowest) as usize;
+        let offset = (position % self.capacity as u64) as usize;
+        let len = std::cmp::min(space, self.capacity + self.window - offset);
+
+        unsafe { (self.memory.ptr.add(offset), len) }
+    }
+
+    async fn notify_finished(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        for (_, inbox, input_id) in self.readers.iter_mut() {
+            let _ = inbox
+                .send(BlockMessage::StreamInputDone {
+                    input_id: *input_id,
+                })
+                .await;
+        }
+    }
+
+    fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    fn finished(&self) -> bool {
+        self.finished
+    }
+}
+
+pub struct Reader {
+    memory: Arc<Memory>,
+    capacity: usize,
+    window: usize,
+    writer_position: Arc<AtomicU64>,
+    state: Arc<ReaderState>,
+    item_size: usize,
+    finished: bool,
+    inbox: Sender<BlockMessage>,
+    writer_inbox: Sender<BlockMessage>,
+    writer_output_id: usize,
+}
+
+impl Reader {
+    /// Offset of the read position in the memory, bytes available, and bytes accessible without
+    /// crossing the end of the window.
+    fn available(&self, position: u64) -> (usize, usize, usize) {
+        let available = (self.writer_position.load(Ordering::Acquire) - position) as usize;
+        let offset = (position % self.capacity as u64) as usize;
+        let len = std::cmp::min(available, self.capacity + self.window - offset);
+        (offset, available, len)
+    }
+}
+
+#[async_trait]
+impl BufferReaderHost for Reader {
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn bytes(&mut self) -> (*const u8, usize, Vec<ItemTag>) {
+        let position = self.state.position.load(Ordering::Relaxed);
+        let (offset, _, len) = self.available(position);
+
+        let mut tags = Vec::new();
+        if self.state.n_tags.load(Ordering::Acquire) > 0 {
+            let start = (position / self.item_size as u64) as usize;
+            let end = start + len / self.item_size;
+            tags.extend(
+                self.state
+                    .tags
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .filter(|t| t.index >= start && t.index < end)
+                    .map(|t| ItemTag {
+                        index: t.index - start,
+                        tag: t.tag.clone(),
+                    }),
+            );
+        }
+
+        unsafe { (self.memory.ptr.add(offset) as *const u8, len, tags) }
+    }
+
+    fn consume(&mut self, amount: usize) {
+        let position =
+            self.state.position.load(Ordering::Relaxed) + (amount * self.item_size) as u64;
+
+        if self.state.n_tags.load(Ordering::Acquire) > 0 {
+            let index = (position / self.item_size as u64) as usize;
+            let mut t = self.state.tags.lock().unwrap();
+            t.retain(|x| x.index >= index);
+            self.state.n_tags.store(t.len(), Ordering::Release);
+        }
+
+        self.state.position.store(position, Ordering::Release);
+        let _ = self.writer_inbox.try_send(BlockMessage::Notify);
+
+        // make sure to be called again for items behind the wraparound
+        if self.available(position).1 > 0 {
+            let _ = self.inbox.try_send(BlockMessage::Notify);
+        }
+    }
+
+    async fn notify_finished(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        let _ = self
+            .writer_inbox
+            .send(BlockMessage::StreamOutputDone {
+                output_id: self.writer_output_id,
+            })
+            .await;
+    }
+
+    fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    fn finished(&self) -> bool {
+        // the block might not have seen the items behind the wraparound
+        if self.finished {
+            let (_, available, len) = self.available(self.state.position.load(Ordering::Relaxed));
+            available == len
+        } else {
+            false
+        }
+    }
+}
+
+impl fmt::Debug for Reader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ring::Reader")
+            .field("capacity", &self.capacity)
+            .field("window", &self.window)
+            .field("item_size", &self.item_size)
+            .field("writer_output_id", &self.writer_output_id)
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
@@ -0,0 +1,883 @@
+use futures::channel::mpsc::Sender;
+use futures::prelude::*;
+use std::any::Any;
+use std::cell::UnsafeCell;
+use std::ffi::CString;
+use std::fmt;
+use std::ptr;
+use std::sync::atomic::{fence, AtomicBool, AtomicI32, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::anyhow::{anyhow, bail, Context, Result};
+use crate::runtime::buffer::BufferBuilder;
+use crate::runtime::buffer::BufferReader;
+use crate::runtime::buffer::BufferReaderHost;
+use crate::runtime::buffer::BufferWriter;
+use crate::runtime::buffer::BufferWriterHost;
+use crate::runtime::config;
+use crate::runtime::ctrl_port::BufferDescription;
+use crate::runtime::BlockMessage;
+use crate::runtime::ItemTag;
+use crate::runtime::Tag;
+
+// The shared memory object holds a header, followed by the items. The items are mapped twice
+// back-to-back, so that readers and the writer always see contiguous memory, like with the
+// Circular buffer. Positions are absolute byte counts that only increase. Tags are serialized
+// to a ring of fixed-size slots in the header.
+
+const MAGIC: u64 = 0x4675_7475_7265_5344;
+const VERSION: u64 = 1;
+const MAX_READERS: usize = 8;
+const TAG_SLOTS: usize = 1024;
+const TAG_BYTES: usize = 240;
+// how long blocking waits take at most, before checking the state again
+const POLL: Duration = Duration::from_millis(100);
+
+const SLOT_FREE: u32 = 0;
+const SLOT_ATTACHING: u32 = 1;
+const SLOT_ACTIVE: u32 = 2;
+
+#[repr(C)]
+struct Header {
+    magic: AtomicU64,
+    version: u64,
+    item_size: u64,
+    capacity: u64,
+    position: AtomicU64,
+    n_tags: AtomicU64,
+    finished: AtomicU32,
+    writer_waiting: AtomicU32,
+    writer_sem: UnsafeCell<libc::sem_t>,
+    readers: [ReaderSlot; MAX_READERS],
+    tags: [TagSlot; TAG_SLOTS],
+}
+
+#[repr(C)]
+struct ReaderSlot {
+    state: AtomicU32,
+    pid: AtomicI32,
+    position: AtomicU64,
+    waiting: AtomicU32,
+    sem: UnsafeCell<libc::sem_t>,
+}
+
+// Tag slots are a seqlock: for tag n, the writer sets seq to 2 * (n + 1) - 1, writes the slot, and
+// sets seq to 2 * (n + 1). A reader copies the slot and checks that seq did not change. The data
+// is copied through atomics, since the writer can overwrite the slot while a reader copies it.
+#[repr(C)]
+struct TagSlot {
+    seq: AtomicU64,
+    index: AtomicU64,
+    len: AtomicU32,
+    data: [AtomicU64; TAG_BYTES / 8],
+}
+
+/// Buffer in POSIX shared memory that other processes can attach to.
+///
+/// The buffer is used like any other buffer for an edge of the flowgraph. In addition, it creates
+/// the shared memory object `name`, to which readers in other processes can attach, e.g., with a
+/// [ShmSource](crate::blocks::ShmSource). The buffer supports eight readers in total. The writer waits for the attached readers but
+/// detaches readers of processes that terminate, so that decoders in other processes can crash
+/// and restart. Readers start with the items produced after they attach. [Tags](ItemTag) are
+/// serialized alongside the items; tags that cannot be serialized, i.e.,
+/// [Tag::NamedAny](crate::runtime::Tag::NamedAny) and tags larger than 240 bytes, are dropped.
+///
+/// Building the buffer fails, if the shared memory object already exists, unless the buffer is
+/// configured to [unlink](Shm::unlink_existing) it.
+#[derive(Debug, PartialEq, Hash)]
+pub struct Shm {
+    name: String,
+    min_bytes: usize,
+    unlink_existing: bool,
+}
+
+impl Eq for Shm {}
+
+impl Shm {
+    pub fn new(name: impl Into<String>) -> Shm {
+        Shm {
+            name: name.into(),
+            min_bytes: config::config().buffer_size,
+            unlink_existing: false,
+        }
+    }
+
+    pub fn with_size(name: impl Into<String>, min_bytes: usize) -> Shm {
+        Shm {
+            name: name.into(),
+            min_bytes,
+            unlink_existing: false,
+        }
+    }
+
+    /// Unlink an existing shared memory object of the same name, e.g., a stale object of a
+    /// crashed process, instead of failing.
+    ///
+    /// Readers that are attached to the existing object keep it alive but will not see new items.
+    #[must_use]
+    pub fn unlink_existing(mut self) -> Shm {
+        self.unlink_existing = true;
+        self
+    }
+}
+
+impl BufferBuilder for Shm {
+    fn build(
+        &self,
+        item_size: usize,
+        writer_inbox: Sender<BlockMessage>,
+        writer_output_id: usize,
+    ) -> Result<BufferWriter> {
+        Ok(BufferWriter::Host(Box::new(Writer::new(
+            &self.name,
+            item_size,
+            self.min_bytes,
+            self.unlink_existing,
+            writer_inbox,
+            writer_output_id,
+        )?)))
+    }
+
+    fn description(&self, item_size: usize) -> BufferDescription {
+        BufferDescription {
+            type_name: "Shm".to_string(),
+            item_size,
+            capacity: Some(capacity(item_size, self.min_bytes) / item_size),
+        }
+    }
+}
+
+fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+/// Size of the header in bytes, a multiple of the page size.
+fn header_len() -> usize {
+    let page_size = page_size();
+    let mut len = page_size;
+    while len < std::mem::size_of::<Header>() {
+        len += page_size;
+    }
+    len
+}
+
+/// Size of the item memory in bytes, a multiple of the page size and the item size.
+fn capacity(item_size: usize, min_bytes: usize) -> usize {
+    let page_size = page_size();
+    let mut capacity = page_size;
+    while (capacity < min_bytes) || (capacity % item_size != 0) {
+        capacity += page_size;
+    }
+    capacity
+}
+
+/// Name of the shared memory object, which has to start with a slash.
+fn object_name(name: &str) -> Result<CString> {
+    let name = if name.starts_with('/') {
+        name.to_string()
+    } else {
+        format!("/{}", name)
+    };
+    CString::new(name).context("invalid shared memory name")
+}
+
+fn errno() -> i32 {
+    std::io::Error::last_os_error().raw_os_error().unwrap_or(0)
+}
+
+/// Device and inode of an open file, to tell if a name still refers to the same object.
+fn file_id(fd: i32) -> Option<(u64, u64)> {
+    unsafe {
+        let mut stat: libc::stat = std::mem::zeroed();
+        if libc::fstat(fd, &mut stat) == 0 {
+            Some((stat.st_dev as u64, stat.st_ino as u64))
+        } else {
+            None
+        }
+    }
+}
+
+/// Mapping of the shared memory object.
+struct Mapping {
+    ptr: *mut u8,
+    len: usize,
+    header_len: usize,
+    capacity: usize,
+}
+
+impl Mapping {
+    /// Maps the object, with the item memory mapped twice.
+    unsafe fn new(fd: i32, header_len: usize, capacity: usize) -> Result<Mapping> {
+        let len = header_len + 2 * capacity;
+        let ptr = libc::mmap(
+            ptr::null_mut(),
+            len,
+            libc::PROT_NONE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+        if ptr == libc::MAP_FAILED {
+            bail!("cannot reserve memory ({})", errno());
+        }
+        let mapping = Mapping {
+            ptr: ptr as *mut u8,
+            len,
+            header_len,
+            capacity,
+        };
+
+        for (offset, len, file_offset) in [
+            (0, header_len, 0),
+            (header_len, capacity, header_len),
+            (header_len + capacity, capacity, header_len),
+        ] {
+            let p = libc::mmap(
+                mapping.ptr.add(offset) as *mut libc::c_void,
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED | libc::MAP_FIXED,
+                fd,
+                file_offset as libc::off_t,
+            );
+            if p == libc::MAP_FAILED {
+                bail!("cannot map shared memory ({})", errno());
+            }
+        }
+
+        Ok(mapping)
+    }
+
+    fn header(&self) -> &Header {
+        unsafe { &*(self.ptr as *const Header) }
+    }
+
+    fn slot(&self, i: usize) -> &ReaderSlot {
+        &self.header().readers[i]
+    }
+
+    fn items(&self, position: u64) -> *mut u8 {
+        unsafe {
+            self.ptr
+                .add(self.header_len + (position % self.capacity as u64) as usize)
+        }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, self.len);
+        }
+    }
+}
+
+// the writer and the readers only access disjoint regions of the items, the header is
+// synchronized with atomics and semaphores
+unsafe impl Send for Mapping {}
+unsafe impl Sync for Mapping {}
+
+fn sem_post(sem: &UnsafeCell<libc::sem_t>) {
+    unsafe {
+        libc::sem_post(sem.get());
+    }
+}
+
+/// Waits for the semaphore at most [POLL], returning `true` if it was posted.
+fn sem_wait(sem: &UnsafeCell<libc::sem_t>) -> bool {
+    unsafe {
+        let mut t: libc::timespec = std::mem::zeroed();
+        libc::clock_gettime(libc::CLOCK_REALTIME, &mut t);
+        let mut nsec = t.tv_nsec as i64 + i64::from(POLL.subsec_nanos());
+        if nsec >= 1_000_000_000 {
+            t.tv_sec += 1;
+            nsec -= 1_000_000_000;
+        }
+        t.tv_nsec = nsec as _;
+        libc::sem_timedwait(sem.get(), &t) == 0
+    }
+}
+
+/// Checks, if the process of a reader terminated.
+fn terminated(pid: i32) -> bool {
+    unsafe { libc::kill(pid, 0) == -1 && errno() == libc::ESRCH }
+}
+
+/// Wakes the writer and detaches readers of terminated processes.
+fn watch(mapping: Arc<Mapping>, stop: Arc<AtomicBool>, mut inbox: Sender<BlockMessage>) {
+    let header = mapping.header();
+    while !stop.load(Ordering::Acquire) {
+        let mut notify = sem_wait(&header.writer_sem);
+
+        for slot in header.readers.iter() {
+            if slot.state.load(Ordering::Acquire) == SLOT_ACTIVE
+                && terminated(slot.pid.load(Ordering::Relaxed))
+                && slot
+                    .state
+                    .compare_exchange(SLOT_ACTIVE, SLOT_FREE, Ordering::AcqRel, Ordering::Relaxed)
+                    .is_ok()
+            {
+                warn!("shm buffer: reader of terminated process detached");
+                notify = true;
+            }
+        }
+
+        if notify || header.writer_waiting.load(Ordering::Acquire) == 1 {
+            let _ = inbox.try_send(BlockMessage::Notify);
+        }
+    }
+}
+
+pub struct Writer {
+    mapping: Arc<Mapping>,
+    name: CString,
+    id: Option<(u64, u64)>,
+    unlinked: bool,
+    // slot, inbox, input id of readers in this flowgraph
+    readers: Vec<(usize, Sender<BlockMessage>, usize)>,
+    item_size: usize,
+    inbox: Sender<BlockMessage>,
+    output_id: usize,
+    stop: Arc<AtomicBool>,
+    watcher: Option<JoinHandle<()>>,
+    finished: bool,
+}
+
+impl Writer {
+    /// Creates the shared memory object.
+    ///
+    /// Fails, if an object of the same name exists, unless `unlink_existing` is set.
+    pub fn new(
+        name: &str,
+        item_size: usize,
+        min_bytes: usize,
+        unlink_existing: bool,
+        inbox: Sender<BlockMessage>,
+        output_id: usize,
+    ) -> Result<Writer> {
+        let c_name = object_name(name)?;
+        let header_len = header_len();
+        let capacity = capacity(item_size, min_bytes);
+
+        let (mapping, id) = unsafe {
+            if unlink_existing {
+                libc::shm_unlink(c_name.as_ptr());
+            }
+            let fd = libc::shm_open(
+                c_name.as_ptr(),
+                libc::O_CREAT | libc::O_EXCL | libc::O_RDWR,
+                0o600,
+            );
+            if fd < 0 {
+                let e = errno();
+                if e == libc::EEXIST {
+                    bail!(
+                        "shared memory {} already exists, another process might use it",
+                        name
+                    );
+                }
+                bail!("cannot create shared memory {} ({})", name, e);
+            }
+            let id = file_id(fd);
+            let mapping = if libc::ftruncate(fd, (header_len + capacity) as libc::off_t) == 0 {
+                Mapping::new(fd, header_len, capacity)
+            } else {
+                Err(anyhow!("cannot resize shared memory ({})", errno()))
+            };
+            libc::close(fd);
+            if mapping.is_err() {
+                libc::shm_unlink(c_name.as_ptr());
+            }
+            let mapping =
+                mapping.with_context(|| format!("cannot create shared memory {}", name))?;
+            (mapping, id)
+        };
+
+        unsafe {
+            let h = mapping.ptr as *mut Header;
+            (*h).version = VERSION;
+            (*h).item_size = item_size as u64;
+            (*h).capacity = capacity as u64;
+            libc::sem_init((*h).writer_sem.get(), 1, 0);
+            for slot in (*h).readers.iter() {
+                libc::sem_init(slot.sem.get(), 1, 0);
+            }
+            (*h).magic.store(MAGIC, Ordering::Release);
+        }
+
+        let mapping = Arc::new(mapping);
+        let stop = Arc::new(AtomicBool::new(false));
+        let watcher = {
+            let mapping = mapping.clone();
+            let stop = stop.clone();
+            let inbox = inbox.clone();
+            std::thread::spawn(move || watch(mapping, stop, inbox))
+        };
+
+        Ok(Writer {
+            mapping,
+            name: c_name,
+            id,
+            unlinked: false,
+            readers: Vec::new(),
+            item_size,
+            inbox,
+            output_id,
+            stop,
+            watcher: Some(watcher),
+            finished: false,
+        })
+    }
+
+    /// Unlinks the shared memory object, unless another writer replaced it.
+    fn unlink(&mut self) {
+        if !self.unlinked {
+            unsafe {
+                let fd = libc::shm_open(self.name.as_ptr(), libc::O_RDONLY, 0);
+                if fd >= 0 {
+                    let id = file_id(fd);
+                    libc::close(fd);
+                    if id.is_some() && id == self.id {
+                        libc::shm_unlink(self.name.as_ptr());
+                    }
+                }
+            }
+            self.unlinked = true;
+        }
+    }
+
+    /// Serializes tags to the tag slots, `index` is the absolute index of the first item.
+    fn write_tags(&self, index: u64, tags: &[ItemTag]) {
+        let header = self.mapping.header();
+        let mut n = header.n_tags.load(Ordering::Relaxed);
+
+        for t in tags.iter() {
+            let data = match serde_json::to_vec(&t.tag) {
+                Ok(d) if d.len() <= TAG_BYTES => d,
+                _ => {
+                    warn!("shm buffer: cannot serialize tag {:?}, dropping it", t.tag);
+                    continue;
+                }
+            };
+
+            let slot = &header.tags[n as usize % TAG_SLOTS];
+            let seq = 2 * (n + 1);
+            slot.seq.store(seq - 1, Ordering::Relaxed);
+            fence(Ordering::Release);
+            slot.index.store(index + t.index as u64, Ordering::Relaxed);
+            slot.len.store(data.len() as u32, Ordering::Relaxed);
+            for (word, chunk) in slot.data.iter().zip(data.chunks(8)) {
+                let mut bytes = [0u8; 8];
+                bytes[..chunk.len()].copy_from_slice(chunk);
+                word.store(u64::from_ne_bytes(bytes), Ordering::Relaxed);
+            }
+            slot.seq.store(seq, Ordering::Release);
+            n += 1;
+        }
+
+        header.n_tags.store(n, Ordering::Release);
+    }
+}
+
+impl fmt::Debug for Writer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("shm::Writer")
+            .field("name", &self.name)
+            .field("capacity", &self.mapping.capacity)
+            .field("item_size", &self.item_size)
+            .field("output_id", &self.output_id)
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl BufferWriterHost for Writer {
+    fn add_reader(&mut self, inbox: Sender<BlockMessage>, input_id: usize) -> Result<BufferReader> {
+        let mut reader = Reader::new(self.mapping.clone(), self.item_size)
+            .context("cannot add reader to shared memory buffer")?;
+        self.readers.push((reader.slot, inbox, input_id));
+        reader.writer_inbox = Some((self.inbox.clone(), self.output_id));
+        Ok(BufferReader::Host(Box::new(reader)))
+    }
+
+    fn remove_reader(
+        &mut self,
+        reader_inbox: &Sender<BlockMessage>,
+        reader_input_id: usize,
+    ) -> Result<()> {
+        // the reader itself frees its slot, once it is dropped
+        let n = self.readers.len();
+        self.readers.retain(|(_, inbox, id)| {
+            !(inbox.same_receiver(reader_inbox) && *id == reader_input_id)
+        });
+        if self.readers.len() == n {
+            bail!("reader not connected");
+        }
+        Ok(())
+    }
+
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn produce(&mut self, items: usize, tags: Vec<ItemTag>) {
+        let header = self.mapping.header();
+        let position = header.position.load(Ordering::Relaxed);
+
+        if !tags.is_empty() {
+            self.write_tags(position / self.item_size as u64, &tags);
+        }
+        header.position.store(
+            position + (items * self.item_size) as u64,
+            Ordering::Release,
+        );
+
+        for (_, inbox, _) in self.readers.iter_mut() {
+            let _ = inbox.try_send(BlockMessage::Notify);
+        }
+        for slot in header.readers.iter() {
+            if slot.waiting.swap(0, Ordering::AcqRel) == 1 {
+                sem_post(&slot.sem);
+            }
+        }
+    }
+
+    fn bytes(&mut self) -> (*mut u8, usize) {
+        let header = self.mapping.header();
+        let position = header.position.load(Ordering::Relaxed);
+
+        // announce that the writer might wait, before checking the readers
+        header.writer_waiting.store(1, Ordering::SeqCst);
+        let slowest = header
+            .readers
+            .iter()
+            .filter(|s| s.state.load(Ordering::SeqCst) == SLOT_ACTIVE)
+            .map(|s| s.position.load(Ordering::Acquire))
+            .min()
+            .unwrap_or(position);
+
+        let space = self
+            .mapping
+            .capacity
+            .saturating_sub((position - slowest) as usize);
+        if space == self.mapping.capacity {
+            header.writer_waiting.store(0, Ordering::Relaxed);
+        }
+
+        (self.mapping.items(position), space)
+    }
+
+    async fn notify_finished(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        let header = self.mapping.header();
+        header.finished.store(1, Ordering::Release);
+        for slot in header.readers.iter() {
+            sem_post(&slot.sem);
+        }
+        self.unlink();
+
+        for (_, inbox, input_id) in self.readers.iter_mut() {
+            let _ = inbox
+                .send(BlockMessage::StreamInputDone {
+                    input_id: *input_id,
+                })
+                .await;
+        }
+    }
+
+    fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    fn finished(&self) -> bool {
+        self.finished
+    }
+}
+
+impl Drop for Writer {
+    fn drop(&mut self) {
+        self.unlink();
+        let header = self.mapping.header();
+        header.finished.store(1, Ordering::Release);
+        for slot in header.readers.iter() {
+            sem_post(&slot.sem);
+        }
+
+        self.stop.store(true, Ordering::Release);
+        sem_post(&header.writer_sem);
+        if let Some(w) = self.watcher.take() {
+            let _ = w.join();
+        }
+    }
+}
+
+/// Reader of a [Shm] buffer.
+///
+/// Readers in the flowgraph of the writer are created by the runtime. Readers in other processes
+/// [attach](Reader::attach) to the buffer by its name and [wait](Reader::wait) for items.
+pub struct Reader {
+    mapping: Arc<Mapping>,
+    slot: usize,
+    item_size: usize,
+    // tags with absolute item indices
+    tags: Vec<ItemTag>,
+    next_tag: u64,
+    writer_inbox: Option<(Sender<BlockMessage>, usize)>,
+    finished: bool,
+}
+
+impl Reader {
+    /// Attaches to the buffer with the given name, created by another process.
+    pub fn attach(name: &str, item_size: usize) -> Result<Reader> {
+        let c_name = object_name(name)?;
+        let header_len = header_len();
+
+        let mapping = unsafe {
+            let fd = libc::shm_open(c_name.as_ptr(), libc::O_RDWR, 0);
+            if fd < 0 {
+                bail!("cannot open shared memory {} ({})", name, errno());
+            }
+            let mut stat: libc::stat = std::mem::zeroed();
+            let mapping = if libc::fstat(fd, &mut stat) != 0 {
+                Err(anyhow!("cannot stat shared memory ({})", errno()))
+            } else if (stat.st_size as usize) <= header_len {
+                Err(anyhow!("shared memory too small"))
+            } else {
+                Mapping::new(fd, header_len, stat.st_size as usize - header_len)
+            };
+            libc::close(fd);
+            mapping.with_context(|| format!("cannot attach to shared memory {}", name))?
+        };
+
+        let header = mapping.header();
+        if header.magic.load(Ordering::Acquire) != MAGIC || header.version != VERSION {
+            bail!("{} is not a shared memory buffer", name);
+        }
+        if header.item_size as usize != item_size {
+            bail!(
+                "shared memory buffer {} has items of size {}, expected {}",
+                name,
+                header.item_size,
+                item_size
+            );
+        }
+        if header.capacity as usize != mapping.capacity {
+            bail!("shared memory buffer {} has an invalid size", name);
+        }
+
+        Reader::new(Arc::new(mapping), item_size)
+    }
+
+    fn new(mapping: Arc<Mapping>, item_size: usize) -> Result<Reader> {
+        let header = mapping.header();
+        let slot = header
+            .readers
+            .iter()
+            .position(|s| {
+                s.state
+                    .compare_exchange(
+                        SLOT_FREE,
+                        SLOT_ATTACHING,
+                        Ordering::AcqRel,
+                        Ordering::Relaxed,
+                    )
+                    .is_ok()
+            })
+            .context("no free reader slot")?;
+
+        let s = &header.readers[slot];
+        s.pid
+            .store(unsafe { libc::getpid() } as i32, Ordering::Relaxed);
+        s.waiting.store(0, Ordering::Relaxed);
+        s.position
+            .store(header.position.load(Ordering::Acquire), Ordering::Release);
+        let next_tag = header.n_tags.load(Ordering::Acquire);
+        s.state.store(SLOT_ACTIVE, Ordering::SeqCst);
+        // the writer might have continued before it saw the slot, skip these items
+        s.position
+            .store(header.position.load(Ordering::SeqCst), Ordering::Release);
+
+        Ok(Reader {
+            mapping,
+            slot,
+            item_size,
+            tags: Vec::new(),
+            next_tag,
+            writer_inbox: None,
+            finished: false,
+        })
+    }
+
+    fn position(&self) -> u64 {
+        self.mapping
+            .slot(self.slot)
+            .position
+            .load(Ordering::Relaxed)
+    }
+
+    fn available(&self) -> usize {
+        let position = self.mapping.header().position.load(Ordering::Acquire);
+        (position - self.position()) as usize
+    }
+
+    /// Deserializes tags that were added since the last call.
+    fn read_tags(&mut self) {
+        let header = self.mapping.header();
+        let n_tags = header.n_tags.load(Ordering::Acquire);
+        if n_tags - self.next_tag > TAG_SLOTS as u64 {
+            warn!(
+                "shm buffer: reader fell behind, lost {} tags",
+                n_tags - self.next_tag - TAG_SLOTS as u64
+            );
+            self.next_tag = n_tags - TAG_SLOTS as u64;
+        }
+
+        let mut data = [0u8; TAG_BYTES];
+        while self.next_tag < n_tags {
+            let slot = &header.tags[self.next_tag as usize % TAG_SLOTS];
+            let seq = 2 * (self.next_tag + 1);
+            let mut index = 0;
+            let mut len = 0;
+            let mut valid = slot.seq.load(Ordering::Acquire) == seq;
+            if valid {
+                index = slot.index.load(Ordering::Relaxed);
+                len = std::cmp::min(slot.len.load(Ordering::Relaxed) as usize, TAG_BYTES);
+                for (word, chunk) in slot.data.iter().zip(data.chunks_mut(8)) {
+                    chunk.copy_from_slice(&word.load(Ordering::Relaxed).to_ne_bytes());
+                }
+                fence(Ordering::Acquire);
+                valid = slot.seq.load(Ordering::Relaxed) == seq;
+            }
+            // the slot was overwritten, before or while it was copied
+            if !valid {
+                warn!("shm buffer: reader fell behind, lost tag");
+            } else if let Ok(tag) = serde_json::from_slice::<Tag>(&data[..len]) {
+                self.tags.push(ItemTag {
+                    index: index as usize,
+                    tag,
+                });
+            }
+            self.next_tag += 1;
+        }
+    }
+
+    /// Waits until the writer produced items or finished, for at most 100ms.
+    pub async fn wait(&mut self) {
+        let slot = self.mapping.slot(self.slot);
+        slot.waiting.store(1, Ordering::SeqCst);
+        if self.available() > 0 || self.mapping.header().finished.load(Ordering::Acquire) == 1 {
+            slot.waiting.store(0, Ordering::Relaxed);
+            return;
+        }
+
+        let mapping = self.mapping.clone();
+        let slot = self.slot;
+        blocking::unblock(move || sem_wait(&mapping.slot(slot).sem)).await;
+    }
+}
+
+#[async_trait]
+impl BufferReaderHost for Reader {
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn bytes(&mut self) -> (*const u8, usize, Vec<ItemTag>) {
+        let position = self.position();
+        let available = self.available();
+
+        if self.mapping.header().n_tags.load(Ordering::Acquire) > self.next_tag {
+            self.read_tags();
+        }
+        let start = (position / self.item_size as u64) as usize;
+        let end = start + available / self.item_size;
+        let tags = self
+            .tags
+            .iter()
+            .filter(|t| t.index >= start && t.index < end)
+            .map(|t| ItemTag {
+                index: t.index - start,
+                tag: t.tag.clone(),
+            })
+            .collect();
+
+        (self.mapping.items(position), available, tags)
+    }
+
+    fn consume(&mut self, amount: usize) {
+        let slot = self.mapping.slot(self.slot);
+        let position = self.position() + (amount * self.item_size) as u64;
+        slot.position.store(position, Ordering::SeqCst);
+
+        let index = (position / self.item_size as u64) as usize;
+        self.tags.retain(|t| t.index >= index);
+
+        if let Some((inbox, _)) = self.writer_inbox.as_mut() {
+            let _ = inbox.try_send(BlockMessage::Notify);
+        } else if self
+            .mapping
+            .header()
+            .writer_waiting
+            .swap(0, Ordering::SeqCst)
+            == 1
+        {
+            sem_post(&self.mapping.header().writer_sem);
+        }
+    }
+
+    async fn notify_finished(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        if let Some((inbox, output_id)) = self.writer_inbox.as_mut() {
+            let _ = inbox
+                .send(BlockMessage::StreamOutputDone {
+                    output_id: *output_id,
+                })
+                .await;
+        }
+    }
+
+    fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    fn finished(&self) -> bool {
+        (self.finished || self.mapping.header().finished.load(Ordering::Acquire) == 1)
+            && self.available() == 0
+    }
+}
+
+impl Drop for Reader {
+    fn drop(&mut self) {
+        let header = self.mapping.header();
+        header.readers[self.slot]
+            .state
+            .store(SLOT_FREE, Ordering::SeqCst);
+
+        // the writer might wait for this reader
+        if let Some((inbox, _)) = self.writer_inbox.as_mut() {
+            let _ = inbox.try_send(BlockMessage::Notify);
+        } else {
+            header.writer_waiting.store(0, Ordering::Relaxed);
+            sem_post(&header.writer_sem);
+        }
+    }
+}
+
+impl fmt::Debug for Reader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("shm::Reader")
+            .field("slot", &self.slot)
+            .field("item_size", &self.item_size)
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
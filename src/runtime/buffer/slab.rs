@@ -3,19 +3,25 @@ use futures::prelude::*;
 use std::any::Any;
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use crate::anyhow::{bail, Result};
+use crate::runtime::buffer::latency_items;
+use crate::runtime::buffer::lossy::LossyQueue;
 use crate::runtime::buffer::BufferBuilder;
 use crate::runtime::buffer::BufferReader;
 use crate::runtime::buffer::BufferReaderHost;
 use crate::runtime::buffer::BufferWriter;
 use crate::runtime::buffer::BufferWriterHost;
 use crate::runtime::config;
+use crate::runtime::ctrl_port::BufferDescription;
 use crate::runtime::BlockMessage;
 use crate::runtime::ItemTag;
 
 #[derive(Debug, PartialEq, Hash)]
 pub struct Slab {
     min_bytes: usize,
+    min_items: usize,
     n_buffer: usize,
     reserved_items: usize,
 }
@@ -26,6 +32,7 @@ impl Slab {
     pub fn new() -> Slab {
         Slab {
             min_bytes: config::config().buffer_size,
+            min_items: 0,
             n_buffer: 2,
             reserved_items: config::config().slab_reserved,
         }
@@ -34,14 +41,40 @@ impl Slab {
     pub fn with_size(min_bytes: usize) -> Slab {
         Slab {
             min_bytes,
+            min_items: 0,
             n_buffer: 2,
             reserved_items: config::config().slab_reserved,
         }
     }
 
+    /// Slab with buffers for at least `min_items` items each.
+    pub fn with_items(min_items: usize) -> Slab {
+        Slab {
+            min_bytes: 0,
+            min_items,
+            n_buffer: 2,
+            reserved_items: config::config().slab_reserved,
+        }
+    }
+
+    /// Slab that queues at most the items of `latency` at the given sample rate.
+    ///
+    /// The items are split across the buffers, which are passed to the reader once they are
+    /// full. Blocks that need more output space per call to `work()` stall.
+    pub fn with_max_latency(latency: Duration, sample_rate: f64) -> Slab {
+        let n_buffer = 2;
+        Slab {
+            min_bytes: 0,
+            min_items: std::cmp::max(latency_items(latency, sample_rate) / n_buffer, 1),
+            n_buffer,
+            reserved_items: config::config().slab_reserved,
+        }
+    }
+
     pub fn with_buffers(n_buffer: usize) -> Slab {
         Slab {
             min_bytes: config::config().buffer_size,
+            min_items: 0,
             n_buffer,
             reserved_items: config::config().slab_reserved,
         }
@@ -50,10 +83,23 @@ impl Slab {
     pub fn with_config(min_bytes: usize, n_buffer: usize, reserved_items: usize) -> Slab {
         Slab {
             min_bytes,
+            min_items: 0,
             n_buffer,
             reserved_items,
         }
     }
+
+    /// Size of a buffer in bytes, including the reserved items.
+    fn buffer_size(&self, item_size: usize) -> usize {
+        let mut buffer_size = std::cmp::max(
+            self.min_bytes,
+            (self.reserved_items + self.min_items) * item_size,
+        );
+        while buffer_size % item_size != 0 {
+            buffer_size += 1;
+        }
+        buffer_size
+    }
 }
 
 impl Default for Slab {
@@ -68,15 +114,28 @@ impl BufferBuilder for Slab {
         item_size: usize,
         writer_inbox: Sender<BlockMessage>,
         writer_output_id: usize,
-    ) -> BufferWriter {
-        Writer::new(
+    ) -> Result<BufferWriter> {
+        Ok(Writer::new(
             item_size,
-            self.min_bytes,
+            self.buffer_size(item_size),
             self.n_buffer,
             self.reserved_items,
             writer_inbox,
             writer_output_id,
-        )
+        ))
+    }
+
+    fn lossy_readers(&self) -> bool {
+        true
+    }
+
+    fn description(&self, item_size: usize) -> BufferDescription {
+        let items = (self.buffer_size(item_size) / item_size).saturating_sub(self.reserved_items);
+        BufferDescription {
+            type_name: "Slab".to_string(),
+            item_size,
+            capacity: Some(self.n_buffer * items),
+        }
     }
 }
 
@@ -110,6 +169,8 @@ pub struct Writer {
     reserved_items: usize,
     reader_inbox: Option<Sender<BlockMessage>>,
     reader_input_id: Option<usize>,
+    lossy_readers: Vec<LossyQueue>,
+    capacity: usize,
     writer_inbox: Sender<BlockMessage>,
     writer_output_id: usize,
     finished: bool,
@@ -152,6 +213,8 @@ impl Writer {
             reserved_items,
             reader_inbox: None,
             reader_input_id: None,
+            lossy_readers: Vec::new(),
+            capacity: n_buffer * (buffer_size / item_size).saturating_sub(reserved_items),
             writer_inbox,
             writer_output_id,
             finished: false,
@@ -165,14 +228,14 @@ impl BufferWriterHost for Writer {
         &mut self,
         reader_inbox: Sender<BlockMessage>,
         reader_input_id: usize,
-    ) -> BufferReader {
+    ) -> Result<BufferReader> {
         debug_assert!(self.reader_inbox.is_none());
         debug_assert!(self.reader_input_id.is_none());
 
         self.reader_inbox = Some(reader_inbox.clone());
         self.reader_input_id = Some(reader_input_id);
 
-        BufferReader::Host(Box::new(Reader {
+        Ok(BufferReader::Host(Box::new(Reader {
             current: None,
             state: self.state.clone(),
             item_size: self.item_size,
@@ -181,7 +244,44 @@ impl BufferWriterHost for Writer {
             writer_inbox: self.writer_inbox.clone(),
             writer_output_id: self.writer_output_id,
             finished: false,
-        }))
+        })))
+    }
+
+    fn add_lossy_reader(
+        &mut self,
+        reader_inbox: Sender<BlockMessage>,
+        reader_input_id: usize,
+    ) -> Result<BufferReader> {
+        // lossy readers get a copy of the items, they do not take the buffers
+        let (queue, reader) =
+            LossyQueue::new(self.capacity, self.item_size, reader_inbox, reader_input_id);
+        self.lossy_readers.push(queue);
+        Ok(reader)
+    }
+
+    fn remove_reader(
+        &mut self,
+        reader_inbox: &Sender<BlockMessage>,
+        reader_input_id: usize,
+    ) -> Result<()> {
+        let n = self.lossy_readers.len();
+        self.lossy_readers
+            .retain(|q| !q.is_reader(reader_inbox, reader_input_id));
+        if self.lossy_readers.len() < n {
+            return Ok(());
+        }
+
+        // full buffers stay queued for the next reader
+        match (self.reader_inbox.as_ref(), self.reader_input_id) {
+            (Some(inbox), Some(id))
+                if inbox.same_receiver(reader_inbox) && id == reader_input_id =>
+            {
+                self.reader_inbox = None;
+                self.reader_input_id = None;
+                Ok(())
+            }
+            _ => bail!("reader not connected"),
+        }
     }
 
     fn as_any(&mut self) -> &mut dyn Any {
@@ -219,6 +319,12 @@ impl BufferWriterHost for Writer {
 
         let c = self.current.as_mut().unwrap();
         debug_assert!(amount <= c.capacity - c.offset);
+        for q in self.lossy_readers.iter_mut() {
+            q.push(
+                &c.buffer[c.offset * self.item_size..(c.offset + amount) * self.item_size],
+                &tags,
+            );
+        }
         for t in tags.iter_mut() {
             t.index += c.offset;
         }
@@ -228,17 +334,22 @@ impl BufferWriterHost for Writer {
             let c = self.current.take().unwrap();
             let mut state = self.state.lock().unwrap();
 
-            state.reader_input.push_back(BufferFull {
-                buffer: c.buffer,
-                items: c.capacity - self.reserved_items,
-                tags: c.tags,
-            });
+            if self.reader_inbox.is_none() && !self.lossy_readers.is_empty() {
+                // only lossy readers, which already have a copy of the items
+                state
+                    .writer_input
+                    .push_back(BufferEmpty { buffer: c.buffer });
+            } else {
+                state.reader_input.push_back(BufferFull {
+                    buffer: c.buffer,
+                    items: c.capacity - self.reserved_items,
+                    tags: c.tags,
+                });
+            }
 
-            let _ = self
-                .reader_inbox
-                .as_mut()
-                .unwrap()
-                .try_send(BlockMessage::Notify);
+            if let Some(inbox) = self.reader_inbox.as_mut() {
+                let _ = inbox.try_send(BlockMessage::Notify);
+            }
 
             // make sure to be called again, if we have another buffer queued
             if !state.writer_input.is_empty() {
@@ -270,14 +381,12 @@ impl BufferWriterHost for Writer {
             }
         }
 
-        let _ = self
-            .reader_inbox
-            .as_mut()
-            .unwrap()
-            .send(BlockMessage::StreamInputDone {
-                input_id: self.reader_input_id.unwrap(),
-            })
-            .await;
+        if let (Some(inbox), Some(input_id)) = (self.reader_inbox.as_mut(), self.reader_input_id) {
+            let _ = inbox.send(BlockMessage::StreamInputDone { input_id }).await;
+        }
+        for q in self.lossy_readers.iter_mut() {
+            q.notify_finished().await;
+        }
     }
 
     fn finish(&mut self) {
@@ -421,4 +530,17 @@ impl BufferReaderHost for Reader {
     }
 }
 
+impl Drop for Reader {
+    fn drop(&mut self) {
+        // hand the current buffer back, so that the writer can continue, if the reader is removed
+        if let Some(c) = self.current.take() {
+            let mut state = self.state.lock().unwrap();
+            state
+                .writer_input
+                .push_back(BufferEmpty { buffer: c.buffer });
+            let _ = self.writer_inbox.try_send(BlockMessage::Notify);
+        }
+    }
+}
+
 unsafe impl Send for Reader {}
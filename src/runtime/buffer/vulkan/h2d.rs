@@ -4,6 +4,7 @@ use std::any::Any;
 use std::sync::{Arc, Mutex};
 use vulkano::buffer::BufferAccess;
 
+use crate::anyhow::{bail, Result};
 use crate::runtime::buffer::vulkan::BufferEmpty;
 use crate::runtime::buffer::vulkan::BufferFull;
 use crate::runtime::buffer::BufferBuilder;
@@ -37,8 +38,8 @@ impl BufferBuilder for H2D {
         item_size: usize,
         writer_inbox: Sender<BlockMessage>,
         writer_output_id: usize,
-    ) -> BufferWriter {
-        WriterH2D::new(item_size, writer_inbox, writer_output_id)
+    ) -> Result<BufferWriter> {
+        Ok(WriterH2D::new(item_size, writer_inbox, writer_output_id))
     }
 }
 
@@ -92,7 +93,7 @@ impl BufferWriterHost for WriterH2D {
         &mut self,
         reader_inbox: Sender<BlockMessage>,
         reader_input_id: usize,
-    ) -> BufferReader {
+    ) -> Result<BufferReader> {
         debug!("H2D writer called add reader");
         debug_assert!(self.reader_inbox.is_none());
         debug_assert!(self.reader_input_id.is_none());
@@ -101,13 +102,30 @@ impl BufferWriterHost for WriterH2D {
         self.reader_input_id = Some(reader_input_id);
 
         debug_assert_eq!(reader_input_id, 0);
-        BufferReader::Custom(Box::new(ReaderH2D {
+        Ok(BufferReader::Custom(Box::new(ReaderH2D {
             inbound: self.outbound.clone(),
             outbound: self.inbound.clone(),
             writer_inbox: self.writer_inbox.clone(),
             writer_output_id: self.writer_output_id,
             finished: false,
-        }))
+        })))
+    }
+
+    fn remove_reader(
+        &mut self,
+        reader_inbox: &Sender<BlockMessage>,
+        reader_input_id: usize,
+    ) -> Result<()> {
+        match (self.reader_inbox.as_ref(), self.reader_input_id) {
+            (Some(inbox), Some(id))
+                if inbox.same_receiver(reader_inbox) && id == reader_input_id =>
+            {
+                self.reader_inbox = None;
+                self.reader_input_id = None;
+                Ok(())
+            }
+            _ => bail!("reader not connected"),
+        }
     }
 
     fn as_any(&mut self) -> &mut dyn Any {
@@ -160,11 +178,9 @@ impl BufferWriterHost for WriterH2D {
                 });
             }
 
-            let _ = self
-                .reader_inbox
-                .as_mut()
-                .unwrap()
-                .try_send(BlockMessage::Notify);
+            if let Some(inbox) = self.reader_inbox.as_mut() {
+                let _ = inbox.try_send(BlockMessage::Notify);
+            }
         }
     }
 
@@ -183,14 +199,9 @@ impl BufferWriterHost for WriterH2D {
             }
         }
 
-        self.reader_inbox
-            .as_mut()
-            .unwrap()
-            .send(BlockMessage::StreamInputDone {
-                input_id: self.reader_input_id.unwrap(),
-            })
-            .await
-            .unwrap();
+        if let (Some(inbox), Some(input_id)) = (self.reader_inbox.as_mut(), self.reader_input_id) {
+            let _ = inbox.send(BlockMessage::StreamInputDone { input_id }).await;
+        }
     }
 
     fn finish(&mut self) {
@@ -5,6 +5,7 @@ use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use wgpu::BufferView;
 
+use crate::anyhow::{bail, Result};
 use crate::runtime::buffer::wgpu::OutputBufferEmpty as BufferEmpty;
 use crate::runtime::buffer::wgpu::OutputBufferFull as BufferFull;
 use crate::runtime::buffer::BufferBuilder;
@@ -38,8 +39,8 @@ impl BufferBuilder for D2H {
         item_size: usize,
         writer_inbox: Sender<BlockMessage>,
         writer_output_id: usize,
-    ) -> BufferWriter {
-        WriterD2H::new(item_size, writer_inbox, writer_output_id)
+    ) -> Result<BufferWriter> {
+        Ok(WriterD2H::new(item_size, writer_inbox, writer_output_id))
     }
 }
 
@@ -82,11 +83,9 @@ impl WriterD2H {
 
     pub fn submit(&mut self, buffer: BufferFull) {
         self.outbound.lock().unwrap().push_back(buffer);
-        let _ = self
-            .reader_inbox
-            .as_mut()
-            .unwrap()
-            .try_send(BlockMessage::Notify);
+        if let Some(inbox) = self.reader_inbox.as_mut() {
+            let _ = inbox.try_send(BlockMessage::Notify);
+        }
     }
 }
 
@@ -96,14 +95,14 @@ impl BufferWriterCustom for WriterD2H {
         &mut self,
         reader_inbox: Sender<BlockMessage>,
         reader_input_id: usize,
-    ) -> BufferReader {
+    ) -> Result<BufferReader> {
         debug_assert!(self.reader_inbox.is_none());
         debug_assert!(self.reader_input_id.is_none());
 
         self.reader_inbox = Some(reader_inbox.clone());
         self.reader_input_id = Some(reader_input_id);
 
-        BufferReader::Host(Box::new(ReaderD2H {
+        Ok(BufferReader::Host(Box::new(ReaderD2H {
             buffer: None,
             outbound: self.inbound.clone(),
             inbound: self.outbound.clone(),
@@ -112,7 +111,24 @@ impl BufferWriterCustom for WriterD2H {
             writer_output_id: self.writer_output_id,
             my_inbox: reader_inbox,
             finished: false,
-        }))
+        })))
+    }
+
+    fn remove_reader(
+        &mut self,
+        reader_inbox: &Sender<BlockMessage>,
+        reader_input_id: usize,
+    ) -> Result<()> {
+        match (self.reader_inbox.as_ref(), self.reader_input_id) {
+            (Some(inbox), Some(id))
+                if inbox.same_receiver(reader_inbox) && id == reader_input_id =>
+            {
+                self.reader_inbox = None;
+                self.reader_input_id = None;
+                Ok(())
+            }
+            _ => bail!("reader not connected"),
+        }
     }
 
     fn as_any(&mut self) -> &mut dyn Any {
@@ -124,14 +140,9 @@ impl BufferWriterCustom for WriterD2H {
             return;
         }
 
-        self.reader_inbox
-            .as_mut()
-            .unwrap()
-            .send(BlockMessage::StreamInputDone {
-                input_id: self.reader_input_id.unwrap(),
-            })
-            .await
-            .unwrap();
+        if let (Some(inbox), Some(input_id)) = (self.reader_inbox.as_mut(), self.reader_input_id) {
+            let _ = inbox.send(BlockMessage::StreamInputDone { input_id }).await;
+        }
     }
 
     fn finish(&mut self) {
@@ -65,6 +65,9 @@ static CONFIG: Lazy<Config> = Lazy::new(|| {
                 "ctrlport_enable" => {
                     c.ctrlport_enable = config_parse::<bool>(v);
                 }
+                "ctrlport_metrics" => {
+                    c.ctrlport_metrics = config_parse::<bool>(v);
+                }
                 "ctrlport_bind" => {
                     c.ctrlport_bind = Some(config_parse::<SocketAddr>(v));
                 }
@@ -92,6 +95,7 @@ pub struct Config {
     pub slab_reserved: usize,
     pub log_level: LevelFilter,
     pub ctrlport_enable: bool,
+    pub ctrlport_metrics: bool,
     pub ctrlport_bind: Option<SocketAddr>,
     pub frontend_path: Option<PathBuf>,
     misc: HashMap<String, Value>,
@@ -117,6 +121,7 @@ impl Default for Config {
             slab_reserved: 128,
             log_level: LevelFilter::Debug,
             ctrlport_enable: true,
+            ctrlport_metrics: true,
             ctrlport_bind: "127.0.0.1:1337".parse::<SocketAddr>().ok(),
             frontend_path: None,
             misc: HashMap::new(),
@@ -131,6 +136,7 @@ impl Default for Config {
             slab_reserved: 128,
             log_level: LevelFilter::Info,
             ctrlport_enable: false,
+            ctrlport_metrics: false,
             ctrlport_bind: None,
             frontend_path: None,
             misc: HashMap::new(),
@@ -1,18 +1,30 @@
+use axum::extract::ws::{Message, WebSocketUpgrade};
 use axum::extract::{Extension, Path};
+use axum::http::header;
 use axum::http::StatusCode;
+use axum::response::Response;
 use axum::routing::{get, get_service, post};
 use axum::Json;
 use axum::Router;
+use futures::future;
+use futures::SinkExt;
+use futures::StreamExt;
+use std::fmt::Write;
 use std::path;
 use tower_http::add_extension::AddExtensionLayer;
 use tower_http::cors::CorsLayer;
 use tower_http::services::ServeDir;
 
 pub use futuresdr_pmt::BlockDescription;
+pub use futuresdr_pmt::BlockStats;
+pub use futuresdr_pmt::BufferDescription;
 pub use futuresdr_pmt::FlowgraphDescription;
 
 use crate::runtime::config;
+use crate::runtime::registry;
+use crate::runtime::registry::BlockSchema;
 use crate::runtime::FlowgraphHandle;
+use crate::runtime::FlowgraphSnapshot;
 use crate::runtime::Pmt;
 
 macro_rules! relative {
@@ -35,10 +47,59 @@ async fn flowgraph_description(
     }
 }
 
+async fn flowgraph_snapshot(
+    Extension(mut flowgraph): Extension<FlowgraphHandle>,
+) -> Result<Json<FlowgraphSnapshot>, StatusCode> {
+    if let Ok(s) = flowgraph.snapshot().await {
+        Ok(Json::from(s))
+    } else {
+        Err(StatusCode::BAD_REQUEST)
+    }
+}
+
+/// Resolve a block, given by its id or instance name.
+async fn block_id(flowgraph: &mut FlowgraphHandle, blk: &str) -> Result<usize, StatusCode> {
+    if let Ok(id) = blk.parse::<usize>() {
+        return Ok(id);
+    }
+    flowgraph
+        .description()
+        .await
+        .ok()
+        .and_then(|d| d.blocks.into_iter().find(|b| b.instance_name == blk))
+        .map(|b| b.id)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Resolve a message handler, given by its id or port name.
+async fn handler_id(
+    flowgraph: &mut FlowgraphHandle,
+    blk: usize,
+    handler: &str,
+) -> Result<usize, StatusCode> {
+    let d = flowgraph
+        .block_description(blk)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    if let Ok(id) = handler.parse::<usize>() {
+        if id < d.message_inputs.len() {
+            Ok(id)
+        } else {
+            Err(StatusCode::NOT_FOUND)
+        }
+    } else {
+        d.message_inputs
+            .iter()
+            .position(|x| x == handler)
+            .ok_or(StatusCode::NOT_FOUND)
+    }
+}
+
 async fn block_description(
-    Path(blk): Path<usize>,
+    Path(blk): Path<String>,
     Extension(mut flowgraph): Extension<FlowgraphHandle>,
 ) -> Result<Json<BlockDescription>, StatusCode> {
+    let blk = block_id(&mut flowgraph, &blk).await?;
     if let Ok(d) = flowgraph.block_description(blk).await {
         Ok(Json::from(d))
     } else {
@@ -46,10 +107,195 @@ async fn block_description(
     }
 }
 
-async fn handler_id(
-    Path((blk, handler)): Path<(usize, usize)>,
+async fn block_stats(
+    Path(blk): Path<String>,
+    Extension(mut flowgraph): Extension<FlowgraphHandle>,
+) -> Result<Json<BlockStats>, StatusCode> {
+    let blk = block_id(&mut flowgraph, &blk).await?;
+    if let Ok(d) = flowgraph.block_description(blk).await {
+        Ok(Json::from(d.stats))
+    } else {
+        Err(StatusCode::BAD_REQUEST)
+    }
+}
+
+async fn metrics(
+    Extension(mut flowgraph): Extension<FlowgraphHandle>,
+) -> Result<([(header::HeaderName, &'static str); 1], String), StatusCode> {
+    if let Ok(d) = flowgraph.description().await {
+        Ok((
+            [(
+                header::CONTENT_TYPE,
+                "application/openmetrics-text; version=1.0.0; charset=utf-8",
+            )],
+            openmetrics(&d),
+        ))
+    } else {
+        Err(StatusCode::BAD_REQUEST)
+    }
+}
+
+/// Render the statistics of a flowgraph in the OpenMetrics text format.
+pub fn openmetrics(fg: &FlowgraphDescription) -> String {
+    fn escape(s: &str) -> String {
+        s.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    }
+
+    fn family(out: &mut String, name: &str, kind: &str, unit: Option<&str>, help: &str) {
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        if let Some(unit) = unit {
+            let _ = writeln!(out, "# UNIT {} {}", name, unit);
+        }
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+    }
+
+    fn sample(out: &mut String, name: &str, kind: &str, labels: &str, value: f64) {
+        let suffix = if kind == "counter" { "_total" } else { "" };
+        let _ = writeln!(out, "{}{}{{{}}} {}", name, suffix, labels, value);
+    }
+
+    type BlockMetric = fn(&BlockDescription) -> f64;
+    type PortMetric = fn(&BlockDescription) -> Vec<(&String, f64)>;
+
+    let mut out = String::new();
+
+    for (name, help, value) in [
+        ("futuresdr_blocks", "Number of blocks.", fg.blocks.len()),
+        (
+            "futuresdr_stream_edges",
+            "Number of stream connections.",
+            fg.stream_edges.len(),
+        ),
+        (
+            "futuresdr_message_edges",
+            "Number of message connections.",
+            fg.message_edges.len(),
+        ),
+    ] {
+        family(&mut out, name, "gauge", None, help);
+        let _ = writeln!(out, "{} {}", name, value);
+    }
+
+    let labels: Vec<String> = fg
+        .blocks
+        .iter()
+        .map(|b| {
+            format!(
+                "id=\"{}\",block=\"{}\",type=\"{}\"",
+                b.id,
+                escape(&b.instance_name),
+                escape(&b.type_name)
+            )
+        })
+        .collect();
+
+    let block_metrics: [(&str, &str, Option<&str>, &str, BlockMetric); 4] = [
+        (
+            "futuresdr_block_work_calls",
+            "counter",
+            None,
+            "Number of work() calls.",
+            |b| b.stats.work_calls as f64,
+        ),
+        (
+            "futuresdr_block_work_seconds",
+            "counter",
+            Some("seconds"),
+            "Time spent in work().",
+            |b| b.stats.work_time as f64 / 1e9,
+        ),
+        (
+            "futuresdr_block_blocked_seconds",
+            "counter",
+            Some("seconds"),
+            "Time spent waiting for messages or events.",
+            |b| b.stats.blocked_time as f64 / 1e9,
+        ),
+        (
+            "futuresdr_block_message_queue_depth",
+            "gauge",
+            None,
+            "Number of queued messages, when the block last processed its inbox.",
+            |b| b.stats.message_queue_depth as f64,
+        ),
+    ];
+
+    for (name, kind, unit, help, value) in block_metrics {
+        family(&mut out, name, kind, unit, help);
+        for (b, l) in fg.blocks.iter().zip(labels.iter()) {
+            sample(&mut out, name, kind, l, value(b));
+        }
+    }
+
+    let port_metrics: [(&str, &str, &str, PortMetric); 4] = [
+        (
+            "futuresdr_block_items_consumed",
+            "counter",
+            "Number of items consumed per stream input.",
+            |b| {
+                b.stream_inputs
+                    .iter()
+                    .zip(b.stats.items_consumed.iter().map(|x| *x as f64))
+                    .collect()
+            },
+        ),
+        (
+            "futuresdr_block_items_produced",
+            "counter",
+            "Number of items produced per stream output.",
+            |b| {
+                b.stream_outputs
+                    .iter()
+                    .zip(b.stats.items_produced.iter().map(|x| *x as f64))
+                    .collect()
+            },
+        ),
+        (
+            "futuresdr_block_input_fill",
+            "gauge",
+            "Number of items in the input buffer, when the block last accessed it.",
+            |b| {
+                b.stream_inputs
+                    .iter()
+                    .zip(b.stats.input_fill.iter().map(|x| *x as f64))
+                    .collect()
+            },
+        ),
+        (
+            "futuresdr_block_handler_calls",
+            "counter",
+            "Number of calls per message handler.",
+            |b| {
+                b.message_inputs
+                    .iter()
+                    .zip(b.stats.handler_calls.iter().map(|x| *x as f64))
+                    .collect()
+            },
+        ),
+    ];
+
+    for (name, kind, help, values) in port_metrics {
+        family(&mut out, name, kind, None, help);
+        for (b, l) in fg.blocks.iter().zip(labels.iter()) {
+            for (port, v) in values(b) {
+                let l = format!("{},port=\"{}\"", l, escape(port));
+                sample(&mut out, name, kind, &l, v);
+            }
+        }
+    }
+
+    out.push_str("# EOF\n");
+    out
+}
+
+async fn handler_call(
+    Path((blk, handler)): Path<(String, String)>,
     Extension(mut flowgraph): Extension<FlowgraphHandle>,
 ) -> Result<Json<Pmt>, StatusCode> {
+    let blk = block_id(&mut flowgraph, &blk).await?;
+    let handler = handler_id(&mut flowgraph, blk, &handler).await?;
     if let Ok(ret) = flowgraph.callback(blk, handler, Pmt::Null).await {
         Ok(Json::from(ret))
     } else {
@@ -57,11 +303,13 @@ async fn handler_id(
     }
 }
 
-async fn handler_id_post(
-    Path((blk, handler)): Path<(usize, usize)>,
+async fn handler_call_post(
+    Path((blk, handler)): Path<(String, String)>,
     Json(pmt): Json<Pmt>,
     Extension(mut flowgraph): Extension<FlowgraphHandle>,
 ) -> Result<Json<Pmt>, StatusCode> {
+    let blk = block_id(&mut flowgraph, &blk).await?;
+    let handler = handler_id(&mut flowgraph, blk, &handler).await?;
     if let Ok(ret) = flowgraph.callback(blk, handler, pmt).await {
         Ok(Json::from(ret))
     } else {
@@ -69,6 +317,55 @@ async fn handler_id_post(
     }
 }
 
+async fn registry_schemas() -> Json<Vec<BlockSchema>> {
+    Json::from(registry::schemas())
+}
+
+async fn registry_schema(Path(type_name): Path<String>) -> Result<Json<BlockSchema>, StatusCode> {
+    registry::schema(&type_name)
+        .map(Json::from)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Stream the messages, posted to a message output port, as JSON over a websocket.
+async fn subscribe(
+    ws: WebSocketUpgrade,
+    Path((blk, port)): Path<(String, String)>,
+    Extension(mut flowgraph): Extension<FlowgraphHandle>,
+) -> Result<Response, StatusCode> {
+    let blk = block_id(&mut flowgraph, &blk).await?;
+    let mut messages = flowgraph
+        .subscribe_message(blk, &port)
+        .await
+        .or(Err(StatusCode::NOT_FOUND))?;
+
+    Ok(ws.on_upgrade(|socket| async move {
+        let (mut tx, mut rx) = socket.split();
+        let forward = async move {
+            while let Some(p) = messages.next().await {
+                match serde_json::to_string(&p) {
+                    Ok(s) => {
+                        if tx.send(Message::Text(s)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => warn!("CtrlPort cannot serialize message {:?} ({})", p, e),
+                }
+            }
+            let _ = tx.close().await;
+        };
+        let closed = async move {
+            while let Some(Ok(m)) = rx.next().await {
+                if let Message::Close(_) = m {
+                    break;
+                }
+            }
+        };
+        futures::pin_mut!(forward, closed);
+        future::select(forward, closed).await;
+    }))
+}
+
 pub async fn start_control_port(flowgraph: FlowgraphHandle, custom_routes: Option<Router>) {
     if !config::config().ctrlport_enable {
         return;
@@ -76,9 +373,18 @@ pub async fn start_control_port(flowgraph: FlowgraphHandle, custom_routes: Optio
 
     let mut app = Router::new()
         .route("/api/fg/", get(flowgraph_description))
+        .route("/api/fg/snapshot/", get(flowgraph_snapshot))
         .route("/api/block/:blk/", get(block_description))
-        .route("/api/block/:blk/call/:handler/", get(handler_id))
-        .route("/api/block/:blk/call/:handler/", post(handler_id_post))
+        .route("/api/block/:blk/stats/", get(block_stats))
+        .route("/api/block/:blk/call/:handler/", get(handler_call))
+        .route("/api/block/:blk/call/:handler/", post(handler_call_post))
+        .route("/api/block/:blk/subscribe/:port/", get(subscribe))
+        .route("/api/registry/", get(registry_schemas))
+        .route("/api/registry/:type_name/", get(registry_schema));
+    if config::config().ctrlport_metrics {
+        app = app.route("/metrics", get(metrics));
+    }
+    app = app
         .layer(AddExtensionLayer::new(flowgraph))
         .layer(CorsLayer::permissive());
     if let Some(c) = custom_routes {
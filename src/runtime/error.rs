@@ -0,0 +1,94 @@
+use std::fmt;
+use std::sync::Mutex;
+
+use crate::anyhow::Error;
+use crate::runtime::Flowgraph;
+
+/// Phase of a [Block](crate::runtime::Block), in which an error occurred.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BlockPhase {
+    Init,
+    Work,
+    Handler,
+    Deinit,
+}
+
+impl fmt::Display for BlockPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockPhase::Init => write!(f, "init()"),
+            BlockPhase::Work => write!(f, "work()"),
+            BlockPhase::Handler => write!(f, "message handler"),
+            BlockPhase::Deinit => write!(f, "deinit()"),
+        }
+    }
+}
+
+/// Error of a [Block](crate::runtime::Block) that terminated the [Flowgraph].
+///
+/// [Runtime::run](crate::runtime::Runtime::run) returns it wrapped in an
+/// [anyhow::Error], i.e., it can be recovered with `downcast`. The
+/// [Flowgraph], including the state of all blocks at the time of the error,
+/// can be taken out of the error for post-mortem inspection.
+pub struct FlowgraphError {
+    pub block_id: usize,
+    pub instance_name: String,
+    pub phase: BlockPhase,
+    pub error: Error,
+    flowgraph: Mutex<Option<Flowgraph>>,
+}
+
+impl FlowgraphError {
+    pub(crate) fn new(
+        block_id: usize,
+        instance_name: String,
+        phase: BlockPhase,
+        error: Error,
+    ) -> FlowgraphError {
+        FlowgraphError {
+            block_id,
+            instance_name,
+            phase,
+            error,
+            flowgraph: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn set_flowgraph(&mut self, fg: Flowgraph) {
+        *self.flowgraph.get_mut().unwrap() = Some(fg);
+    }
+
+    /// Take the partially finished [Flowgraph].
+    ///
+    /// Returns `None` if it was already taken.
+    pub fn take_flowgraph(&self) -> Option<Flowgraph> {
+        self.flowgraph.lock().unwrap().take()
+    }
+}
+
+impl fmt::Debug for FlowgraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlowgraphError")
+            .field("block_id", &self.block_id)
+            .field("instance_name", &self.instance_name)
+            .field("phase", &self.phase)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl fmt::Display for FlowgraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "block {} (id {}) failed in {}",
+            self.instance_name, self.block_id, self.phase
+        )
+    }
+}
+
+impl std::error::Error for FlowgraphError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.error.as_ref())
+    }
+}
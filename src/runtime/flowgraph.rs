@@ -1,13 +1,19 @@
 #[cfg(not(target_arch = "wasm32"))]
 use axum::Router;
-use futures::channel::mpsc::Sender;
+use futures::channel::mpsc::{channel, Sender};
 use futures::channel::oneshot;
+use futures::future;
 use futures::SinkExt;
+use futures::Stream;
+use futures::StreamExt;
 use futuresdr_pmt::BlockDescription;
+use futuresdr_pmt::BufferDescription;
 use futuresdr_pmt::FlowgraphDescription;
 use std::cmp::{Eq, PartialEq};
 use std::fmt::Debug;
 use std::hash::Hash;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
 
 use crate::anyhow::Result;
 #[cfg(not(target_arch = "wasm32"))]
@@ -16,9 +22,15 @@ use crate::runtime::buffer::circular::Circular;
 use crate::runtime::buffer::slab::Slab;
 use crate::runtime::buffer::BufferBuilder;
 use crate::runtime::buffer::BufferWriter;
+use crate::runtime::config;
 use crate::runtime::Block;
 use crate::runtime::BlockMessage;
+use crate::runtime::CallTime;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::runtime::FlowgraphFile;
 use crate::runtime::FlowgraphMessage;
+use crate::runtime::FlowgraphSnapshot;
+use crate::runtime::HierBlock;
 use crate::runtime::Kernel;
 use crate::runtime::Pmt;
 use crate::runtime::Topology;
@@ -29,6 +41,7 @@ use crate::runtime::Topology;
 /// There is at least one source and one sink in every Flowgraph.
 pub struct Flowgraph {
     pub(crate) topology: Option<Topology>,
+    pub(crate) snapshot: Option<FlowgraphSnapshot>,
     #[cfg(not(target_arch = "wasm32"))]
     pub(crate) custom_routes: Option<Router>,
 }
@@ -38,15 +51,36 @@ impl Flowgraph {
     pub fn new() -> Flowgraph {
         Flowgraph {
             topology: Some(Topology::new()),
+            snapshot: None,
             #[cfg(not(target_arch = "wasm32"))]
             custom_routes: None,
         }
     }
 
+    /// Build a [Flowgraph] from a [FlowgraphFile] in TOML, YAML, or JSON format.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Flowgraph> {
+        FlowgraphFile::load(path)?.build()
+    }
+
     pub fn add_block(&mut self, block: Block) -> usize {
         self.topology.as_mut().unwrap().add_block(block)
     }
 
+    /// Add a [HierBlock], returning an `id` that can be used like the id of a [Block] to
+    /// connect its exported ports.
+    pub fn add_hier_block(&mut self, block: HierBlock) -> usize {
+        self.topology.as_mut().unwrap().add_hier_block(block)
+    }
+
+    /// Restore the parameters of a [FlowgraphSnapshot], when the flowgraph is started.
+    ///
+    /// The saved values are passed to the parameter handlers of the blocks with the same instance
+    /// name, after the blocks are initialized and before they start processing.
+    pub fn restore(&mut self, snapshot: FlowgraphSnapshot) {
+        self.snapshot = Some(snapshot);
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub fn set_custom_routes(&mut self, routes: Router) {
         self.custom_routes = Some(routes);
@@ -82,6 +116,41 @@ impl Flowgraph {
             .connect_stream(src_block, src_port, dst_block, dst_port, buffer)
     }
 
+    /// Connect a stream input as lossy reader that never blocks the source.
+    ///
+    /// If the reader falls behind, the oldest items it did not read yet are dropped and it gets a
+    /// [Tag::Dropped](crate::runtime::Tag::Dropped) at the first item after the gap. This allows
+    /// monitoring branches, e.g., GUI sinks, that do not throttle the rest of the flowgraph.
+    pub fn connect_stream_lossy(
+        &mut self,
+        src_block: usize,
+        src_port: &str,
+        dst_block: usize,
+        dst_port: &str,
+    ) -> Result<()> {
+        self.topology.as_mut().unwrap().connect_stream_lossy(
+            src_block,
+            src_port,
+            dst_block,
+            dst_port,
+            DefaultBuffer::new(),
+        )
+    }
+
+    pub fn connect_stream_lossy_with_type<B: BufferBuilder + Debug + Eq + Hash>(
+        &mut self,
+        src_block: usize,
+        src_port: &str,
+        dst_block: usize,
+        dst_port: &str,
+        buffer: B,
+    ) -> Result<()> {
+        self.topology
+            .as_mut()
+            .unwrap()
+            .connect_stream_lossy(src_block, src_port, dst_block, dst_port, buffer)
+    }
+
     pub fn connect_message(
         &mut self,
         src_block: usize,
@@ -127,18 +196,43 @@ impl FlowgraphHandle {
     }
 
     pub async fn call(&mut self, block_id: usize, port_id: usize, data: Pmt) -> Result<()> {
+        let (tx, rx) = oneshot::channel::<Result<()>>();
         self.inbox
             .send(FlowgraphMessage::BlockCall {
                 block_id,
                 port_id,
                 data,
+                tx,
             })
             .await?;
-        Ok(())
+        rx.await?
+    }
+
+    /// Call a message handler at the given time. The runtime holds the message until then.
+    ///
+    /// Fails, if the block has no such handler or, for [CallTime::Item], no such stream input.
+    pub async fn call_at(
+        &mut self,
+        block_id: usize,
+        port_id: usize,
+        data: Pmt,
+        time: CallTime,
+    ) -> Result<()> {
+        let (tx, rx) = oneshot::channel::<Result<()>>();
+        self.inbox
+            .send(FlowgraphMessage::BlockScheduledCall {
+                block_id,
+                port_id,
+                data,
+                time,
+                tx,
+            })
+            .await?;
+        rx.await?
     }
 
     pub async fn callback(&mut self, block_id: usize, port_id: usize, data: Pmt) -> Result<Pmt> {
-        let (tx, rx) = oneshot::channel::<Pmt>();
+        let (tx, rx) = oneshot::channel::<Result<Pmt>>();
         self.inbox
             .send(FlowgraphMessage::BlockCallback {
                 block_id,
@@ -147,8 +241,7 @@ impl FlowgraphHandle {
                 tx,
             })
             .await?;
-        let p = rx.await?;
-        Ok(p)
+        rx.await?
     }
 
     pub async fn description(&mut self) -> Result<FlowgraphDescription> {
@@ -160,19 +253,183 @@ impl FlowgraphHandle {
         Ok(d)
     }
 
+    /// Collect the current values of the persistent parameters of all blocks.
+    pub async fn snapshot(&mut self) -> Result<FlowgraphSnapshot> {
+        let (tx, rx) = oneshot::channel::<FlowgraphSnapshot>();
+        self.inbox.send(FlowgraphMessage::Snapshot { tx }).await?;
+        let s = rx.await?;
+        Ok(s)
+    }
+
     pub async fn block_description(&mut self, block_id: usize) -> Result<BlockDescription> {
-        let (tx, rx) = oneshot::channel::<BlockDescription>();
+        let (tx, rx) = oneshot::channel::<Result<BlockDescription>>();
         self.inbox
             .send(FlowgraphMessage::BlockDescription { block_id, tx })
             .await?;
-        let d = rx.await?;
-        Ok(d)
+        rx.await?
     }
 
     pub async fn terminate(&mut self) -> Result<()> {
         self.inbox.send(FlowgraphMessage::Terminate).await?;
         Ok(())
     }
+
+    /// Add a [Block] to the running flowgraph, returning its id.
+    ///
+    /// The block is started, once all its stream ports are connected. Ids of removed blocks are
+    /// reused, i.e., an id that is kept after [remove_block](Self::remove_block) may refer to a
+    /// block that was added later.
+    pub async fn add_block(&mut self, block: Block) -> Result<usize> {
+        let (tx, rx) = oneshot::channel::<Result<usize>>();
+        self.inbox
+            .send(FlowgraphMessage::AddBlock { block, tx })
+            .await?;
+        rx.await?
+    }
+
+    /// Remove a [Block] from the running flowgraph.
+    ///
+    /// The block is disconnected and processes the samples that are left in its input buffers,
+    /// before it is shut down and returned. Its id becomes invalid and may be reused by
+    /// [add_block](Self::add_block).
+    pub async fn remove_block(&mut self, block_id: usize) -> Result<Block> {
+        let (tx, rx) = oneshot::channel::<Result<Block>>();
+        self.inbox
+            .send(FlowgraphMessage::RemoveBlock { block_id, tx })
+            .await?;
+        rx.await?
+    }
+
+    pub async fn connect_stream(
+        &mut self,
+        src_block: usize,
+        src_port: &str,
+        dst_block: usize,
+        dst_port: &str,
+    ) -> Result<()> {
+        self.connect_stream_with_type(
+            src_block,
+            src_port,
+            dst_block,
+            dst_port,
+            DefaultBuffer::new(),
+        )
+        .await
+    }
+
+    pub async fn connect_stream_with_type<B: BufferBuilder + Debug + Eq + Hash>(
+        &mut self,
+        src_block: usize,
+        src_port: &str,
+        dst_block: usize,
+        dst_port: &str,
+        buffer: B,
+    ) -> Result<()> {
+        let (tx, rx) = oneshot::channel::<Result<()>>();
+        self.inbox
+            .send(FlowgraphMessage::ConnectStream {
+                src_block,
+                src_port: src_port.to_string(),
+                dst_block,
+                dst_port: dst_port.to_string(),
+                buffer: Box::new(buffer),
+                tx,
+            })
+            .await?;
+        rx.await?
+    }
+
+    pub async fn disconnect_stream(
+        &mut self,
+        src_block: usize,
+        src_port: &str,
+        dst_block: usize,
+        dst_port: &str,
+    ) -> Result<()> {
+        let (tx, rx) = oneshot::channel::<Result<()>>();
+        self.inbox
+            .send(FlowgraphMessage::DisconnectStream {
+                src_block,
+                src_port: src_port.to_string(),
+                dst_block,
+                dst_port: dst_port.to_string(),
+                tx,
+            })
+            .await?;
+        rx.await?
+    }
+
+    pub async fn connect_message(
+        &mut self,
+        src_block: usize,
+        src_port: &str,
+        dst_block: usize,
+        dst_port: &str,
+    ) -> Result<()> {
+        let (tx, rx) = oneshot::channel::<Result<()>>();
+        self.inbox
+            .send(FlowgraphMessage::ConnectMessage {
+                src_block,
+                src_port: src_port.to_string(),
+                dst_block,
+                dst_port: dst_port.to_string(),
+                tx,
+            })
+            .await?;
+        rx.await?
+    }
+
+    pub async fn disconnect_message(
+        &mut self,
+        src_block: usize,
+        src_port: &str,
+        dst_block: usize,
+        dst_port: &str,
+    ) -> Result<()> {
+        let (tx, rx) = oneshot::channel::<Result<()>>();
+        self.inbox
+            .send(FlowgraphMessage::DisconnectMessage {
+                src_block,
+                src_port: src_port.to_string(),
+                dst_block,
+                dst_port: dst_port.to_string(),
+                tx,
+            })
+            .await?;
+        rx.await?
+    }
+
+    /// Subscribe to the messages that a block posts to a message output port.
+    ///
+    /// The stream ends, when the block terminates. Dropping it ends the subscription.
+    pub async fn subscribe_message(
+        &mut self,
+        block_id: usize,
+        port: &str,
+    ) -> Result<impl Stream<Item = Pmt> + Send + Unpin> {
+        let (inbox, messages) = channel::<BlockMessage>(config::config().queue_size);
+        let (tx, rx) = oneshot::channel::<Result<()>>();
+        self.inbox
+            .send(FlowgraphMessage::SubscribeMessage {
+                block_id,
+                port: port.to_string(),
+                inbox,
+                tx,
+            })
+            .await?;
+        rx.await??;
+
+        Ok(messages
+            .take_while(|m| future::ready(!matches!(m, BlockMessage::Terminate)))
+            .filter_map(|m| {
+                future::ready(match m {
+                    BlockMessage::Call { data, .. } | BlockMessage::ScheduledCall { data, .. } => {
+                        Some(data)
+                    }
+                    _ => None,
+                })
+            }))
+    }
 }
 
 #[derive(Debug, PartialEq, Hash)]
@@ -181,7 +438,7 @@ pub struct DefaultBuffer;
 impl Eq for DefaultBuffer {}
 
 impl DefaultBuffer {
-    fn new() -> DefaultBuffer {
+    pub(crate) fn new() -> DefaultBuffer {
         DefaultBuffer
     }
 }
@@ -193,7 +450,7 @@ impl BufferBuilder for DefaultBuffer {
         item_size: usize,
         writer_inbox: Sender<BlockMessage>,
         writer_output_id: usize,
-    ) -> BufferWriter {
+    ) -> Result<BufferWriter> {
         Circular::new().build(item_size, writer_inbox, writer_output_id)
     }
     #[cfg(target_arch = "wasm32")]
@@ -202,7 +459,20 @@ impl BufferBuilder for DefaultBuffer {
         item_size: usize,
         writer_inbox: Sender<BlockMessage>,
         writer_output_id: usize,
-    ) -> BufferWriter {
+    ) -> Result<BufferWriter> {
         Slab::new().build(item_size, writer_inbox, writer_output_id)
     }
+
+    fn lossy_readers(&self) -> bool {
+        true
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn description(&self, item_size: usize) -> BufferDescription {
+        Circular::new().description(item_size)
+    }
+    #[cfg(target_arch = "wasm32")]
+    fn description(&self, item_size: usize) -> BufferDescription {
+        Slab::new().description(item_size)
+    }
 }
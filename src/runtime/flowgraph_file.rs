@@ -0,0 +1,160 @@
+use config::Value;
+use serde::Deserialize;
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+use crate::anyhow::{bail, Context, Result};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::runtime::buffer::circular::Circular;
+use crate::runtime::buffer::slab::Slab;
+use crate::runtime::registry;
+use crate::runtime::registry::Parameters;
+use crate::runtime::Flowgraph;
+
+/// Declarative description of a [Flowgraph].
+///
+/// Blocks are instantiated through the [registry], using their type name and parameters.
+/// Edges refer to blocks by their name and to ports by their name. In TOML, a flowgraph looks
+/// like
+///
+/// ```toml
+/// [[blocks]]
+/// name = "src"
+/// type = "NullSource"
+///
+/// [[blocks]]
+/// name = "head"
+/// type = "Head"
+/// parameters = { n_items = 1000 }
+///
+/// [[blocks]]
+/// name = "snk"
+/// type = "NullSink"
+///
+/// [[stream_edges]]
+/// src = "src"
+/// src_port = "out"
+/// dst = "head"
+/// dst_port = "in"
+///
+/// [[stream_edges]]
+/// src = "head"
+/// src_port = "out"
+/// dst = "snk"
+/// dst_port = "in"
+/// buffer = "slab"
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct FlowgraphFile {
+    #[serde(default)]
+    pub blocks: Vec<BlockEntry>,
+    #[serde(default)]
+    pub stream_edges: Vec<StreamEdge>,
+    #[serde(default)]
+    pub message_edges: Vec<MessageEdge>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct BlockEntry {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    #[serde(default)]
+    pub parameters: HashMap<String, Value>,
+}
+
+/// Buffer of a stream edge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BufferType {
+    Circular,
+    Slab,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct StreamEdge {
+    pub src: String,
+    pub src_port: String,
+    pub dst: String,
+    pub dst_port: String,
+    #[serde(default)]
+    pub buffer: Option<BufferType>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct MessageEdge {
+    pub src: String,
+    pub src_port: String,
+    pub dst: String,
+    pub dst_port: String,
+}
+
+impl FlowgraphFile {
+    /// Load a flowgraph file. The format (TOML, YAML, or JSON) is derived from the extension.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<Path>) -> Result<FlowgraphFile> {
+        let path = path.as_ref();
+        ::config::Config::builder()
+            .add_source(::config::File::from(path))
+            .build()
+            .and_then(|c| c.try_deserialize())
+            .with_context(|| format!("cannot load flowgraph file {}", path.display()))
+    }
+
+    /// Instantiate the blocks and connect them.
+    pub fn build(&self) -> Result<Flowgraph> {
+        let mut fg = Flowgraph::new();
+        let mut ids = HashMap::new();
+
+        for b in self.blocks.iter() {
+            if ids.contains_key(&b.name) {
+                bail!("duplicate block name {}", b.name);
+            }
+            let mut block = registry::create(&b.type_name, &Parameters::new(b.parameters.clone()))
+                .with_context(|| format!("block {}", b.name))?;
+            block.set_instance_name(&b.name);
+            ids.insert(b.name.clone(), fg.add_block(block));
+        }
+
+        let id = |name: &str| {
+            ids.get(name)
+                .copied()
+                .with_context(|| format!("unknown block {}", name))
+        };
+
+        for e in self.stream_edges.iter() {
+            let (src, dst) = (id(&e.src)?, id(&e.dst)?);
+            match e.buffer {
+                None => fg.connect_stream(src, &e.src_port, dst, &e.dst_port),
+                #[cfg(not(target_arch = "wasm32"))]
+                Some(BufferType::Circular) => {
+                    fg.connect_stream_with_type(src, &e.src_port, dst, &e.dst_port, Circular::new())
+                }
+                #[cfg(target_arch = "wasm32")]
+                Some(BufferType::Circular) => bail!("circular buffers are not supported on wasm"),
+                Some(BufferType::Slab) => {
+                    fg.connect_stream_with_type(src, &e.src_port, dst, &e.dst_port, Slab::new())
+                }
+            }
+            .with_context(|| {
+                format!(
+                    "cannot connect {}.{} to {}.{}",
+                    e.src, e.src_port, e.dst, e.dst_port
+                )
+            })?;
+        }
+
+        for e in self.message_edges.iter() {
+            fg.connect_message(id(&e.src)?, &e.src_port, id(&e.dst)?, &e.dst_port)
+                .with_context(|| {
+                    format!(
+                        "cannot connect {}.{} to {}.{}",
+                        e.src, e.src_port, e.dst, e.dst_port
+                    )
+                })?;
+        }
+
+        Ok(fg)
+    }
+}
@@ -0,0 +1,701 @@
+//! Import of [GNU Radio Companion](https://wiki.gnuradio.org/index.php/GNURadioCompanion)
+//! flowgraphs.
+//!
+//! A `.grc` file is converted into a [FlowgraphFile], i.e., the blocks are instantiated through
+//! the [registry](crate::runtime::registry). Alternatively, the converter generates Rust code
+//! that builds the flowgraph. Supported GNU Radio blocks are
+//!
+//! | GNU Radio | FutureSDR |
+//! |---|---|
+//! | `blocks_file_source` | [FileSource](crate::blocks::FileSource) |
+//! | `blocks_file_sink` | [FileSink](crate::blocks::FileSink) |
+//! | `blocks_throttle` | [Throttle](crate::blocks::Throttle) |
+//! | `fir_filter_xxx` | [Fir](crate::blocks::FirBuilder) |
+//! | `rational_resampler_xxx` | [Fir](crate::blocks::FirBuilder) (resampling) |
+//! | `fft_vxx` | [Fft](crate::blocks::Fft) |
+//! | `blocks_head` | [Head](crate::blocks::Head) |
+//! | `blocks_null_source` | [NullSource](crate::blocks::NullSource) |
+//! | `blocks_null_sink` | [NullSink](crate::blocks::NullSink) |
+//! | `blocks_vector_source_x` | [VectorSource](crate::blocks::VectorSource) |
+//! | `blocks_vector_sink_x` | [VectorSink](crate::blocks::VectorSink) |
+//! | `zeromq_pub_sink` | `zeromq::PubSink` |
+//! | `zeromq_sub_source` | `zeromq::SubSource` |
+//!
+//! Parameters can be literals or refer to `variable` blocks. Other expressions, vector streams,
+//! and message connections are not supported. Blocks that cannot be converted are collected
+//! and reported by [Grc::build] and [Grc::to_rust].
+use config::Value;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+use crate::anyhow::{anyhow, bail, Context, Result};
+use crate::runtime::flowgraph_file::BlockEntry;
+use crate::runtime::flowgraph_file::StreamEdge;
+use crate::runtime::registry;
+use crate::runtime::Flowgraph;
+use crate::runtime::FlowgraphFile;
+
+#[derive(Debug, Deserialize)]
+struct GrcFile {
+    #[serde(default)]
+    blocks: Vec<GrcBlock>,
+    #[serde(default)]
+    connections: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GrcBlock {
+    name: String,
+    id: String,
+    #[serde(default)]
+    parameters: HashMap<String, Value>,
+    #[serde(default)]
+    states: HashMap<String, Value>,
+}
+
+impl GrcBlock {
+    fn enabled(&self) -> Result<bool> {
+        match self.states.get("state") {
+            None => Ok(true),
+            Some(s) => match s.clone().into_string()?.as_str() {
+                "true" | "True" | "enabled" => Ok(true),
+                "false" | "False" | "disabled" => Ok(false),
+                s => bail!("block state {} not supported", s),
+            },
+        }
+    }
+}
+
+/// GNU Radio block or connection that could not be imported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Unsupported {
+    /// Block name or, for connections, `src:port -> dst:port`.
+    pub name: String,
+    /// GNU Radio block id, empty for connections.
+    pub id: String,
+    pub reason: String,
+}
+
+impl fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.id.is_empty() {
+            write!(f, "{}: {}", self.name, self.reason)
+        } else {
+            write!(f, "{} ({}): {}", self.name, self.id, self.reason)
+        }
+    }
+}
+
+/// Imported GNU Radio Companion flowgraph.
+#[derive(Debug)]
+pub struct Grc {
+    flowgraph: FlowgraphFile,
+    unsupported: Vec<Unsupported>,
+    code: Vec<String>,
+    connections: Vec<String>,
+    imports: BTreeSet<&'static str>,
+}
+
+impl Grc {
+    /// Load and convert a `.grc` file.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<Path>) -> Result<Grc> {
+        let path = path.as_ref();
+        let s = std::fs::read_to_string(path)
+            .with_context(|| format!("cannot read GRC file {}", path.display()))?;
+        Self::parse(&s).with_context(|| format!("cannot import GRC file {}", path.display()))
+    }
+
+    /// Convert a `.grc` flowgraph, given as YAML string.
+    pub fn parse(s: &str) -> Result<Grc> {
+        let file: GrcFile = ::config::Config::builder()
+            .add_source(::config::File::from_str(s, ::config::FileFormat::Yaml))
+            .build()
+            .and_then(|c| c.try_deserialize())
+            .context("invalid GRC file")?;
+
+        let mut grc = Grc {
+            flowgraph: FlowgraphFile::default(),
+            unsupported: Vec::new(),
+            code: Vec::new(),
+            connections: Vec::new(),
+            imports: BTreeSet::new(),
+        };
+
+        let mut variables = HashMap::new();
+        let mut blocks = Vec::new();
+        for b in file.blocks.iter() {
+            match b.enabled() {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    grc.unsupport(&b.name, &b.id, e.to_string());
+                    continue;
+                }
+            }
+            match b.id.as_str() {
+                "variable" | "parameter" => {
+                    let value = b.parameters.get("value").cloned().unwrap_or_default();
+                    variables.insert(b.name.clone(), value.into_string()?.trim().to_string());
+                }
+                "import" | "note" => {}
+                _ => blocks.push(b),
+            }
+        }
+
+        let mut ports = HashMap::new();
+        for b in blocks {
+            let p = Params {
+                block: b,
+                variables: &variables,
+            };
+            match convert(&p) {
+                Ok(m) => match registry::schema(m.type_name) {
+                    Some(schema) => {
+                        grc.flowgraph.blocks.push(BlockEntry {
+                            name: b.name.clone(),
+                            type_name: m.type_name.to_string(),
+                            parameters: m
+                                .parameters
+                                .into_iter()
+                                .map(|(k, v)| (k.to_string(), v))
+                                .collect(),
+                        });
+                        grc.code.push(format!(
+                            "let {} = fg.add_block({});",
+                            ident(&b.name),
+                            m.code
+                        ));
+                        grc.imports.extend(m.imports);
+                        ports.insert(
+                            b.name.as_str(),
+                            (schema.stream_outputs, schema.stream_inputs),
+                        );
+                    }
+                    None => grc.unsupport(
+                        &b.name,
+                        &b.id,
+                        format!("block type {} not available in this build", m.type_name),
+                    ),
+                },
+                Err(e) => grc.unsupport(&b.name, &b.id, e.to_string()),
+            }
+        }
+
+        for c in file.connections.iter() {
+            let (src, src_port, dst, dst_port) = match c.as_slice() {
+                [a, b, c, d] => (a, b, c, d),
+                _ => bail!("invalid connection {:?}", c),
+            };
+            let edge = format!("{}:{} -> {}:{}", src, src_port, dst, dst_port);
+            let (src_ports, dst_ports) = match (ports.get(src.as_str()), ports.get(dst.as_str())) {
+                (Some((o, _)), Some((_, i))) => (o, i),
+                // disabled or unsupported blocks
+                _ => continue,
+            };
+            let port = |names: &[String], port: &str| -> Result<String> {
+                let i: usize = port
+                    .parse()
+                    .map_err(|_| anyhow!("message connections not supported"))?;
+                names
+                    .get(i)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("no stream port {}", i))
+            };
+            match (port(src_ports, src_port), port(dst_ports, dst_port)) {
+                (Ok(src_port), Ok(dst_port)) => {
+                    grc.connections.push(format!(
+                        "fg.connect_stream({}, {:?}, {}, {:?})?;",
+                        ident(src),
+                        src_port,
+                        ident(dst),
+                        dst_port
+                    ));
+                    grc.flowgraph.stream_edges.push(StreamEdge {
+                        src: src.clone(),
+                        src_port,
+                        dst: dst.clone(),
+                        dst_port,
+                        buffer: None,
+                    });
+                }
+                (Err(e), _) | (_, Err(e)) => grc.unsupport(&edge, "", e.to_string()),
+            }
+        }
+
+        Ok(grc)
+    }
+
+    fn unsupport(&mut self, name: &str, id: &str, reason: String) {
+        self.unsupported.push(Unsupported {
+            name: name.to_string(),
+            id: id.to_string(),
+            reason,
+        });
+    }
+
+    /// Blocks and connections that could not be imported.
+    pub fn unsupported(&self) -> &[Unsupported] {
+        &self.unsupported
+    }
+
+    fn check(&self) -> Result<()> {
+        if !self.unsupported.is_empty() {
+            let list: Vec<String> = self
+                .unsupported
+                .iter()
+                .map(|u| format!("  {}", u))
+                .collect();
+            bail!(
+                "flowgraph contains unsupported GNU Radio blocks\n{}",
+                list.join("\n")
+            );
+        }
+        Ok(())
+    }
+
+    /// Converted flowgraph, without the unsupported blocks and connections.
+    pub fn flowgraph_file(&self) -> &FlowgraphFile {
+        &self.flowgraph
+    }
+
+    /// Build the [Flowgraph]. Fails if any block or connection is unsupported.
+    pub fn build(&self) -> Result<Flowgraph> {
+        self.check()?;
+        self.flowgraph.build()
+    }
+
+    /// Generate a Rust program that builds and runs the flowgraph. Fails if any block or
+    /// connection is unsupported.
+    pub fn to_rust(&self) -> Result<String> {
+        self.check()?;
+        let mut s = String::from("use futuresdr::anyhow::Result;\n");
+        for i in self.imports.iter() {
+            s.push_str(&format!("use {};\n", i));
+        }
+        s.push_str("use futuresdr::runtime::Flowgraph;\nuse futuresdr::runtime::Runtime;\n\n");
+        s.push_str("fn main() -> Result<()> {\n    let mut fg = Flowgraph::new();\n\n");
+        for l in self.code.iter() {
+            s.push_str(&format!("    {}\n", l));
+        }
+        s.push('\n');
+        for l in self.connections.iter() {
+            s.push_str(&format!("    {}\n", l));
+        }
+        s.push_str("\n    Runtime::new().run(fg)?;\n\n    Ok(())\n}\n");
+        Ok(s)
+    }
+}
+
+fn ident(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Block, mapped to a registry type and to Rust code.
+struct Mapped {
+    type_name: &'static str,
+    parameters: Vec<(&'static str, Value)>,
+    code: String,
+    imports: Vec<&'static str>,
+}
+
+impl Mapped {
+    fn new(type_name: &'static str, import: &'static str) -> Mapped {
+        Mapped {
+            type_name,
+            parameters: Vec::new(),
+            code: String::new(),
+            imports: vec![import],
+        }
+    }
+
+    fn param(mut self, name: &'static str, value: impl Into<Value>) -> Mapped {
+        self.parameters.push((name, value.into()));
+        self
+    }
+
+    fn item(mut self, item: Item) -> Mapped {
+        if item == Item::C32 {
+            self.imports.push("futuresdr::num_complex::Complex32");
+        }
+        self.param("item", item.name())
+    }
+
+    fn code(mut self, code: String) -> Mapped {
+        self.code = code;
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Item {
+    C32,
+    F32,
+    I32,
+    I16,
+    U8,
+}
+
+impl Item {
+    fn from_grc(t: &str) -> Result<Item> {
+        match t {
+            "complex" | "c" => Ok(Item::C32),
+            "float" | "f" => Ok(Item::F32),
+            "int" | "i" => Ok(Item::I32),
+            "short" | "s" => Ok(Item::I16),
+            "byte" | "b" => Ok(Item::U8),
+            t => bail!("item type {} not supported", t),
+        }
+    }
+
+    /// Name of the item type in the registry.
+    fn name(&self) -> &'static str {
+        match self {
+            Item::C32 => "c32",
+            Item::F32 => "f32",
+            Item::I32 => "i32",
+            Item::I16 => "i16",
+            Item::U8 => "u8",
+        }
+    }
+
+    /// Name of the item type in Rust code.
+    fn rust(&self) -> &'static str {
+        match self {
+            Item::C32 => "Complex32",
+            Item::F32 => "f32",
+            Item::I32 => "i32",
+            Item::I16 => "i16",
+            Item::U8 => "u8",
+        }
+    }
+}
+
+/// Parameters of a GRC block, resolving references to variables.
+struct Params<'a> {
+    block: &'a GrcBlock,
+    variables: &'a HashMap<String, String>,
+}
+
+impl<'a> Params<'a> {
+    fn raw(&self, name: &str) -> Result<String> {
+        let mut v = self
+            .block
+            .parameters
+            .get(name)
+            .with_context(|| format!("missing parameter {}", name))?
+            .clone()
+            .into_string()?;
+        // variables can refer to variables
+        for _ in 0..16 {
+            match self.variables.get(v.trim()) {
+                Some(x) => v = x.clone(),
+                None => return Ok(v.trim().to_string()),
+            }
+        }
+        bail!("cannot resolve parameter {}", name)
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.block.parameters.contains_key(name)
+    }
+
+    fn string(&self, name: &str) -> Result<String> {
+        let v = self.raw(name)?;
+        for q in ['"', '\''] {
+            if v.len() >= 2 && v.starts_with(q) && v.ends_with(q) {
+                return Ok(v[1..v.len() - 1].to_string());
+            }
+        }
+        Ok(v)
+    }
+
+    fn f64(&self, name: &str) -> Result<f64> {
+        let v = self.raw(name)?;
+        v.parse()
+            .map_err(|_| anyhow!("cannot evaluate {} = {}", name, v))
+    }
+
+    fn u64(&self, name: &str) -> Result<u64> {
+        let v = self.f64(name)?;
+        if v < 0.0 || v.fract() != 0.0 {
+            bail!("{} = {} is not a positive integer", name, v);
+        }
+        Ok(v as u64)
+    }
+
+    fn bool(&self, name: &str) -> Result<bool> {
+        match self.raw(name)?.as_str() {
+            "True" | "true" | "1" => Ok(true),
+            "False" | "false" | "0" => Ok(false),
+            v => bail!("cannot evaluate {} = {}", name, v),
+        }
+    }
+
+    fn list(&self, name: &str) -> Result<Vec<f64>> {
+        let v = self.raw(name)?;
+        let inner = v
+            .strip_prefix('[')
+            .and_then(|v| v.strip_suffix(']'))
+            .or_else(|| v.strip_prefix('(').and_then(|v| v.strip_suffix(')')))
+            .unwrap_or(&v);
+        inner
+            .split(',')
+            .map(|x| x.trim())
+            .filter(|x| !x.is_empty())
+            .map(|x| {
+                x.parse()
+                    .map_err(|_| anyhow!("cannot evaluate {} = {}", name, v))
+            })
+            .collect()
+    }
+
+    fn item(&self) -> Result<Item> {
+        Item::from_grc(&self.raw("type")?)
+    }
+
+    fn scalar(&self) -> Result<()> {
+        if self.has("vlen") && self.u64("vlen")? != 1 {
+            bail!("vector streams not supported");
+        }
+        Ok(())
+    }
+
+    fn unsupported_if(&self, name: &str, cond: bool) -> Result<()> {
+        if cond {
+            bail!("parameter {} = {} not supported", name, self.raw(name)?);
+        }
+        Ok(())
+    }
+}
+
+fn convert(p: &Params) -> Result<Mapped> {
+    p.scalar()?;
+    match p.block.id.as_str() {
+        "blocks_file_source" => {
+            let item = p.item()?;
+            let path = p.string("file")?;
+            let repeat = p.bool("repeat")?;
+            p.unsupported_if("offset", p.has("offset") && p.u64("offset")? != 0)?;
+            p.unsupported_if("length", p.has("length") && p.u64("length")? != 0)?;
+            Ok(Mapped::new("FileSource", "futuresdr::blocks::FileSource")
+                .item(item)
+                .param("path", path.clone())
+                .param("repeat", repeat)
+                .code(format!(
+                    "FileSource::<{}>::new({:?}, {})",
+                    item.rust(),
+                    path,
+                    repeat
+                )))
+        }
+        "blocks_file_sink" => {
+            let item = p.item()?;
+            let path = p.string("file")?;
+            p.unsupported_if("append", p.has("append") && p.bool("append")?)?;
+            Ok(Mapped::new("FileSink", "futuresdr::blocks::FileSink")
+                .item(item)
+                .param("path", path.clone())
+                .code(format!("FileSink::<{}>::new({:?})", item.rust(), path)))
+        }
+        "blocks_throttle" => {
+            let item = p.item()?;
+            let rate = p.f64("samples_per_second")?;
+            Ok(Mapped::new("Throttle", "futuresdr::blocks::Throttle")
+                .item(item)
+                .param("rate", rate)
+                .code(format!("Throttle::<{}>::new({:?})", item.rust(), rate)))
+        }
+        "fir_filter_xxx" => {
+            let item = filter_item(p)?;
+            let taps: Vec<f32> = p.list("taps")?.into_iter().map(|x| x as f32).collect();
+            let decim = p.u64("decim")?;
+            if decim == 1 {
+                Ok(Mapped::new("Fir", "futuresdr::blocks::FirBuilder")
+                    .item(item)
+                    .param("taps", taps.iter().map(|x| *x as f64).collect::<Vec<f64>>())
+                    .code(format!(
+                        "FirBuilder::new::<{0}, {0}, f32, _>(vec!{1:?})",
+                        item.rust(),
+                        taps
+                    )))
+            } else {
+                resampler(item, 1, decim, taps)
+            }
+        }
+        "rational_resampler_xxx" => {
+            let item = filter_item(p)?;
+            let interp = p.u64("interp")?;
+            let decim = p.u64("decim")?;
+            let taps = if p.has("taps") {
+                p.list("taps")?.into_iter().map(|x| x as f32).collect()
+            } else {
+                Vec::new()
+            };
+            if p.raw("type")? == "ccc" && !taps.is_empty() {
+                bail!("complex taps not supported");
+            }
+            resampler(item, interp, decim, taps)
+        }
+        "fft_vxx" => {
+            if p.item()? != Item::C32 {
+                bail!("only complex FFTs supported");
+            }
+            let len = p.u64("fft_size")?;
+            let forward = p.bool("forward")?;
+            let shift = p.has("shift") && p.bool("shift")?;
+            if p.has("window") && !p.raw("window")?.is_empty() {
+                warn!("GRC import: window of {} is not applied", p.block.name);
+            }
+            let direction = if forward { "Forward" } else { "Inverse" };
+            let mut m = Mapped::new("Fft", "futuresdr::blocks::Fft")
+                .param("len", len)
+                .param("direction", direction.to_lowercase())
+                .param("shift", shift)
+                .code(format!(
+                    "Fft::with_options({}, FftDirection::{}, {}, None)",
+                    len, direction, shift
+                ));
+            m.imports.push("futuresdr::blocks::FftDirection");
+            Ok(m)
+        }
+        "blocks_head" => {
+            let item = p.item()?;
+            let n = p.u64("num_items")?;
+            Ok(Mapped::new("Head", "futuresdr::blocks::Head")
+                .item(item)
+                .param("n_items", n)
+                .code(format!("Head::<{}>::new({})", item.rust(), n)))
+        }
+        "blocks_null_source" => {
+            let item = p.item()?;
+            p.unsupported_if(
+                "num_outputs",
+                p.has("num_outputs") && p.u64("num_outputs")? != 1,
+            )?;
+            Ok(Mapped::new("NullSource", "futuresdr::blocks::NullSource")
+                .item(item)
+                .code(format!("NullSource::<{}>::new()", item.rust())))
+        }
+        "blocks_null_sink" => {
+            let item = p.item()?;
+            p.unsupported_if(
+                "num_inputs",
+                p.has("num_inputs") && p.u64("num_inputs")? != 1,
+            )?;
+            Ok(Mapped::new("NullSink", "futuresdr::blocks::NullSink")
+                .item(item)
+                .code(format!("NullSink::<{}>::new()", item.rust())))
+        }
+        "blocks_vector_source_x" => {
+            let item = p.item()?;
+            if item == Item::C32 {
+                bail!("complex vectors not supported");
+            }
+            p.unsupported_if("repeat", p.bool("repeat")?)?;
+            let items = p.list("vector")?;
+            let code = if item == Item::F32 {
+                format!("VectorSource::<f32>::new(vec!{:?})", items)
+            } else {
+                let items: Vec<i64> = items.iter().map(|x| *x as i64).collect();
+                format!("VectorSource::<{}>::new(vec!{:?})", item.rust(), items)
+            };
+            Ok(
+                Mapped::new("VectorSource", "futuresdr::blocks::VectorSource")
+                    .item(item)
+                    .param("items", items)
+                    .code(code),
+            )
+        }
+        "blocks_vector_sink_x" => {
+            let item = p.item()?;
+            let capacity = if p.has("reserve_items") {
+                p.u64("reserve_items")?
+            } else {
+                1024
+            };
+            Ok(
+                Mapped::new("VectorSink", "futuresdr::blocks::VectorSinkBuilder")
+                    .item(item)
+                    .param("capacity", capacity)
+                    .code(format!(
+                        "VectorSinkBuilder::<{}>::new().init_capacity({}).build()",
+                        item.rust(),
+                        capacity
+                    )),
+            )
+        }
+        "zeromq_pub_sink" => {
+            let item = p.item()?;
+            let address = p.string("address")?;
+            Ok(Mapped::new("PubSink", "futuresdr::blocks::zeromq::PubSink")
+                .item(item)
+                .param("address", address.clone())
+                .code(format!(
+                    "PubSink::new(std::mem::size_of::<{}>(), {:?})",
+                    item.rust(),
+                    address
+                )))
+        }
+        "zeromq_sub_source" => {
+            let item = p.item()?;
+            let address = p.string("address")?;
+            Ok(
+                Mapped::new("SubSource", "futuresdr::blocks::zeromq::SubSource")
+                    .item(item)
+                    .param("address", address.clone())
+                    .code(format!(
+                        "SubSource::new(std::mem::size_of::<{}>(), {:?})",
+                        item.rust(),
+                        address
+                    )),
+            )
+        }
+        _ => bail!("no FutureSDR equivalent"),
+    }
+}
+
+/// Item type of a GNU Radio filter, given as `<input><output><taps>`, e.g., `ccf`.
+fn filter_item(p: &Params) -> Result<Item> {
+    match p.raw("type")?.as_str() {
+        "fff" => Ok(Item::F32),
+        "ccf" | "ccc" => Ok(Item::C32),
+        t => bail!("filter type {} not supported", t),
+    }
+}
+
+fn resampler(item: Item, interp: u64, decim: u64, taps: Vec<f32>) -> Result<Mapped> {
+    let code = if taps.is_empty() {
+        format!(
+            "FirBuilder::new_resampling::<{0}, {0}>({1}, {2})",
+            item.rust(),
+            interp,
+            decim
+        )
+    } else {
+        format!(
+            "FirBuilder::new_resampling_with_taps::<{0}, {0}, f32, _>({1}, {2}, vec!{3:?})",
+            item.rust(),
+            interp,
+            decim,
+            taps
+        )
+    };
+    Ok(
+        Mapped::new("RationalResampler", "futuresdr::blocks::FirBuilder")
+            .item(item)
+            .param("interp", interp)
+            .param("decim", decim)
+            .param(
+                "taps",
+                taps.into_iter().map(|x| x as f64).collect::<Vec<f64>>(),
+            )
+            .code(code),
+    )
+}
@@ -9,6 +9,20 @@ use crate::runtime::BlockMessage;
 use crate::runtime::BlockMeta;
 use crate::runtime::Pmt;
 
+/// When a scheduled message is delivered to its handler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallTime {
+    /// Once the stream input `input` of the block consumed `index` items. Until then, the block
+    /// does not get items beyond `index`, i.e., the handler is called exactly at this item. Blocks
+    /// that need more items per call to `work()`, see
+    /// [min_items](crate::runtime::BlockMetaBuilder::min_items), still get them and the handler is
+    /// called once they consumed past `index`.
+    Item { input: usize, index: u64 },
+    /// At the given time.
+    #[cfg(not(target_arch = "wasm32"))]
+    Instant(std::time::Instant),
+}
+
 pub struct MessageInput<T: ?Sized> {
     name: String,
     #[allow(clippy::type_complexity)]
@@ -22,6 +36,8 @@ pub struct MessageInput<T: ?Sized> {
             + Send
             + Sync,
     >,
+    #[allow(clippy::type_complexity)]
+    value: Option<Arc<dyn Fn(&T) -> Pmt + Send + Sync>>,
 }
 
 impl<T: Send + ?Sized> MessageInput<T> {
@@ -43,6 +59,7 @@ impl<T: Send + ?Sized> MessageInput<T> {
         MessageInput {
             name: name.to_string(),
             handler,
+            value: None,
         }
     }
 
@@ -65,12 +82,18 @@ impl<T: Send + ?Sized> MessageInput<T> {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// Whether the input sets a persistent parameter.
+    pub fn is_parameter(&self) -> bool {
+        self.value.is_some()
+    }
 }
 
 #[derive(Debug)]
 pub struct MessageOutput {
     name: String,
     handlers: Vec<(usize, Sender<BlockMessage>)>,
+    subscribers: Vec<Sender<BlockMessage>>,
 }
 
 impl MessageOutput {
@@ -78,6 +101,7 @@ impl MessageOutput {
         MessageOutput {
             name: name.to_string(),
             handlers: Vec::new(),
+            subscribers: Vec::new(),
         }
     }
 
@@ -89,21 +113,65 @@ impl MessageOutput {
         self.handlers.push((port, sender));
     }
 
+    pub fn disconnect(&mut self, port: usize, sender: &Sender<BlockMessage>) {
+        self.handlers
+            .retain(|(p, s)| !(*p == port && s.same_receiver(sender)));
+    }
+
+    /// Add a subscriber outside of the flowgraph, e.g., a websocket of the control port.
+    ///
+    /// Unlike message handlers of blocks, subscribers do not back-pressure the block. If they
+    /// do not keep up, messages are dropped.
+    pub fn subscribe(&mut self, sender: Sender<BlockMessage>) {
+        self.subscribers.push(sender);
+    }
+
     pub async fn notify_finished(&mut self) {
         for (_, sender) in self.handlers.iter_mut() {
             let _ = sender.send(BlockMessage::Terminate).await;
         }
+        // dropping the senders ends the subscription, even if the queue is full
+        for mut sender in self.subscribers.drain(..) {
+            let _ = sender.try_send(BlockMessage::Terminate);
+        }
     }
 
     pub async fn post(&mut self, p: Pmt) {
+        self.send(|port_id| BlockMessage::Call {
+            port_id,
+            data: p.clone(),
+        })
+        .await;
+    }
+
+    /// Post a message that is delivered to the handlers at the given time.
+    pub async fn post_at(&mut self, p: Pmt, time: CallTime) {
+        self.send(|port_id| BlockMessage::ScheduledCall {
+            port_id,
+            data: p.clone(),
+            time,
+        })
+        .await;
+    }
+
+    async fn send(&mut self, m: impl Fn(usize) -> BlockMessage) {
         for (port_id, sender) in self.handlers.iter_mut() {
-            sender
-                .send(BlockMessage::Call {
-                    port_id: *port_id,
-                    data: p.clone(),
-                })
-                .await
-                .unwrap();
+            let _ = sender.send(m(*port_id)).await;
+        }
+
+        let mut closed = false;
+        for sender in self.subscribers.iter_mut() {
+            if let Err(e) = sender.try_send(m(0)) {
+                if e.is_full() {
+                    debug!("{}: dropping message for slow subscriber", self.name);
+                } else {
+                    closed = true;
+                }
+            }
+        }
+        // subscribers just drop their receiver
+        if closed {
+            self.subscribers.retain(|s| !s.is_closed());
         }
     }
 }
@@ -134,6 +202,14 @@ impl<T: Send + ?Sized> MessageIo<T> {
         self.inputs.iter().map(|x| x.name().to_string()).collect()
     }
 
+    /// Current values of the persistent parameters of the kernel.
+    pub fn parameters(&self, kernel: &T) -> Vec<(String, Pmt)> {
+        self.inputs
+            .iter()
+            .filter_map(|i| i.value.as_ref().map(|v| (i.name.clone(), v(kernel))))
+            .collect()
+    }
+
     pub fn outputs(&self) -> &Vec<MessageOutput> {
         &self.outputs
     }
@@ -161,6 +237,11 @@ impl<T: Send + ?Sized> MessageIo<T> {
     pub async fn post(&mut self, id: usize, p: Pmt) {
         self.output_mut(id).post(p).await;
     }
+
+    /// Post a message that is delivered to the handlers at the given time.
+    pub async fn post_at(&mut self, id: usize, p: Pmt, time: CallTime) {
+        self.output_mut(id).post_at(p, time).await;
+    }
 }
 
 pub struct MessageIoBuilder<T> {
@@ -194,6 +275,31 @@ impl<T: Send> MessageIoBuilder<T> {
         self
     }
 
+    /// Add a message input that sets a persistent parameter.
+    ///
+    /// `value` returns the current value of the parameter. It is included in snapshots of the
+    /// flowgraph, and restoring a snapshot calls the handler `c` with the saved value.
+    #[must_use]
+    pub fn add_parameter(
+        mut self,
+        name: &str,
+        value: impl Fn(&T) -> Pmt + Send + Sync + 'static,
+        c: impl for<'a> Fn(
+                &'a mut T,
+                &'a mut MessageIo<T>,
+                &'a mut BlockMeta,
+                Pmt,
+            ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> MessageIoBuilder<T> {
+        let mut input = MessageInput::new(name, Arc::new(c));
+        input.value = Some(Arc::new(value));
+        self.inputs.push(input);
+        self
+    }
+
     #[must_use]
     pub fn add_output(mut self, name: &str) -> MessageIoBuilder<T> {
         self.outputs.push(MessageOutput::new(name));
@@ -2,6 +2,7 @@ use futures::channel::mpsc::Sender;
 use std::any::Any;
 use std::fmt::Debug;
 
+use crate::anyhow::{bail, Result};
 use crate::runtime::buffer::BufferReaderHost;
 use crate::runtime::buffer::BufferWriterHost;
 use crate::runtime::Block;
@@ -155,9 +156,16 @@ impl<T: Debug + Send + 'static> BufferWriterHost for MockWriter<T> {
         &mut self,
         _reader_inbox: Sender<BlockMessage>,
         _reader_input_id: usize,
-    ) -> BufferReader {
+    ) -> Result<BufferReader> {
         unimplemented!();
     }
+    fn remove_reader(
+        &mut self,
+        _reader_inbox: &Sender<BlockMessage>,
+        _reader_input_id: usize,
+    ) -> Result<()> {
+        bail!("mock writer has no readers")
+    }
     fn as_any(&mut self) -> &mut dyn Any {
         self
     }
@@ -12,9 +12,12 @@ pub mod ctrl_port;
 #[cfg(target_arch = "wasm32")]
 pub mod ctrl_port {
     pub use futuresdr_pmt::BlockDescription;
+    pub use futuresdr_pmt::BlockStats;
+    pub use futuresdr_pmt::BufferDescription;
     pub use futuresdr_pmt::FlowgraphDescription;
 }
 use crate::runtime::ctrl_port::BlockDescription;
+use crate::runtime::ctrl_port::BlockStats;
 use crate::runtime::ctrl_port::FlowgraphDescription;
 
 #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
@@ -26,14 +29,21 @@ mod logging;
 #[path = "logging_wasm.rs"]
 mod logging;
 
+mod error;
 mod flowgraph;
+pub mod flowgraph_file;
+pub mod grc;
 pub mod message_io;
 mod mocker;
+pub mod registry;
 #[allow(clippy::module_inception)]
 mod runtime;
 pub mod scheduler;
+mod snapshot;
 pub mod stream_io;
 mod tag;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod time;
 mod topology;
 
 pub use block::Block;
@@ -41,9 +51,16 @@ pub use block::Kernel;
 pub use block::WorkIo;
 pub use block_meta::BlockMeta;
 pub use block_meta::BlockMetaBuilder;
+pub use block_meta::ErrorPolicy;
+pub use error::BlockPhase;
+pub use error::FlowgraphError;
 pub use flowgraph::Flowgraph;
 pub use flowgraph::FlowgraphHandle;
+pub use flowgraph_file::FlowgraphFile;
 pub use futuresdr_pmt::Pmt;
+pub use futuresdr_pmt::PmtConversionError;
+pub use futuresdr_pmt::PmtKind;
+pub use message_io::CallTime;
 pub use message_io::MessageInput;
 pub use message_io::MessageIo;
 pub use message_io::MessageIoBuilder;
@@ -52,16 +69,23 @@ pub use mocker::Mocker;
 pub(crate) use runtime::run_block;
 pub use runtime::Runtime;
 pub use runtime::RuntimeBuilder;
+pub use snapshot::FlowgraphSnapshot;
+pub use stream_io::ItemType;
 pub use stream_io::StreamInput;
 pub use stream_io::StreamIo;
 pub use stream_io::StreamIoBuilder;
 pub use stream_io::StreamOutput;
 pub use tag::ItemTag;
+pub use tag::SampleClock;
 pub use tag::Tag;
+pub use tag::TagPropagation;
+pub use topology::HierBlock;
 pub use topology::Topology;
 
+use crate::anyhow::Result;
 use crate::runtime::buffer::BufferReader;
 use crate::runtime::buffer::BufferWriter;
+use crate::runtime::topology::BufferBuilderKey;
 
 pub fn init() {
     logging::init();
@@ -75,23 +99,83 @@ pub enum FlowgraphMessage {
         block_id: usize,
         block: Block,
     },
+    BlockError {
+        block_id: usize,
+        block: Block,
+        phase: BlockPhase,
+        error: anyhow::Error,
+    },
     BlockCall {
         block_id: usize,
         port_id: usize,
         data: Pmt,
+        tx: oneshot::Sender<Result<()>>,
     },
     BlockCallback {
         block_id: usize,
         port_id: usize,
         data: Pmt,
-        tx: oneshot::Sender<Pmt>,
+        tx: oneshot::Sender<Result<Pmt>>,
+    },
+    BlockScheduledCall {
+        block_id: usize,
+        port_id: usize,
+        data: Pmt,
+        time: CallTime,
+        tx: oneshot::Sender<Result<()>>,
     },
     FlowgraphDescription {
         tx: oneshot::Sender<FlowgraphDescription>,
     },
+    Snapshot {
+        tx: oneshot::Sender<FlowgraphSnapshot>,
+    },
     BlockDescription {
         block_id: usize,
-        tx: oneshot::Sender<BlockDescription>,
+        tx: oneshot::Sender<Result<BlockDescription>>,
+    },
+    AddBlock {
+        block: Block,
+        tx: oneshot::Sender<Result<usize>>,
+    },
+    RemoveBlock {
+        block_id: usize,
+        tx: oneshot::Sender<Result<Block>>,
+    },
+    ConnectStream {
+        src_block: usize,
+        src_port: String,
+        dst_block: usize,
+        dst_port: String,
+        buffer: Box<dyn BufferBuilderKey>,
+        tx: oneshot::Sender<Result<()>>,
+    },
+    DisconnectStream {
+        src_block: usize,
+        src_port: String,
+        dst_block: usize,
+        dst_port: String,
+        tx: oneshot::Sender<Result<()>>,
+    },
+    ConnectMessage {
+        src_block: usize,
+        src_port: String,
+        dst_block: usize,
+        dst_port: String,
+        tx: oneshot::Sender<Result<()>>,
+    },
+    DisconnectMessage {
+        src_block: usize,
+        src_port: String,
+        dst_block: usize,
+        dst_port: String,
+        tx: oneshot::Sender<Result<()>>,
+    },
+    SubscribeMessage {
+        block_id: usize,
+        port: String,
+        inbox: mpsc::Sender<BlockMessage>,
+        tx: oneshot::Sender<Result<()>>,
     },
 }
 
@@ -101,7 +185,13 @@ pub enum BlockMessage {
     Terminate,
     Notify,
     BlockDescription {
-        tx: oneshot::Sender<BlockDescription>,
+        tx: oneshot::Sender<Result<BlockDescription>>,
+    },
+    Parameters {
+        tx: oneshot::Sender<Vec<(String, Pmt)>>,
+    },
+    Restore {
+        parameters: Vec<(String, Pmt)>,
     },
     StreamOutputInit {
         src_port: usize,
@@ -111,6 +201,21 @@ pub enum BlockMessage {
         dst_port: usize,
         reader: BufferReader,
     },
+    StreamOutputConnect {
+        src_port: usize,
+        dst_port: usize,
+        dst_inbox: mpsc::Sender<BlockMessage>,
+        tx: oneshot::Sender<Result<()>>,
+    },
+    StreamOutputDisconnect {
+        src_port: usize,
+        dst_port: usize,
+        dst_inbox: mpsc::Sender<BlockMessage>,
+        tx: oneshot::Sender<Result<()>>,
+    },
+    StreamInputDisconnect {
+        dst_port: usize,
+    },
     StreamInputDone {
         input_id: usize,
     },
@@ -122,6 +227,15 @@ pub enum BlockMessage {
         dst_port: usize,
         dst_inbox: mpsc::Sender<BlockMessage>,
     },
+    MessageOutputSubscribe {
+        src_port: usize,
+        inbox: mpsc::Sender<BlockMessage>,
+    },
+    MessageOutputDisconnect {
+        src_port: usize,
+        dst_port: usize,
+        dst_inbox: mpsc::Sender<BlockMessage>,
+    },
     Call {
         port_id: usize,
         data: Pmt,
@@ -129,6 +243,11 @@ pub enum BlockMessage {
     Callback {
         port_id: usize,
         data: Pmt,
-        tx: oneshot::Sender<Pmt>,
+        tx: oneshot::Sender<Result<Pmt>>,
+    },
+    ScheduledCall {
+        port_id: usize,
+        data: Pmt,
+        time: CallTime,
     },
 }
@@ -0,0 +1,695 @@
+//! Registry of block factories to instantiate blocks by their type name.
+//!
+//! The registry is used to build flowgraphs from a [FlowgraphFile](crate::runtime::FlowgraphFile).
+//! It comes with the built-in blocks that can be configured with plain parameters. Custom blocks
+//! can be added with [register] or, to have their parameters validated and to make them
+//! discoverable, e.g., through the control port, with [register_with_schema].
+use config::Value;
+use config::ValueKind;
+use num_complex::Complex32;
+use once_cell::sync::Lazy;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use crate::anyhow::{anyhow, bail, Context, Result};
+#[cfg(feature = "zeromq")]
+use crate::blocks::zeromq::{PubSink, SubSource};
+use crate::blocks::ConsoleSink;
+use crate::blocks::Copy;
+use crate::blocks::Fft;
+use crate::blocks::FftDirection;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::blocks::FileSink;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::blocks::FileSource;
+use crate::blocks::FirBuilder;
+use crate::blocks::Head;
+use crate::blocks::MessageCopy;
+use crate::blocks::MessageSink;
+use crate::blocks::NullSink;
+use crate::blocks::NullSource;
+#[cfg(feature = "soapy")]
+use crate::blocks::SoapySourceBuilder;
+use crate::blocks::TagDebug;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::blocks::Throttle;
+use crate::blocks::VectorSinkBuilder;
+use crate::blocks::VectorSource;
+use crate::runtime::Block;
+use crate::runtime::Pmt;
+use crate::runtime::PmtKind;
+
+pub use futuresdr_pmt::BlockSchema;
+pub use futuresdr_pmt::ParameterSchema;
+
+/// Parameters of a block, as given in a flowgraph file.
+#[derive(Clone, Debug, Default)]
+pub struct Parameters {
+    values: HashMap<String, Value>,
+}
+
+impl Parameters {
+    pub fn new(values: HashMap<String, Value>) -> Parameters {
+        Parameters { values }
+    }
+
+    /// Get a required parameter.
+    pub fn get<T: DeserializeOwned>(&self, name: &str) -> Result<T> {
+        self.values
+            .get(name)
+            .with_context(|| format!("missing parameter {}", name))?
+            .clone()
+            .try_deserialize()
+            .with_context(|| format!("invalid parameter {}", name))
+    }
+
+    /// Get an optional parameter, falling back to a default value.
+    pub fn get_or<T: DeserializeOwned>(&self, name: &str, default: T) -> Result<T> {
+        if self.values.contains_key(name) {
+            self.get(name)
+        } else {
+            Ok(default)
+        }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(|x| x.as_str())
+    }
+}
+
+/// Function that creates a [Block] from its [Parameters].
+pub type BlockFactory = Arc<dyn Fn(&Parameters) -> Result<Block> + Send + Sync>;
+
+#[derive(Clone)]
+struct Entry {
+    schema: Option<BlockSchema>,
+    factory: BlockFactory,
+}
+
+static REGISTRY: Lazy<RwLock<HashMap<String, Entry>>> = Lazy::new(|| {
+    let mut r = HashMap::new();
+    builtin(&mut r);
+    RwLock::new(r)
+});
+
+/// Register a block factory under a type name, replacing an existing one.
+///
+/// Parameters are passed to the factory as is. Use [register_with_schema] to have them
+/// validated and to make the block type discoverable.
+pub fn register<F>(type_name: impl Into<String>, factory: F)
+where
+    F: Fn(&Parameters) -> Result<Block> + Send + Sync + 'static,
+{
+    REGISTRY.write().unwrap().insert(
+        type_name.into(),
+        Entry {
+            schema: None,
+            factory: Arc::new(factory),
+        },
+    );
+}
+
+/// Register a block factory together with its [BlockSchema], replacing an existing one.
+///
+/// Parameters are checked against the schema before they are passed to the factory. Default
+/// values of missing parameters are filled in.
+pub fn register_with_schema<F>(schema: BlockSchema, factory: F)
+where
+    F: Fn(&Parameters) -> Result<Block> + Send + Sync + 'static,
+{
+    REGISTRY.write().unwrap().insert(
+        schema.type_name.clone(),
+        Entry {
+            schema: Some(schema),
+            factory: Arc::new(factory),
+        },
+    );
+}
+
+/// Create a block of a registered type.
+pub fn create(type_name: &str, parameters: &Parameters) -> Result<Block> {
+    let entry = REGISTRY
+        .read()
+        .unwrap()
+        .get(type_name)
+        .cloned()
+        .ok_or_else(|| anyhow!("unknown block type {}", type_name))?;
+    let res = match entry.schema {
+        Some(schema) => validate(&schema, parameters).and_then(|p| (entry.factory)(&p)),
+        None => (entry.factory)(parameters),
+    };
+    res.with_context(|| format!("cannot create block of type {}", type_name))
+}
+
+/// Type names of all registered blocks.
+pub fn types() -> Vec<String> {
+    let mut v: Vec<String> = REGISTRY.read().unwrap().keys().cloned().collect();
+    v.sort();
+    v
+}
+
+/// Schema of a registered block type.
+///
+/// Returns `None` if the type is unknown or was registered without schema.
+pub fn schema(type_name: &str) -> Option<BlockSchema> {
+    REGISTRY
+        .read()
+        .unwrap()
+        .get(type_name)
+        .and_then(|e| e.schema.clone())
+}
+
+/// Schemas of all block types that were registered with schema, sorted by type name.
+pub fn schemas() -> Vec<BlockSchema> {
+    let mut v: Vec<BlockSchema> = REGISTRY
+        .read()
+        .unwrap()
+        .values()
+        .filter_map(|e| e.schema.clone())
+        .collect();
+    v.sort_by(|a, b| a.type_name.cmp(&b.type_name));
+    v
+}
+
+fn validate(schema: &BlockSchema, parameters: &Parameters) -> Result<Parameters> {
+    let mut values = parameters.values.clone();
+
+    for name in parameters.names() {
+        let known = schema.parameters.iter().any(|p| p.name == name)
+            || (name == "item" && !schema.item_types.is_empty());
+        if !known {
+            bail!("unknown parameter {}", name);
+        }
+    }
+
+    if let Some(default) = schema.item_types.first() {
+        let item = parameters.get_or("item", default.clone())?;
+        if !schema.item_types.contains(&item) {
+            bail!(
+                "unsupported item type {} (expected one of {})",
+                item,
+                schema.item_types.join(", ")
+            );
+        }
+        values.insert("item".to_string(), Value::from(item));
+    }
+
+    for p in schema.parameters.iter() {
+        match (parameters.values.get(&p.name), &p.default) {
+            (Some(v), _) => check(p, v).with_context(|| format!("invalid parameter {}", p.name))?,
+            (None, Some(d)) => {
+                values.insert(p.name.clone(), to_value(d)?);
+            }
+            (None, None) => bail!("missing parameter {}", p.name),
+        }
+    }
+
+    Ok(Parameters::new(values))
+}
+
+fn check(p: &ParameterSchema, v: &Value) -> Result<()> {
+    match p.kind {
+        PmtKind::Bool => is::<bool>(v)?,
+        PmtKind::U32 => is::<u32>(v)?,
+        PmtKind::U64 => is::<u64>(v)?,
+        PmtKind::I32 => is::<i32>(v)?,
+        PmtKind::I64 => is::<i64>(v)?,
+        PmtKind::F32 => is::<f32>(v)?,
+        PmtKind::F64 => is::<f64>(v)?,
+        PmtKind::String => is::<String>(v)?,
+        PmtKind::VecF32 => is::<Vec<f32>>(v)?,
+        PmtKind::VecF64 => is::<Vec<f64>>(v)?,
+        PmtKind::VecU64 => is::<Vec<u64>>(v)?,
+        PmtKind::Blob => is::<Vec<u8>>(v)?,
+        _ => {}
+    }
+    if let Some((min, max)) = p.range {
+        let x = v.clone().try_deserialize::<f64>()?;
+        if x < min || x > max {
+            bail!("{} out of range [{}, {}]", x, min, max);
+        }
+    }
+    Ok(())
+}
+
+fn is<T: DeserializeOwned>(v: &Value) -> Result<()> {
+    v.clone().try_deserialize::<T>()?;
+    Ok(())
+}
+
+fn to_value(p: &Pmt) -> Result<Value> {
+    Ok(match p {
+        Pmt::Null => Value::new(None, ValueKind::Nil),
+        Pmt::String(s) => Value::from(s.clone()),
+        Pmt::Bool(v) => Value::from(*v),
+        Pmt::U32(v) => Value::from(*v as u64),
+        Pmt::U64(v) => Value::from(*v),
+        Pmt::I32(v) => Value::from(*v as i64),
+        Pmt::I64(v) => Value::from(*v),
+        Pmt::F32(v) => Value::from(*v as f64),
+        Pmt::F64(v) => Value::from(*v),
+        Pmt::VecF32(v) => Value::from(v.iter().map(|x| *x as f64).collect::<Vec<f64>>()),
+        Pmt::VecU64(v) => Value::from(v.clone()),
+        Pmt::Blob(v) => Value::from(v.iter().map(|x| *x as u64).collect::<Vec<u64>>()),
+        _ => bail!("unsupported default value {:?}", p),
+    })
+}
+
+/// Builder for a [BlockSchema].
+pub struct BlockSchemaBuilder {
+    schema: BlockSchema,
+}
+
+impl BlockSchemaBuilder {
+    pub fn new(type_name: impl Into<String>) -> BlockSchemaBuilder {
+        BlockSchemaBuilder {
+            schema: BlockSchema {
+                type_name: type_name.into(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Supported stream item types. The first one is the default.
+    #[must_use]
+    pub fn item_types(mut self, types: &[&str]) -> BlockSchemaBuilder {
+        self.schema.item_types = types.iter().map(|x| x.to_string()).collect();
+        self
+    }
+
+    #[must_use]
+    pub fn stream_input(mut self, name: &str) -> BlockSchemaBuilder {
+        self.schema.stream_inputs.push(name.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn stream_output(mut self, name: &str) -> BlockSchemaBuilder {
+        self.schema.stream_outputs.push(name.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn message_input(mut self, name: &str) -> BlockSchemaBuilder {
+        self.schema.message_inputs.push(name.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn message_output(mut self, name: &str) -> BlockSchemaBuilder {
+        self.schema.message_outputs.push(name.to_string());
+        self
+    }
+
+    /// Add a required parameter.
+    #[must_use]
+    pub fn parameter(mut self, name: &str, kind: PmtKind) -> BlockSchemaBuilder {
+        self.schema.parameters.push(ParameterSchema {
+            name: name.to_string(),
+            kind,
+            default: None,
+            range: None,
+        });
+        self
+    }
+
+    /// Add an optional parameter.
+    #[must_use]
+    pub fn parameter_with_default(
+        mut self,
+        name: &str,
+        kind: PmtKind,
+        default: Pmt,
+    ) -> BlockSchemaBuilder {
+        self.schema.parameters.push(ParameterSchema {
+            name: name.to_string(),
+            kind,
+            default: Some(default),
+            range: None,
+        });
+        self
+    }
+
+    /// Restrict the last parameter to the range `[min, max]`.
+    #[must_use]
+    pub fn range(mut self, min: f64, max: f64) -> BlockSchemaBuilder {
+        if let Some(p) = self.schema.parameters.last_mut() {
+            p.range = Some((min, max));
+        }
+        self
+    }
+
+    pub fn build(self) -> BlockSchema {
+        self.schema
+    }
+}
+
+/// Instantiate a generic block for the stream item type, given by the `item` parameter.
+macro_rules! with_item {
+    ($p:expr, $t:ident => $e:expr) => {
+        match $p.get_or("item", String::from("f32"))?.as_str() {
+            "u8" => {
+                type $t = u8;
+                Ok($e)
+            }
+            "u16" => {
+                type $t = u16;
+                Ok($e)
+            }
+            "u32" => {
+                type $t = u32;
+                Ok($e)
+            }
+            "u64" => {
+                type $t = u64;
+                Ok($e)
+            }
+            "i8" => {
+                type $t = i8;
+                Ok($e)
+            }
+            "i16" => {
+                type $t = i16;
+                Ok($e)
+            }
+            "i32" => {
+                type $t = i32;
+                Ok($e)
+            }
+            "i64" => {
+                type $t = i64;
+                Ok($e)
+            }
+            "f32" => {
+                type $t = f32;
+                Ok($e)
+            }
+            "f64" => {
+                type $t = f64;
+                Ok($e)
+            }
+            "c32" => {
+                type $t = Complex32;
+                Ok($e)
+            }
+            t => bail!("unsupported item type {}", t),
+        }
+    };
+}
+
+/// Stream item types supported by [with_item].
+const ITEM_TYPES: &[&str] = &[
+    "f32", "f64", "c32", "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64",
+];
+
+fn add<F>(r: &mut HashMap<String, Entry>, schema: BlockSchemaBuilder, factory: F)
+where
+    F: Fn(&Parameters) -> Result<Block> + Send + Sync + 'static,
+{
+    let schema = schema.build();
+    r.insert(
+        schema.type_name.clone(),
+        Entry {
+            schema: Some(schema),
+            factory: Arc::new(factory),
+        },
+    );
+}
+
+fn builtin(r: &mut HashMap<String, Entry>) {
+    add(
+        r,
+        BlockSchemaBuilder::new("ConsoleSink")
+            .item_types(ITEM_TYPES)
+            .stream_input("in")
+            .parameter_with_default("sep", PmtKind::String, Pmt::String(", ".to_string())),
+        |p| {
+            let sep = p.get::<String>("sep")?;
+            with_item!(p, T => ConsoleSink::<T>::new(sep))
+        },
+    );
+    add(
+        r,
+        BlockSchemaBuilder::new("Copy")
+            .item_types(ITEM_TYPES)
+            .stream_input("in")
+            .stream_output("out"),
+        |p| with_item!(p, T => Copy::<T>::new()),
+    );
+    add(
+        r,
+        BlockSchemaBuilder::new("Fft")
+            .stream_input("in")
+            .stream_output("out")
+            .parameter("len", PmtKind::U64)
+            .range(1.0, u32::MAX as f64)
+            .parameter_with_default(
+                "direction",
+                PmtKind::String,
+                Pmt::String("forward".to_string()),
+            )
+            .parameter_with_default("shift", PmtKind::Bool, Pmt::Bool(false)),
+        |p| {
+            let len = p.get("len")?;
+            let direction = match p.get::<String>("direction")?.as_str() {
+                "forward" => FftDirection::Forward,
+                "inverse" => FftDirection::Inverse,
+                d => bail!("invalid direction {} (expected forward or inverse)", d),
+            };
+            let shift = p.get::<bool>("shift")?;
+            Ok(Fft::with_options(len, direction, shift, None))
+        },
+    );
+    #[cfg(not(target_arch = "wasm32"))]
+    add(
+        r,
+        BlockSchemaBuilder::new("FileSink")
+            .item_types(ITEM_TYPES)
+            .stream_input("in")
+            .parameter("path", PmtKind::String),
+        |p| {
+            let path = p.get::<String>("path")?;
+            with_item!(p, T => FileSink::<T>::new(path))
+        },
+    );
+    #[cfg(not(target_arch = "wasm32"))]
+    add(
+        r,
+        BlockSchemaBuilder::new("FileSource")
+            .item_types(ITEM_TYPES)
+            .stream_output("out")
+            .parameter("path", PmtKind::String)
+            .parameter_with_default("repeat", PmtKind::Bool, Pmt::Bool(false)),
+        |p| {
+            let path = p.get::<String>("path")?;
+            let repeat = p.get::<bool>("repeat")?;
+            with_item!(p, T => FileSource::<T>::new(path, repeat))
+        },
+    );
+    add(
+        r,
+        BlockSchemaBuilder::new("Fir")
+            .item_types(&["f32", "c32"])
+            .stream_input("in")
+            .stream_output("out")
+            .parameter("taps", PmtKind::VecF32),
+        |p| {
+            let taps = p.get::<Vec<f32>>("taps")?;
+            match p.get::<String>("item")?.as_str() {
+                "f32" => Ok(FirBuilder::new::<f32, f32, f32, _>(taps)),
+                "c32" => Ok(FirBuilder::new::<Complex32, Complex32, f32, _>(taps)),
+                t => bail!("unsupported item type {}", t),
+            }
+        },
+    );
+    add(
+        r,
+        BlockSchemaBuilder::new("Head")
+            .item_types(ITEM_TYPES)
+            .stream_input("in")
+            .stream_output("out")
+            .parameter("n_items", PmtKind::U64),
+        |p| {
+            let n_items = p.get("n_items")?;
+            with_item!(p, T => Head::<T>::new(n_items))
+        },
+    );
+    add(
+        r,
+        BlockSchemaBuilder::new("MessageCopy")
+            .message_input("in")
+            .message_output("out"),
+        |_| Ok(MessageCopy::new()),
+    );
+    add(
+        r,
+        BlockSchemaBuilder::new("MessageSink").message_input("in"),
+        |_| Ok(MessageSink::new()),
+    );
+    add(
+        r,
+        BlockSchemaBuilder::new("NullSink")
+            .item_types(ITEM_TYPES)
+            .stream_input("in"),
+        |p| with_item!(p, T => NullSink::<T>::new()),
+    );
+    add(
+        r,
+        BlockSchemaBuilder::new("NullSource")
+            .item_types(ITEM_TYPES)
+            .stream_output("out"),
+        |p| with_item!(p, T => NullSource::<T>::new()),
+    );
+    #[cfg(feature = "zeromq")]
+    add(
+        r,
+        BlockSchemaBuilder::new("PubSink")
+            .item_types(ITEM_TYPES)
+            .stream_input("in")
+            .parameter("address", PmtKind::String),
+        |p| {
+            let address = p.get::<String>("address")?;
+            with_item!(p, T => PubSink::new(std::mem::size_of::<T>(), address))
+        },
+    );
+    add(
+        r,
+        BlockSchemaBuilder::new("RationalResampler")
+            .item_types(&["f32", "c32"])
+            .stream_input("in")
+            .stream_output("out")
+            .parameter("interp", PmtKind::U64)
+            .range(1.0, u32::MAX as f64)
+            .parameter("decim", PmtKind::U64)
+            .range(1.0, u32::MAX as f64)
+            .parameter_with_default("taps", PmtKind::VecF32, Pmt::VecF32(Vec::new())),
+        |p| {
+            let interp = p.get("interp")?;
+            let decim = p.get("decim")?;
+            let taps = p.get::<Vec<f32>>("taps")?;
+            match (p.get::<String>("item")?.as_str(), taps.is_empty()) {
+                ("f32", true) => Ok(FirBuilder::new_resampling::<f32, f32>(interp, decim)),
+                ("f32", false) => Ok(FirBuilder::new_resampling_with_taps::<f32, f32, f32, _>(
+                    interp, decim, taps,
+                )),
+                ("c32", true) => Ok(FirBuilder::new_resampling::<Complex32, Complex32>(
+                    interp, decim,
+                )),
+                ("c32", false) => Ok(FirBuilder::new_resampling_with_taps::<
+                    Complex32,
+                    Complex32,
+                    f32,
+                    _,
+                >(interp, decim, taps)),
+                (t, _) => bail!("unsupported item type {}", t),
+            }
+        },
+    );
+    #[cfg(feature = "soapy")]
+    add(
+        r,
+        BlockSchemaBuilder::new("SoapySource")
+            .stream_output("out")
+            .message_input("freq")
+            .message_input("sample_rate")
+            .parameter("freq", PmtKind::F64)
+            .range(0.0, f64::MAX)
+            .parameter("sample_rate", PmtKind::F64)
+            .range(0.0, f64::MAX)
+            .parameter_with_default("gain", PmtKind::F64, Pmt::F64(0.0))
+            .parameter_with_default("filter", PmtKind::String, Pmt::String(String::new()))
+            .parameter_with_default("antenna", PmtKind::String, Pmt::String(String::new())),
+        |p| {
+            let mut b = SoapySourceBuilder::new()
+                .freq(p.get("freq")?)
+                .sample_rate(p.get("sample_rate")?)
+                .gain(p.get("gain")?)
+                .filter(p.get::<String>("filter")?);
+            let antenna = p.get::<String>("antenna")?;
+            if !antenna.is_empty() {
+                b = b.antenna(antenna);
+            }
+            Ok(b.build())
+        },
+    );
+    #[cfg(feature = "zeromq")]
+    add(
+        r,
+        BlockSchemaBuilder::new("SubSource")
+            .item_types(ITEM_TYPES)
+            .stream_output("out")
+            .parameter("address", PmtKind::String),
+        |p| {
+            let address = p.get::<String>("address")?;
+            with_item!(p, T => SubSource::new(std::mem::size_of::<T>(), address))
+        },
+    );
+    add(
+        r,
+        BlockSchemaBuilder::new("TagDebug")
+            .item_types(ITEM_TYPES)
+            .stream_input("in")
+            .parameter_with_default("name", PmtKind::String, Pmt::String("TagDebug".to_string())),
+        |p| {
+            let name = p.get::<String>("name")?;
+            with_item!(p, T => TagDebug::<T>::new(name))
+        },
+    );
+    #[cfg(not(target_arch = "wasm32"))]
+    add(
+        r,
+        BlockSchemaBuilder::new("Throttle")
+            .item_types(ITEM_TYPES)
+            .stream_input("in")
+            .stream_output("out")
+            .parameter("rate", PmtKind::F64)
+            .range(f64::MIN_POSITIVE, f64::MAX),
+        |p| {
+            let rate = p.get("rate")?;
+            with_item!(p, T => Throttle::<T>::new(rate))
+        },
+    );
+    add(
+        r,
+        BlockSchemaBuilder::new("VectorSink")
+            .item_types(ITEM_TYPES)
+            .stream_input("in")
+            .parameter_with_default("capacity", PmtKind::U64, Pmt::U64(1024)),
+        |p| {
+            let capacity = p.get("capacity")?;
+            with_item!(p, T => VectorSinkBuilder::<T>::new().init_capacity(capacity).build())
+        },
+    );
+    add(
+        r,
+        BlockSchemaBuilder::new("VectorSource")
+            .item_types(&[
+                "f32", "f64", "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64",
+            ])
+            .stream_output("out")
+            .parameter("items", PmtKind::VecF64),
+        |p| match p.get::<String>("item")?.as_str() {
+            "f32" => vector_source::<f32>(p),
+            "f64" => vector_source::<f64>(p),
+            "u8" => vector_source::<u8>(p),
+            "u16" => vector_source::<u16>(p),
+            "u32" => vector_source::<u32>(p),
+            "u64" => vector_source::<u64>(p),
+            "i8" => vector_source::<i8>(p),
+            "i16" => vector_source::<i16>(p),
+            "i32" => vector_source::<i32>(p),
+            "i64" => vector_source::<i64>(p),
+            t => bail!("unsupported item type {}", t),
+        },
+    );
+}
+
+fn vector_source<T: DeserializeOwned + Send + 'static>(p: &Parameters) -> Result<Block> {
+    Ok(VectorSource::<T>::new(p.get("items")?))
+}
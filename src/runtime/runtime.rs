@@ -8,8 +8,14 @@ use futures::future::join_all;
 use futures::future::Either;
 use futures::prelude::*;
 use futures::FutureExt;
+use slab::Slab;
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
 #[cfg(target_arch = "wasm32")]
 type Task<T> = crate::runtime::scheduler::wasm::TaskHandle<T>;
+#[cfg(target_arch = "wasm32")]
+use crate::runtime::scheduler::wasm::Instant;
 
 use crate::anyhow::{bail, Context, Result};
 use crate::runtime::config;
@@ -20,13 +26,26 @@ use crate::runtime::scheduler::Scheduler;
 use crate::runtime::scheduler::SmolScheduler;
 #[cfg(target_arch = "wasm32")]
 use crate::runtime::scheduler::WasmScheduler;
+use crate::runtime::stream_io::check_items;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::runtime::time;
+use crate::runtime::topology::BufferBuilderEntry;
 use crate::runtime::Block;
 use crate::runtime::BlockDescription;
 use crate::runtime::BlockMessage;
+use crate::runtime::BlockPhase;
+use crate::runtime::BlockStats;
+use crate::runtime::CallTime;
+use crate::runtime::ErrorPolicy;
 use crate::runtime::Flowgraph;
 use crate::runtime::FlowgraphDescription;
+use crate::runtime::FlowgraphError;
 use crate::runtime::FlowgraphHandle;
 use crate::runtime::FlowgraphMessage;
+use crate::runtime::FlowgraphSnapshot;
+use crate::runtime::ItemType;
+use crate::runtime::Pmt;
+use crate::runtime::Topology;
 use crate::runtime::WorkIo;
 
 /// This is the [Runtime] that runs a [Flowgraph] to completion.
@@ -127,12 +146,15 @@ impl<S: Scheduler> Runtime<S> {
             fg_inbox_rx,
             tx,
         ));
-        rx.await
-            .expect("run_flowgraph did not signal startup completed");
+        // if the startup fails, the task returns the error
+        let _ = rx.await;
         (task, FlowgraphHandle::new(fg_inbox))
     }
 
     /// Main method that kicks-off the running of a [Flowgraph].
+    ///
+    /// If a block fails, the error is a [FlowgraphError], which holds the
+    /// partially finished [Flowgraph].
     #[cfg(not(target_arch = "wasm32"))]
     pub fn run(&self, fg: Flowgraph) -> Result<Flowgraph> {
         let (handle, _) = block_on(self.start(fg));
@@ -167,8 +189,18 @@ async fn run_flowgraph<S: Scheduler>(
 ) -> Result<Flowgraph> {
     debug!("in run_flowgraph");
     let mut topology = fg.topology.take().context("flowgraph not initialized")?;
+    topology.flatten()?;
     topology.validate()?;
 
+    let mut info: HashMap<usize, BlockInfo> = topology
+        .blocks
+        .iter()
+        .map(|(id, b)| (id, BlockInfo::new(b.as_ref().unwrap())))
+        .collect();
+    let mut pending: Vec<usize> = Vec::new();
+    let mut removals: HashMap<usize, oneshot::Sender<Result<Block>>> = HashMap::new();
+    let mut error: Option<FlowgraphError> = None;
+
     let mut inboxes = scheduler.run_topology(&mut topology, &main_channel);
 
     debug!("connect stream io");
@@ -176,18 +208,29 @@ async fn run_flowgraph<S: Scheduler>(
     for ((src, src_port, buffer_builder), v) in topology.stream_edges.iter() {
         debug_assert!(!v.is_empty());
 
+        let min_items = v
+            .iter()
+            .map(|(dst, _)| info[dst].min_items)
+            .chain(std::iter::once(info[src].min_items))
+            .max()
+            .unwrap();
         let src_inbox = inboxes[*src].as_ref().unwrap().clone();
-        let mut writer = buffer_builder.build(src_inbox, *src_port);
+        let mut writer = buffer_builder.build(min_items, src_inbox, *src_port)?;
 
         for (dst, dst_port) in v.iter() {
             let dst_inbox = inboxes[*dst].as_ref().unwrap().clone();
+            let reader = if topology.lossy_edges.contains(&(*dst, *dst_port)) {
+                writer.add_lossy_reader(dst_inbox, *dst_port)?
+            } else {
+                writer.add_reader(dst_inbox, *dst_port)?
+            };
 
             inboxes[*dst]
                 .as_mut()
                 .unwrap()
                 .send(BlockMessage::StreamInputInit {
                     dst_port: *dst_port,
-                    reader: writer.add_reader(dst_inbox, *dst_port),
+                    reader,
                 })
                 .await
                 .unwrap();
@@ -220,6 +263,22 @@ async fn run_flowgraph<S: Scheduler>(
             .unwrap();
     }
 
+    // restore parameters, applied by the blocks after init
+    if let Some(ref snapshot) = fg.snapshot {
+        for (id, block_info) in info.iter() {
+            if let Some(p) = snapshot.blocks.get(&block_info.instance_name) {
+                inboxes[*id]
+                    .as_mut()
+                    .unwrap()
+                    .send(BlockMessage::Restore {
+                        parameters: p.clone().into_iter().collect(),
+                    })
+                    .await
+                    .unwrap();
+            }
+        }
+    }
+
     debug!("init blocks");
     // init blocks
     let mut active_blocks = 0u32;
@@ -242,6 +301,10 @@ async fn run_flowgraph<S: Scheduler>(
         let m = main_rx.next().await.context("no msg")?;
         match m {
             FlowgraphMessage::Initialized => i -= 1,
+            x @ FlowgraphMessage::BlockError { .. } => {
+                i -= 1;
+                queue.push(x);
+            }
             x => {
                 debug!(
                     "queueing unhandled message received during initialization {:?}",
@@ -290,68 +353,474 @@ async fn run_flowgraph<S: Scheduler>(
                 block_id,
                 port_id,
                 data,
+                tx,
             } => {
-                inboxes[block_id]
-                    .as_mut()
-                    .unwrap()
-                    .send(BlockMessage::Call { port_id, data })
-                    .await
-                    .unwrap();
+                let ret = async {
+                    block_inbox(&mut inboxes, block_id)?
+                        .send(BlockMessage::Call { port_id, data })
+                        .await?;
+                    Ok(())
+                }
+                .await;
+                let _ = tx.send(ret);
             }
             FlowgraphMessage::BlockCallback {
                 block_id,
                 port_id,
                 data,
                 tx,
+            } => match block_inbox(&mut inboxes, block_id) {
+                Ok(inbox) => {
+                    // the block replies or, if it terminated, drops the channel
+                    let _ = inbox
+                        .send(BlockMessage::Callback { port_id, data, tx })
+                        .await;
+                }
+                Err(e) => {
+                    let _ = tx.send(Err(e));
+                }
+            },
+            FlowgraphMessage::BlockScheduledCall {
+                block_id,
+                port_id,
+                data,
+                time,
+                tx,
             } => {
-                inboxes[block_id]
-                    .as_mut()
-                    .unwrap()
-                    .send(BlockMessage::Callback { port_id, data, tx })
-                    .await
-                    .unwrap();
+                let ret = async {
+                    let i = info.get(&block_id).context("invalid block")?;
+                    if port_id >= i.message_inputs.len() {
+                        bail!("invalid handler id {}", port_id);
+                    }
+                    if let CallTime::Item { input, .. } = time {
+                        if input >= i.stream_inputs.len() {
+                            bail!("invalid stream input {}", input);
+                        }
+                    }
+                    block_inbox(&mut inboxes, block_id)?
+                        .send(BlockMessage::ScheduledCall {
+                            port_id,
+                            data,
+                            time,
+                        })
+                        .await?;
+                    Ok(())
+                }
+                .await;
+                let _ = tx.send(ret);
             }
-            FlowgraphMessage::BlockDone { block_id, block } => {
-                *topology.blocks.get_mut(block_id).unwrap() = Some(block);
+            FlowgraphMessage::Initialized => {}
+            FlowgraphMessage::BlockDone {
+                block_id,
+                mut block,
+            } => {
+                if let Some(tx) = removals.remove(&block_id) {
+                    for i in block.stream_inputs_mut() {
+                        i.remove_reader();
+                    }
+                    topology.blocks.remove(block_id);
+                    inboxes[block_id] = None;
+                    let _ = tx.send(Ok(block));
+                } else {
+                    *topology.blocks.get_mut(block_id).unwrap() = Some(block);
+                }
 
                 active_blocks -= 1;
             }
+            FlowgraphMessage::BlockError {
+                block_id,
+                block,
+                phase,
+                error: e,
+            } => {
+                if let Some(tx) = removals.remove(&block_id) {
+                    let _ = tx.send(Err(anyhow::anyhow!("block failed in {}", phase)));
+                }
+                if error.is_none() {
+                    let instance_name = block.instance_name().unwrap().to_string();
+                    error = Some(FlowgraphError::new(block_id, instance_name, phase, e));
+                    for (id, opt) in inboxes.iter_mut() {
+                        if let Some(ref mut chan) = opt {
+                            if id != block_id && chan.send(BlockMessage::Terminate).await.is_err() {
+                                debug!(
+                                    "runtime tried to terminate block that was already terminated"
+                                );
+                            }
+                        }
+                    }
+                }
+                *topology.blocks.get_mut(block_id).unwrap() = Some(block);
+                active_blocks -= 1;
+            }
+            FlowgraphMessage::AddBlock { mut block, tx } => {
+                block.set_instance_name(unique_name(&info, &block));
+                let block_id = topology.add_block(block);
+                let block = topology.blocks[block_id].take().unwrap();
+                info.insert(block_id, BlockInfo::new(&block));
+
+                let inbox = scheduler.spawn_block(block, block_id, &main_channel);
+                while !inboxes.contains(block_id) {
+                    inboxes.insert(None);
+                }
+                inboxes[block_id] = Some(inbox);
+                active_blocks += 1;
+
+                pending.push(block_id);
+                for id in ready_blocks(&mut pending, &topology, &info) {
+                    if let Ok(inbox) = block_inbox(&mut inboxes, id) {
+                        start_block(inbox).await;
+                    }
+                }
+                let _ = tx.send(Ok(block_id));
+            }
+            FlowgraphMessage::RemoveBlock { block_id, tx } => {
+                let ret = async {
+                    // validate everything, before the topology is changed
+                    let n_inputs = info
+                        .get(&block_id)
+                        .context("invalid block")?
+                        .stream_inputs
+                        .len();
+                    let mut inbox = block_inbox(&mut inboxes, block_id)?.clone();
+
+                    let mut edges = Vec::new();
+                    for ((src, src_port, _), v) in topology.stream_edges.iter() {
+                        for (dst, dst_port) in v.iter() {
+                            if *src == block_id || *dst == block_id {
+                                edges.push((*src, *src_port, *dst, *dst_port));
+                            }
+                        }
+                    }
+                    for (src, _, dst, _) in edges.iter() {
+                        block_inbox(&mut inboxes, *src)?;
+                        block_inbox(&mut inboxes, *dst)?;
+                    }
+
+                    // stream edges, an edge is only removed from the topology, once its buffer
+                    // removed the reader
+                    for (src, src_port, dst, dst_port) in edges.into_iter() {
+                        let dst_inbox = block_inbox(&mut inboxes, dst)?.clone();
+                        let src_inbox = block_inbox(&mut inboxes, src)?;
+                        disconnect_output(src_inbox, src_port, dst_port, dst_inbox).await?;
+                        topology.remove_stream_edge(src, src_port, dst, dst_port)?;
+                        if src == block_id {
+                            let _ = block_inbox(&mut inboxes, dst)?
+                                .send(BlockMessage::StreamInputDisconnect { dst_port })
+                                .await;
+                        }
+                    }
+                    topology.stream_edges.retain(|k, _| k.0 != block_id);
+
+                    // message edges
+                    let message_edges: Vec<_> = topology
+                        .message_edges
+                        .iter()
+                        .filter(|x| x.2 == block_id && x.0 != block_id)
+                        .copied()
+                        .collect();
+                    for (src, src_port, _, dst_port) in message_edges {
+                        if let Ok(src_inbox) = block_inbox(&mut inboxes, src) {
+                            let _ = src_inbox
+                                .send(BlockMessage::MessageOutputDisconnect {
+                                    src_port,
+                                    dst_port,
+                                    dst_inbox: inbox.clone(),
+                                })
+                                .await;
+                        }
+                    }
+                    topology
+                        .message_edges
+                        .retain(|x| x.0 != block_id && x.2 != block_id);
+                    info.remove(&block_id);
+
+                    // a finished block was already handed back to the topology
+                    if let Some(Some(_)) = topology.blocks.get(block_id) {
+                        let mut block = topology.blocks.remove(block_id).unwrap();
+                        for i in block.stream_inputs_mut() {
+                            i.remove_reader();
+                        }
+                        inboxes[block_id] = None;
+                        return Ok(Some(block));
+                    }
+
+                    // shut down, draining the inputs
+                    if let Some(i) = pending.iter().position(|x| *x == block_id) {
+                        pending.remove(i);
+                        let _ = inbox.send(BlockMessage::Terminate).await;
+                    } else if n_inputs == 0 {
+                        let _ = inbox.send(BlockMessage::Terminate).await;
+                    } else {
+                        for input_id in 0..n_inputs {
+                            let _ = inbox.send(BlockMessage::StreamInputDone { input_id }).await;
+                        }
+                    }
+                    Ok::<Option<Block>, anyhow::Error>(None)
+                }
+                .await;
+
+                match ret {
+                    Ok(Some(block)) => {
+                        let _ = tx.send(Ok(block));
+                    }
+                    Ok(None) => {
+                        removals.insert(block_id, tx);
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Err(e));
+                    }
+                }
+            }
+            FlowgraphMessage::ConnectStream {
+                src_block,
+                src_port,
+                dst_block,
+                dst_port,
+                buffer,
+                tx,
+            } => {
+                let ret = async {
+                    let src = info.get(&src_block).context("invalid src block")?;
+                    let dst = info.get(&dst_block).context("invalid dst block")?;
+                    let (src_port, src_size, src_type) = src.stream_output(&src_port)?;
+                    let (dst_port, dst_size, dst_type) = dst.stream_input(&dst_port)?;
+                    check_items(src_size, src_type, dst_size, dst_type)?;
+
+                    let src_inbox = block_inbox(&mut inboxes, src_block)?.clone();
+                    let dst_inbox = block_inbox(&mut inboxes, dst_block)?.clone();
+                    let new_buffer = topology.add_stream_edge(
+                        src_block,
+                        src_port,
+                        dst_block,
+                        dst_port,
+                        BufferBuilderEntry::new(src_size, buffer),
+                    )?;
+
+                    if new_buffer {
+                        let min_items = std::cmp::max(src.min_items, dst.min_items);
+                        let writer = topology
+                            .stream_buffer(src_block, src_port)
+                            .unwrap()
+                            .build(min_items, src_inbox.clone(), src_port)
+                            .and_then(|mut w| {
+                                let r = w.add_reader(dst_inbox.clone(), dst_port)?;
+                                Ok((w, r))
+                            });
+                        let (writer, reader) = match writer {
+                            Ok(w) => w,
+                            Err(e) => {
+                                // the output has no buffer, so drop the edge with its builder
+                                topology
+                                    .stream_edges
+                                    .retain(|k, _| !(k.0 == src_block && k.1 == src_port));
+                                return Err(e);
+                            }
+                        };
+                        block_inbox(&mut inboxes, dst_block)?
+                            .send(BlockMessage::StreamInputInit { dst_port, reader })
+                            .await?;
+                        block_inbox(&mut inboxes, src_block)?
+                            .send(BlockMessage::StreamOutputInit { src_port, writer })
+                            .await?;
+                    } else {
+                        let src_inbox = block_inbox(&mut inboxes, src_block)?;
+                        if let Err(e) =
+                            connect_output(src_inbox, src_port, dst_port, dst_inbox).await
+                        {
+                            topology
+                                .remove_stream_edge(src_block, src_port, dst_block, dst_port)?;
+                            return Err(e);
+                        }
+                    }
+
+                    for id in ready_blocks(&mut pending, &topology, &info) {
+                        start_block(block_inbox(&mut inboxes, id)?).await;
+                    }
+                    Ok(())
+                }
+                .await;
+                let _ = tx.send(ret);
+            }
+            FlowgraphMessage::DisconnectStream {
+                src_block,
+                src_port,
+                dst_block,
+                dst_port,
+                tx,
+            } => {
+                let ret = async {
+                    let src = info.get(&src_block).context("invalid src block")?;
+                    let dst = info.get(&dst_block).context("invalid dst block")?;
+                    let (src_port, _, _) = src.stream_output(&src_port)?;
+                    let (dst_port, _, _) = dst.stream_input(&dst_port)?;
+
+                    if !topology.has_stream_edge(src_block, src_port, dst_block, dst_port) {
+                        bail!("stream ports not connected");
+                    }
+
+                    let dst_inbox = block_inbox(&mut inboxes, dst_block)?.clone();
+                    let src_inbox = block_inbox(&mut inboxes, src_block)?;
+                    disconnect_output(src_inbox, src_port, dst_port, dst_inbox).await?;
+                    topology.remove_stream_edge(src_block, src_port, dst_block, dst_port)?;
+                    block_inbox(&mut inboxes, dst_block)?
+                        .send(BlockMessage::StreamInputDisconnect { dst_port })
+                        .await?;
+                    Ok(())
+                }
+                .await;
+                let _ = tx.send(ret);
+            }
+            FlowgraphMessage::ConnectMessage {
+                src_block,
+                src_port,
+                dst_block,
+                dst_port,
+                tx,
+            } => {
+                let ret = async {
+                    let src = info.get(&src_block).context("invalid src block")?;
+                    let dst = info.get(&dst_block).context("invalid dst block")?;
+                    let src_port = src.message_output(&src_port)?;
+                    let dst_port = dst.message_input(&dst_port)?;
+
+                    let dst_inbox = block_inbox(&mut inboxes, dst_block)?.clone();
+                    block_inbox(&mut inboxes, src_block)?
+                        .send(BlockMessage::MessageOutputConnect {
+                            src_port,
+                            dst_port,
+                            dst_inbox,
+                        })
+                        .await?;
+                    topology
+                        .message_edges
+                        .push((src_block, src_port, dst_block, dst_port));
+                    Ok(())
+                }
+                .await;
+                let _ = tx.send(ret);
+            }
+            FlowgraphMessage::SubscribeMessage {
+                block_id,
+                port,
+                inbox,
+                tx,
+            } => {
+                let ret = async {
+                    let src = info.get(&block_id).context("invalid block")?;
+                    let src_port = src.message_output(&port)?;
+                    block_inbox(&mut inboxes, block_id)?
+                        .send(BlockMessage::MessageOutputSubscribe { src_port, inbox })
+                        .await?;
+                    Ok(())
+                }
+                .await;
+                let _ = tx.send(ret);
+            }
+            FlowgraphMessage::DisconnectMessage {
+                src_block,
+                src_port,
+                dst_block,
+                dst_port,
+                tx,
+            } => {
+                let ret = async {
+                    let src = info.get(&src_block).context("invalid src block")?;
+                    let dst = info.get(&dst_block).context("invalid dst block")?;
+                    let src_port = src.message_output(&src_port)?;
+                    let dst_port = dst.message_input(&dst_port)?;
+
+                    let e = (src_block, src_port, dst_block, dst_port);
+                    let i = topology
+                        .message_edges
+                        .iter()
+                        .position(|x| *x == e)
+                        .context("message ports not connected")?;
+
+                    let dst_inbox = block_inbox(&mut inboxes, dst_block)?.clone();
+                    block_inbox(&mut inboxes, src_block)?
+                        .send(BlockMessage::MessageOutputDisconnect {
+                            src_port,
+                            dst_port,
+                            dst_inbox,
+                        })
+                        .await?;
+                    topology.message_edges.remove(i);
+                    Ok(())
+                }
+                .await;
+                let _ = tx.send(ret);
+            }
             FlowgraphMessage::BlockDescription { block_id, tx } => {
-                inboxes[block_id]
-                    .as_mut()
-                    .unwrap()
-                    .send(BlockMessage::BlockDescription { tx })
-                    .await
-                    .unwrap();
+                match block_inbox(&mut inboxes, block_id) {
+                    Ok(inbox) => {
+                        // the block replies or, if it terminated, drops the channel
+                        let _ = inbox.send(BlockMessage::BlockDescription { tx }).await;
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Err(e));
+                    }
+                }
             }
             FlowgraphMessage::FlowgraphDescription { tx } => {
                 let mut blocks = Vec::new();
                 let ids: Vec<usize> = topology.blocks.iter().map(|x| x.0).collect();
                 for id in ids {
-                    let (b_tx, rx) = oneshot::channel::<BlockDescription>();
-                    inboxes[id]
-                        .as_mut()
-                        .unwrap()
-                        .send(BlockMessage::BlockDescription { tx: b_tx })
-                        .await
-                        .unwrap();
-                    blocks.push(rx.await.unwrap());
-                }
-
-                let stream_edges = topology
-                    .stream_edges
-                    .iter()
-                    .flat_map(|x| x.1.iter().map(|y| (x.0 .0, x.0 .1, y.0, y.1)))
-                    .collect();
+                    let (b_tx, rx) = oneshot::channel::<Result<BlockDescription>>();
+                    if let Ok(inbox) = block_inbox(&mut inboxes, id) {
+                        let _ = inbox
+                            .send(BlockMessage::BlockDescription { tx: b_tx })
+                            .await;
+                    }
+                    // blocks that already terminated do not reply
+                    if let Ok(Ok(d)) = rx.await {
+                        blocks.push(d);
+                    }
+                }
+
+                let mut stream_edges = Vec::new();
+                let mut stream_edge_buffers = Vec::new();
+                for ((src, src_port, buffer), v) in topology.stream_edges.iter() {
+                    let description = buffer.description();
+                    for (dst, dst_port) in v.iter() {
+                        stream_edges.push((*src, *src_port, *dst, *dst_port));
+                        stream_edge_buffers.push(description.clone());
+                    }
+                }
                 let message_edges = topology.message_edges.clone();
 
                 tx.send(FlowgraphDescription {
                     blocks,
                     stream_edges,
                     message_edges,
+                    stream_edge_buffers,
                 })
                 .unwrap();
             }
+            FlowgraphMessage::Snapshot { tx } => {
+                let mut snapshot = FlowgraphSnapshot::new();
+                let ids: Vec<usize> = topology.blocks.iter().map(|x| x.0).collect();
+                for id in ids {
+                    // blocks that are being removed are not part of the snapshot
+                    let name = match info.get(&id) {
+                        Some(i) => &i.instance_name,
+                        None => continue,
+                    };
+                    // finished blocks are handed back to the topology
+                    let parameters = if let Some(b) = topology.blocks[id].as_ref() {
+                        b.parameters()
+                    } else if let Ok(inbox) = block_inbox(&mut inboxes, id) {
+                        let (b_tx, rx) = oneshot::channel::<Vec<(String, Pmt)>>();
+                        let _ = inbox.send(BlockMessage::Parameters { tx: b_tx }).await;
+                        rx.await.unwrap_or_default()
+                    } else {
+                        continue;
+                    };
+                    for (parameter, value) in parameters {
+                        snapshot.set(name, &parameter, value);
+                    }
+                }
+                let _ = tx.send(snapshot);
+            }
             FlowgraphMessage::Terminate => {
                 for (_, opt) in inboxes.iter_mut() {
                     if let Some(ref mut chan) = opt {
@@ -362,14 +831,264 @@ async fn run_flowgraph<S: Scheduler>(
                 }
                 bail!("Flowgraph was terminated");
             }
-            _ => warn!("main loop received unhandled message"),
         }
     }
 
     fg.topology = Some(topology);
+    if let Some(mut e) = error {
+        e.set_flowgraph(fg);
+        return Err(e.into());
+    }
     Ok(fg)
 }
 
+/// Ports of a [Block], kept by the runtime, since running blocks are owned by their task.
+struct BlockInfo {
+    instance_name: String,
+    min_items: usize,
+    stream_inputs: Vec<(String, usize, ItemType)>,
+    stream_outputs: Vec<(String, usize, ItemType)>,
+    message_inputs: Vec<String>,
+    message_outputs: Vec<String>,
+}
+
+impl BlockInfo {
+    fn new(block: &Block) -> BlockInfo {
+        BlockInfo {
+            instance_name: block.instance_name().unwrap_or("").to_string(),
+            min_items: block.min_items(),
+            stream_inputs: block
+                .stream_inputs()
+                .iter()
+                .map(|x| (x.name().to_string(), x.item_size(), x.item_type()))
+                .collect(),
+            stream_outputs: block
+                .stream_outputs()
+                .iter()
+                .map(|x| (x.name().to_string(), x.item_size(), x.item_type()))
+                .collect(),
+            message_inputs: block.message_input_names(),
+            message_outputs: block
+                .message_outputs()
+                .iter()
+                .map(|x| x.name().to_string())
+                .collect(),
+        }
+    }
+
+    fn stream_input(&self, name: &str) -> Result<(usize, usize, ItemType)> {
+        self.stream_inputs
+            .iter()
+            .enumerate()
+            .find(|(_, x)| x.0 == name)
+            .map(|(i, x)| (i, x.1, x.2))
+            .context("invalid dst port name")
+    }
+
+    fn stream_output(&self, name: &str) -> Result<(usize, usize, ItemType)> {
+        self.stream_outputs
+            .iter()
+            .enumerate()
+            .find(|(_, x)| x.0 == name)
+            .map(|(i, x)| (i, x.1, x.2))
+            .context("invalid src port name")
+    }
+
+    fn message_input(&self, name: &str) -> Result<usize> {
+        self.message_inputs
+            .iter()
+            .position(|x| x == name)
+            .context("invalid dst port name")
+    }
+
+    fn message_output(&self, name: &str) -> Result<usize> {
+        self.message_outputs
+            .iter()
+            .position(|x| x == name)
+            .context("invalid src port name")
+    }
+}
+
+/// Find a unique instance name for a block that is added to a running flowgraph.
+fn unique_name(info: &HashMap<usize, BlockInfo>, block: &Block) -> String {
+    let (mut i, base_name, mut block_name) = if let Some(name) = block.instance_name() {
+        (-1, name.to_string(), name.to_string())
+    } else {
+        (
+            0,
+            block.type_name().to_string(),
+            format!("{}_{}", block.type_name(), 0),
+        )
+    };
+
+    while info.values().any(|x| x.instance_name == block_name) {
+        i += 1;
+        block_name = format!("{}_{}", base_name, i);
+    }
+    block_name
+}
+
+/// Blocks that were added to a running flowgraph are started, once all their stream ports are connected.
+fn ready_blocks(
+    pending: &mut Vec<usize>,
+    topology: &Topology,
+    info: &HashMap<usize, BlockInfo>,
+) -> Vec<usize> {
+    let mut ready = Vec::new();
+    pending.retain(|id| {
+        let block_info = &info[id];
+        let inputs = (0..block_info.stream_inputs.len()).all(|p| {
+            topology
+                .stream_edges
+                .values()
+                .any(|v| v.contains(&(*id, p)))
+        });
+        let outputs =
+            (0..block_info.stream_outputs.len()).all(|p| topology.stream_buffer(*id, p).is_some());
+
+        if inputs && outputs {
+            ready.push(*id);
+            false
+        } else {
+            true
+        }
+    });
+    ready
+}
+
+/// Inbox of a block of the running flowgraph.
+fn block_inbox(
+    inboxes: &mut Slab<Option<Sender<BlockMessage>>>,
+    block_id: usize,
+) -> Result<&mut Sender<BlockMessage>> {
+    inboxes
+        .get_mut(block_id)
+        .and_then(Option::as_mut)
+        .context("invalid block")
+}
+
+/// Add the reader of a stream edge to the buffer of the output.
+async fn connect_output(
+    src_inbox: &mut Sender<BlockMessage>,
+    src_port: usize,
+    dst_port: usize,
+    dst_inbox: Sender<BlockMessage>,
+) -> Result<()> {
+    let (tx, rx) = oneshot::channel::<Result<()>>();
+    src_inbox
+        .send(BlockMessage::StreamOutputConnect {
+            src_port,
+            dst_port,
+            dst_inbox,
+            tx,
+        })
+        .await
+        .context("source block terminated")?;
+    rx.await.context("source block terminated")?
+}
+
+/// Remove the reader of a stream edge from the buffer of the output.
+async fn disconnect_output(
+    src_inbox: &mut Sender<BlockMessage>,
+    src_port: usize,
+    dst_port: usize,
+    dst_inbox: Sender<BlockMessage>,
+) -> Result<()> {
+    let (tx, rx) = oneshot::channel::<Result<()>>();
+    if src_inbox
+        .send(BlockMessage::StreamOutputDisconnect {
+            src_port,
+            dst_port,
+            dst_inbox,
+            tx,
+        })
+        .await
+        .is_err()
+    {
+        // the block terminated, i.e., it does not write to the buffer anymore
+        return Ok(());
+    }
+    rx.await.unwrap_or(Ok(()))
+}
+
+async fn start_block(inbox: &mut Sender<BlockMessage>) {
+    let _ = inbox.send(BlockMessage::Initialize).await;
+    let _ = inbox.send(BlockMessage::Notify).await;
+}
+
+/// Handle messages that change the connections of a block.
+///
+/// Returns the message, if it is not related to the connections of the block.
+async fn connect_block(block: &mut Block, m: BlockMessage) -> Option<BlockMessage> {
+    match m {
+        BlockMessage::StreamOutputInit { src_port, writer } => {
+            block.stream_output_mut(src_port).init(writer);
+        }
+        BlockMessage::StreamInputInit { dst_port, reader } => {
+            block.stream_input_mut(dst_port).set_reader(reader);
+        }
+        BlockMessage::StreamOutputConnect {
+            src_port,
+            dst_port,
+            mut dst_inbox,
+            tx,
+        } => {
+            match block
+                .stream_output_mut(src_port)
+                .add_reader(dst_inbox.clone(), dst_port)
+            {
+                Ok(reader) => {
+                    let _ = dst_inbox
+                        .send(BlockMessage::StreamInputInit { dst_port, reader })
+                        .await;
+                    let _ = tx.send(Ok(()));
+                }
+                Err(e) => {
+                    let _ = tx.send(Err(e));
+                }
+            }
+        }
+        BlockMessage::StreamOutputDisconnect {
+            src_port,
+            dst_port,
+            dst_inbox,
+            tx,
+        } => {
+            let _ = tx.send(
+                block
+                    .stream_output_mut(src_port)
+                    .remove_reader(&dst_inbox, dst_port),
+            );
+        }
+        BlockMessage::StreamInputDisconnect { dst_port } => {
+            block.stream_input_mut(dst_port).remove_reader();
+        }
+        BlockMessage::MessageOutputConnect {
+            src_port,
+            dst_port,
+            dst_inbox,
+        } => {
+            block
+                .message_output_mut(src_port)
+                .connect(dst_port, dst_inbox);
+        }
+        BlockMessage::MessageOutputSubscribe { src_port, inbox } => {
+            block.message_output_mut(src_port).subscribe(inbox);
+        }
+        BlockMessage::MessageOutputDisconnect {
+            src_port,
+            dst_port,
+            dst_inbox,
+        } => {
+            block
+                .message_output_mut(src_port)
+                .disconnect(dst_port, &dst_inbox);
+        }
+        m => return Some(m),
+    }
+    None
+}
+
 pub(crate) async fn run_block(
     mut block: Block,
     block_id: usize,
@@ -382,43 +1101,69 @@ pub(crate) async fn run_block(
         finished: false,
         block_on: None,
     };
+    let mut restarts = 0;
+    let mut stats = BlockStats {
+        handler_calls: vec![0; block.message_input_names().len()],
+        ..BlockStats::default()
+    };
+
+    // messages held until their scheduled time
+    let mut scheduled: Vec<(CallTime, usize, Pmt)> = Vec::new();
 
     // setup phase
+    let mut restore: Vec<(String, Pmt)> = Vec::new();
     loop {
         match inbox.next().await.context("no msg")? {
             BlockMessage::Initialize => {
                 if let Err(e) = block.init().await {
-                    error!(
-                        "{}: Error during initialization. Terminating.",
-                        block.instance_name().unwrap()
-                    );
-                    main_inbox.send(FlowgraphMessage::Terminate).await?;
-                    return Err(e);
-                } else {
-                    main_inbox.send(FlowgraphMessage::Initialized).await?;
+                    return block_error(main_inbox, block_id, block, BlockPhase::Init, e).await;
+                }
+                for (name, p) in restore.drain(..) {
+                    if let Some(id) = block.message_input_name_to_id(&name) {
+                        stats.handler_calls[id] += 1;
+                        if let Err(e) = block.call_handler(id, p).await {
+                            return block_error(
+                                main_inbox,
+                                block_id,
+                                block,
+                                BlockPhase::Handler,
+                                e,
+                            )
+                            .await;
+                        }
+                    } else {
+                        warn!(
+                            "{}: cannot restore unknown parameter {}",
+                            block.instance_name().unwrap(),
+                            name
+                        );
+                    }
                 }
+                main_inbox.send(FlowgraphMessage::Initialized).await?;
                 break;
             }
-            BlockMessage::StreamOutputInit { src_port, writer } => {
-                block.stream_output_mut(src_port).init(writer);
-            }
-            BlockMessage::StreamInputInit { dst_port, reader } => {
-                block.stream_input_mut(dst_port).set_reader(reader);
+            BlockMessage::Restore { parameters } => restore = parameters,
+            BlockMessage::ScheduledCall {
+                port_id,
+                data,
+                time,
+            } => schedule_call(&block, &mut scheduled, port_id, data, time),
+            BlockMessage::Terminate => {
+                // removed from the flowgraph, before it was started
+                let _ = main_inbox
+                    .send(FlowgraphMessage::BlockDone { block_id, block })
+                    .await;
+                return Ok(());
             }
-            BlockMessage::MessageOutputConnect {
-                src_port,
-                dst_port,
-                dst_inbox,
-            } => {
-                block
-                    .message_output_mut(src_port)
-                    .connect(dst_port, dst_inbox);
+            m => {
+                if let Some(t) = connect_block(&mut block, m).await {
+                    warn!(
+                        "{} unhandled message during init {:?}",
+                        block.instance_name().unwrap(),
+                        t
+                    );
+                }
             }
-            t => warn!(
-                "{} unhandled message during init {:?}",
-                block.instance_name().unwrap(),
-                t
-            ),
         }
     }
 
@@ -428,6 +1173,7 @@ pub(crate) async fn run_block(
     // main loop
     loop {
         // ================== non blocking
+        let mut queued = 0;
         loop {
             match inbox.next().now_or_never() {
                 Some(Some(BlockMessage::Notify)) => {}
@@ -442,6 +1188,16 @@ pub(crate) async fn run_block(
                         .iter()
                         .map(|x| x.name().to_string())
                         .collect();
+                    let stream_input_types: Vec<String> = block
+                        .stream_inputs()
+                        .iter()
+                        .map(|x| x.item_type().describe(x.item_size()))
+                        .collect();
+                    let stream_output_types: Vec<String> = block
+                        .stream_outputs()
+                        .iter()
+                        .map(|x| x.item_type().describe(x.item_size()))
+                        .collect();
                     let message_inputs: Vec<String> = block.message_input_names();
                     let message_outputs: Vec<String> = block
                         .message_outputs()
@@ -455,11 +1211,17 @@ pub(crate) async fn run_block(
                         instance_name: block.instance_name().unwrap().to_string(),
                         stream_inputs,
                         stream_outputs,
+                        stream_input_types,
+                        stream_output_types,
                         message_inputs,
                         message_outputs,
                         blocking: block.is_blocking(),
+                        stats: block_stats(&block, &stats),
                     };
-                    tx.send(description).unwrap();
+                    let _ = tx.send(Ok(description));
+                }
+                Some(Some(BlockMessage::Parameters { tx })) => {
+                    let _ = tx.send(block.parameters());
                 }
                 Some(Some(BlockMessage::StreamInputDone { input_id })) => {
                     block.stream_input_mut(input_id).finish();
@@ -468,38 +1230,100 @@ pub(crate) async fn run_block(
                     work_io.finished = true;
                 }
                 Some(Some(BlockMessage::Call { port_id, data })) => {
-                    if let Err(e) = block.call_handler(port_id, data).await {
-                        error!(
-                            "{}: Error in callback. Terminating. ({:?})",
+                    if let Some(c) = stats.handler_calls.get_mut(port_id) {
+                        *c += 1;
+                        if let Err(e) = block.call_handler(port_id, data).await {
+                            return block_error(
+                                main_inbox,
+                                block_id,
+                                block,
+                                BlockPhase::Handler,
+                                e,
+                            )
+                            .await;
+                        }
+                    } else {
+                        warn!(
+                            "{}: dropping message for unknown handler {}",
                             block.instance_name().unwrap(),
-                            e
+                            port_id
                         );
-                        main_inbox.send(FlowgraphMessage::Terminate).await?;
-                        return Err(e);
                     }
                 }
                 Some(Some(BlockMessage::Callback { port_id, data, tx })) => {
+                    if let Some(c) = stats.handler_calls.get_mut(port_id) {
+                        *c += 1;
+                    } else {
+                        let _ = tx.send(Err(anyhow::anyhow!("invalid handler id {}", port_id)));
+                        continue;
+                    }
                     match block.call_handler(port_id, data).await {
                         Ok(res) => {
-                            tx.send(res).unwrap();
+                            let _ = tx.send(Ok(res));
                         }
                         Err(e) => {
-                            error!(
-                                "{}: Error in callback. Terminating. ({:?})",
-                                block.instance_name().unwrap(),
-                                e
-                            );
-                            main_inbox.send(FlowgraphMessage::Terminate).await?;
-                            return Err(e);
+                            return block_error(
+                                main_inbox,
+                                block_id,
+                                block,
+                                BlockPhase::Handler,
+                                e,
+                            )
+                            .await;
                         }
                     }
                 }
+                Some(Some(BlockMessage::ScheduledCall {
+                    port_id,
+                    data,
+                    time,
+                })) => {
+                    schedule_call(&block, &mut scheduled, port_id, data, time);
+                }
                 Some(Some(BlockMessage::Terminate)) => work_io.finished = true,
-                Some(Some(t)) => warn!("block unhandled message in main loop {:?}", t),
+                Some(Some(m)) => {
+                    if let Some(t) = connect_block(&mut block, m).await {
+                        warn!("block unhandled message in main loop {:?}", t);
+                    }
+                }
                 _ => break,
             }
             // received at least one message
             work_io.call_again = true;
+            queued += 1;
+        }
+        stats.message_queue_depth = queued;
+
+        // ================== scheduled messages
+        if !scheduled.is_empty() {
+            let mut i = 0;
+            while i < scheduled.len() {
+                if !call_due(&block, &scheduled[i].0) {
+                    i += 1;
+                    continue;
+                }
+                let (_, port_id, data) = scheduled.remove(i);
+                if let Some(c) = stats.handler_calls.get_mut(port_id) {
+                    *c += 1;
+                }
+                if let Err(e) = block.call_handler(port_id, data).await {
+                    return block_error(main_inbox, block_id, block, BlockPhase::Handler, e).await;
+                }
+                work_io.call_again = true;
+            }
+            let min_items = block.min_items();
+            for (id, input) in block.stream_inputs_mut().iter_mut().enumerate() {
+                input.set_limit(
+                    scheduled
+                        .iter()
+                        .filter_map(|(t, _, _)| match t {
+                            CallTime::Item { input, index } if *input == id => Some(*index),
+                            _ => None,
+                        })
+                        .min(),
+                    min_items,
+                );
+            }
         }
 
         // ================== shutdown
@@ -527,7 +1351,9 @@ pub(crate) async fn run_block(
             )
             .await;
 
-            block.deinit().await?;
+            if let Err(e) = block.deinit().await {
+                return block_error(main_inbox, block_id, block, BlockPhase::Deinit, e).await;
+            }
 
             // ============= notify main thread
             let _ = main_inbox
@@ -536,36 +1362,78 @@ pub(crate) async fn run_block(
             break;
         }
 
+        // ================== wait until all stream ports are connected
+        if !block.stream_inputs().iter().all(|x| x.connected())
+            || !block.stream_outputs().iter().all(|x| x.connected())
+        {
+            let t = Instant::now();
+            future::select(inbox.as_mut().peek(), next_call(&scheduled)).await;
+            stats.blocked_time += t.elapsed().as_nanos() as u64;
+            continue;
+        }
+
         // ================== blocking
         if !work_io.call_again {
+            let t = Instant::now();
             if let Some(f) = work_io.block_on.take() {
-                let p = inbox.as_mut().peek();
+                let p = future::select(inbox.as_mut().peek(), next_call(&scheduled));
 
                 match future::select(f, p).await {
                     Either::Left(_) => {
                         work_io.call_again = true;
+                        stats.blocked_time += t.elapsed().as_nanos() as u64;
                     }
                     Either::Right((_, f)) => {
                         work_io.block_on = Some(f);
+                        stats.blocked_time += t.elapsed().as_nanos() as u64;
                         continue;
                     }
                 };
             } else {
-                inbox.as_mut().peek().await;
+                future::select(inbox.as_mut().peek(), next_call(&scheduled)).await;
+                stats.blocked_time += t.elapsed().as_nanos() as u64;
                 continue;
             }
         }
 
         // ================== work
         work_io.call_again = false;
-        if let Err(e) = block.work(&mut work_io).await {
-            error!(
-                "{}: Error in work(). Terminating. ({:?})",
-                block.instance_name().unwrap(),
-                e
-            );
-            main_inbox.send(FlowgraphMessage::Terminate).await?;
-            return Err(e);
+        let t = Instant::now();
+        let ret = block.work(&mut work_io).await;
+        stats.work_time += t.elapsed().as_nanos() as u64;
+        stats.work_calls += 1;
+        if let Err(e) = ret {
+            match block.error_policy() {
+                ErrorPolicy::Continue => {
+                    warn!(
+                        "{}: Error in work(). Continuing. ({:?})",
+                        block.instance_name().unwrap(),
+                        e
+                    );
+                }
+                ErrorPolicy::Restart(n) if restarts < n => {
+                    restarts += 1;
+                    warn!(
+                        "{}: Error in work(). Restarting ({}/{}). ({:?})",
+                        block.instance_name().unwrap(),
+                        restarts,
+                        n,
+                        e
+                    );
+                    if let Err(e) = block.deinit().await {
+                        return block_error(main_inbox, block_id, block, BlockPhase::Deinit, e)
+                            .await;
+                    }
+                    if let Err(e) = block.init().await {
+                        return block_error(main_inbox, block_id, block, BlockPhase::Init, e).await;
+                    }
+                    work_io.call_again = true;
+                    work_io.block_on = None;
+                }
+                _ => {
+                    return block_error(main_inbox, block_id, block, BlockPhase::Work, e).await;
+                }
+            }
         }
         block.commit();
 
@@ -574,3 +1442,109 @@ pub(crate) async fn run_block(
 
     Ok(())
 }
+
+/// Hold a message until it is due. Messages for stream inputs that do not exist are dropped.
+fn schedule_call(
+    block: &Block,
+    scheduled: &mut Vec<(CallTime, usize, Pmt)>,
+    port_id: usize,
+    data: Pmt,
+    time: CallTime,
+) {
+    if port_id >= block.message_input_names().len() {
+        warn!(
+            "{}: dropping message scheduled for unknown handler {}",
+            block.instance_name().unwrap(),
+            port_id
+        );
+        return;
+    }
+    if let CallTime::Item { input, .. } = time {
+        if input >= block.stream_inputs().len() {
+            warn!(
+                "{}: dropping message scheduled for unknown stream input {}",
+                block.instance_name().unwrap(),
+                input
+            );
+            return;
+        }
+    }
+    scheduled.push((time, port_id, data));
+}
+
+fn call_due(block: &Block, time: &CallTime) -> bool {
+    match time {
+        CallTime::Item { input, index } => block.stream_inputs()[*input].items_consumed() >= *index,
+        #[cfg(not(target_arch = "wasm32"))]
+        CallTime::Instant(t) => time::now() >= *t,
+    }
+}
+
+/// Resolves when the next message, scheduled at an instant, is due.
+#[cfg(not(target_arch = "wasm32"))]
+fn next_call(scheduled: &[(CallTime, usize, Pmt)]) -> impl Future + Unpin {
+    let next = scheduled
+        .iter()
+        .filter_map(|(t, _, _)| match t {
+            CallTime::Instant(t) => Some(*t),
+            _ => None,
+        })
+        .min();
+    match next {
+        Some(t) => Either::Left(Box::pin(time::sleep_until(t))),
+        None => Either::Right(future::pending()),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn next_call(_scheduled: &[(CallTime, usize, Pmt)]) -> impl Future + Unpin {
+    future::pending::<()>()
+}
+
+/// Complete the counters, kept by `run_block`, with the port statistics of the [Block].
+fn block_stats(block: &Block, stats: &BlockStats) -> BlockStats {
+    BlockStats {
+        work_calls: stats.work_calls,
+        work_time: stats.work_time,
+        avg_work_time: stats.work_time.checked_div(stats.work_calls).unwrap_or(0),
+        blocked_time: stats.blocked_time,
+        items_consumed: block
+            .stream_inputs()
+            .iter()
+            .map(|x| x.items_consumed())
+            .collect(),
+        items_produced: block
+            .stream_outputs()
+            .iter()
+            .map(|x| x.items_produced())
+            .collect(),
+        input_fill: block.stream_inputs().iter().map(|x| x.fill()).collect(),
+        handler_calls: stats.handler_calls.clone(),
+        message_queue_depth: stats.message_queue_depth,
+    }
+}
+
+/// Hand a failed [Block] back to the runtime, which terminates the [Flowgraph].
+async fn block_error(
+    mut main_inbox: Sender<FlowgraphMessage>,
+    block_id: usize,
+    block: Block,
+    phase: BlockPhase,
+    error: anyhow::Error,
+) -> Result<()> {
+    error!(
+        "{}: Error in {}. Terminating. ({:?})",
+        block.instance_name().unwrap(),
+        phase,
+        error
+    );
+    main_inbox
+        .send(FlowgraphMessage::BlockError {
+            block_id,
+            block,
+            phase,
+            error,
+        })
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use async_task::{Runnable, Task};
+use futures::channel::mpsc::{channel, Sender};
+use futures::future::Future;
+use slab::Slab;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::runtime::config;
+use crate::runtime::run_block;
+use crate::runtime::scheduler::Scheduler;
+use crate::runtime::time::VirtualClock;
+use crate::runtime::BlockMessage;
+use crate::runtime::FlowgraphMessage;
+use crate::runtime::Topology;
+
+/// Runs all blocks on a single thread in a reproducible order with a virtual clock.
+///
+/// Tasks are polled in the order in which they are woken. Blocks that use
+/// [`runtime::time`](crate::runtime::time) see a virtual clock, which jumps to the next timer
+/// when no task is ready, i.e., waiting does not take wall-clock time. This makes flowgraphs
+/// with time-dependent blocks, like [Throttle](crate::blocks::Throttle), fast and reproducible
+/// in tests. Blocking blocks also run on the scheduler thread and should not block.
+#[derive(Clone, Debug)]
+pub struct DeterministicScheduler {
+    inner: Arc<DeterministicSchedulerInner>,
+}
+
+struct DeterministicSchedulerInner {
+    shared: Arc<Shared>,
+    worker: Option<thread::JoinHandle<()>>,
+}
+
+impl fmt::Debug for DeterministicSchedulerInner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeterministicSchedulerInner")
+            .field("clock", &self.shared.clock)
+            .finish()
+    }
+}
+
+struct Shared {
+    state: Mutex<State>,
+    ready: Condvar,
+    clock: VirtualClock,
+}
+
+#[derive(Default)]
+struct State {
+    runnables: VecDeque<Runnable>,
+    stop: bool,
+}
+
+impl Drop for DeterministicSchedulerInner {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().stop = true;
+        self.shared.ready.notify_one();
+        if let Some(w) = self.worker.take() {
+            if w.thread().id() != thread::current().id() {
+                w.join().unwrap();
+            }
+        }
+    }
+}
+
+impl DeterministicScheduler {
+    pub fn new() -> DeterministicScheduler {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State::default()),
+            ready: Condvar::new(),
+            clock: VirtualClock::new(),
+        });
+
+        let s = shared.clone();
+        let worker = thread::Builder::new()
+            .name("deterministic".to_string())
+            .spawn(move || run(s))
+            .expect("failed to spawn scheduler thread");
+
+        DeterministicScheduler {
+            inner: Arc::new(DeterministicSchedulerInner {
+                shared,
+                worker: Some(worker),
+            }),
+        }
+    }
+
+    /// Current virtual time.
+    pub fn now(&self) -> Instant {
+        self.inner.shared.clock.now()
+    }
+
+    /// Virtual time since the scheduler was created.
+    pub fn elapsed(&self) -> Duration {
+        self.inner.shared.clock.elapsed()
+    }
+}
+
+impl Default for DeterministicScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn run(shared: Arc<Shared>) {
+    shared.clock.enter();
+    loop {
+        let mut state = shared.state.lock().unwrap();
+        if state.stop {
+            return;
+        }
+        if let Some(r) = state.runnables.pop_front() {
+            drop(state);
+            r.run();
+            continue;
+        }
+        drop(state);
+
+        // all tasks are idle
+        if shared.clock.advance() {
+            continue;
+        }
+
+        let state = shared.state.lock().unwrap();
+        if !state.stop && state.runnables.is_empty() {
+            drop(shared.ready.wait(state).unwrap());
+        }
+    }
+}
+
+impl Scheduler for DeterministicScheduler {
+    fn run_topology(
+        &self,
+        topology: &mut Topology,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Slab<Option<Sender<BlockMessage>>> {
+        let mut inboxes = Slab::new();
+        let max = topology.blocks.iter().map(|(i, _)| i).max().unwrap_or(0);
+        for _ in 0..=max {
+            inboxes.insert(None);
+        }
+        let queue_size = config::config().queue_size;
+
+        // blocks are spawned in the order of their ids
+        for (id, block_o) in topology.blocks.iter_mut() {
+            let block = block_o.take().unwrap();
+
+            let (sender, receiver) = channel::<BlockMessage>(queue_size);
+            inboxes[id] = Some(sender);
+
+            self.spawn(run_block(block, id, main_channel.clone(), receiver))
+                .detach();
+        }
+
+        inboxes
+    }
+
+    fn spawn<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> Task<T> {
+        let shared: Weak<Shared> = Arc::downgrade(&self.inner.shared);
+        let schedule = move |runnable| {
+            if let Some(s) = shared.upgrade() {
+                s.state.lock().unwrap().runnables.push_back(runnable);
+                s.ready.notify_one();
+            }
+        };
+        let (runnable, task) = async_task::spawn(future, schedule);
+        runnable.schedule();
+        task
+    }
+
+    fn spawn_blocking<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> Task<T> {
+        self.spawn(future)
+    }
+}
@@ -1,3 +1,8 @@
+#[cfg(not(target_arch = "wasm32"))]
+mod deterministic;
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::runtime::scheduler::deterministic::DeterministicScheduler;
+
 #[cfg(feature = "flow_scheduler")]
 mod flow;
 #[cfg(feature = "flow_scheduler")]
@@ -1,4 +1,4 @@
-use futures::channel::mpsc::Sender;
+use futures::channel::mpsc::{channel, Sender};
 use futures::future::Future;
 use slab::Slab;
 
@@ -7,6 +7,9 @@ use async_task::Task;
 #[cfg(target_arch = "wasm32")]
 type Task<T> = super::wasm::TaskHandle<T>;
 
+use crate::runtime::config;
+use crate::runtime::run_block;
+use crate::runtime::Block;
 use crate::runtime::BlockMessage;
 use crate::runtime::FlowgraphMessage;
 use crate::runtime::Topology;
@@ -18,6 +21,29 @@ pub trait Scheduler: Clone + Send + 'static {
         main_channel: &Sender<FlowgraphMessage>,
     ) -> Slab<Option<Sender<BlockMessage>>>;
 
+    /// Spawn a single [Block], which is added to a running flowgraph.
+    fn spawn_block(
+        &self,
+        block: Block,
+        block_id: usize,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Sender<BlockMessage> {
+        let queue_size = config::config().queue_size;
+        let (sender, receiver) = channel::<BlockMessage>(queue_size);
+
+        let task = if block.is_blocking() {
+            self.spawn_blocking(run_block(block, block_id, main_channel.clone(), receiver))
+        } else {
+            self.spawn(run_block(block, block_id, main_channel.clone(), receiver))
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        task.detach();
+        #[cfg(target_arch = "wasm32")]
+        drop(task);
+
+        sender
+    }
+
     fn spawn<T: Send + 'static>(&self, future: impl Future<Output = T> + Send + 'static)
         -> Task<T>;
 
@@ -94,3 +94,19 @@ impl Default for WasmScheduler {
         Self::new()
     }
 }
+
+/// Stand-in for [std::time::Instant], which is not available on wasm.
+///
+/// Durations are always zero, i.e., timing statistics of blocks are not supported.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Instant;
+
+impl Instant {
+    pub(crate) fn now() -> Instant {
+        Instant
+    }
+
+    pub(crate) fn elapsed(&self) -> std::time::Duration {
+        std::time::Duration::ZERO
+    }
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+use crate::anyhow::{Context, Result};
+use crate::runtime::Pmt;
+
+/// Persistent parameters of the blocks of a [Flowgraph](crate::runtime::Flowgraph).
+///
+/// A snapshot is taken from a running flowgraph with
+/// [`FlowgraphHandle::snapshot`](crate::runtime::FlowgraphHandle::snapshot) and restored with
+/// [`Flowgraph::restore`](crate::runtime::Flowgraph::restore). Blocks are identified by their
+/// instance name, parameters by the name of their message handler. In TOML, a snapshot looks
+/// like
+///
+/// ```toml
+/// [blocks.SoapySource_0]
+/// freq = { F64 = 100e6 }
+/// sample_rate = { F64 = 3.2e6 }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FlowgraphSnapshot {
+    #[serde(default)]
+    pub blocks: BTreeMap<String, BTreeMap<String, Pmt>>,
+}
+
+impl FlowgraphSnapshot {
+    pub fn new() -> FlowgraphSnapshot {
+        FlowgraphSnapshot::default()
+    }
+
+    /// Saved value of a parameter.
+    pub fn get(&self, block: &str, parameter: &str) -> Option<&Pmt> {
+        self.blocks.get(block).and_then(|p| p.get(parameter))
+    }
+
+    /// Set the value of a parameter.
+    pub fn set(&mut self, block: &str, parameter: &str, value: Pmt) {
+        self.blocks
+            .entry(block.to_string())
+            .or_default()
+            .insert(parameter.to_string(), value);
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(s: &str) -> Result<FlowgraphSnapshot> {
+        serde_json::from_str(s).context("invalid snapshot")
+    }
+
+    // The TOML serializer does not support enums with data, like `Pmt`, so TOML is converted
+    // from and to the JSON representation.
+    pub fn to_toml(&self) -> Result<String> {
+        let v = toml::Value::try_from(serde_json::to_value(self)?)?;
+        Ok(toml::to_string(&v)?)
+    }
+
+    pub fn from_toml(s: &str) -> Result<FlowgraphSnapshot> {
+        let v: toml::Value = toml::from_str(s).context("invalid snapshot")?;
+        serde_json::from_value(serde_json::to_value(v)?).context("invalid snapshot")
+    }
+
+    /// Save the snapshot. The format (TOML or JSON) is derived from the extension.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let s = if is_toml(path) {
+            self.to_toml()?
+        } else {
+            self.to_json()?
+        };
+        std::fs::write(path, s).with_context(|| format!("cannot write snapshot {}", path.display()))
+    }
+
+    /// Load a snapshot. The format (TOML or JSON) is derived from the extension.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<Path>) -> Result<FlowgraphSnapshot> {
+        let path = path.as_ref();
+        let s = std::fs::read_to_string(path)
+            .with_context(|| format!("cannot read snapshot {}", path.display()))?;
+        if is_toml(path) {
+            FlowgraphSnapshot::from_toml(&s)
+        } else {
+            FlowgraphSnapshot::from_json(&s)
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn is_toml(path: &Path) -> bool {
+    path.extension().map_or(false, |e| e == "toml")
+}
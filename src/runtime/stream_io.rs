@@ -1,14 +1,87 @@
 use futures::channel::mpsc::Sender;
+use std::any::type_name;
+use std::any::TypeId;
 use std::fmt;
 use std::mem;
 use std::slice;
 
+use crate::anyhow::{bail, Context, Result};
 use crate::runtime::buffer::BufferReader;
 use crate::runtime::buffer::BufferWriter;
 use crate::runtime::tag::default_tag_propagation;
 use crate::runtime::BlockMessage;
 use crate::runtime::ItemTag;
 use crate::runtime::Tag;
+use crate::runtime::TagPropagation;
+
+/// Type of the items of a stream port.
+///
+/// Raw ports only define the size of their items. They can be connected to any port with the
+/// same item size, which allows blocks to reinterpret the bytes of a stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemType {
+    Raw,
+    Typed { id: TypeId, name: &'static str },
+}
+
+impl ItemType {
+    pub fn of<T: 'static>() -> ItemType {
+        ItemType::Typed {
+            id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+        }
+    }
+
+    /// Check if ports with these item types can be connected.
+    pub fn is_compatible(&self, other: &ItemType) -> bool {
+        match (self, other) {
+            (ItemType::Typed { id: a, .. }, ItemType::Typed { id: b, .. }) => a == b,
+            _ => true,
+        }
+    }
+
+    /// Description of the item type, i.e., the type name or, for raw ports, a byte array.
+    pub fn describe(&self, item_size: usize) -> String {
+        match self {
+            ItemType::Typed { name, .. } => name.to_string(),
+            ItemType::Raw => format!("[u8; {}]", item_size),
+        }
+    }
+}
+
+/// Check that a port can be accessed as a slice of `T`.
+fn check_slice<T: 'static>(port: &str, item_size: usize, item_type: ItemType) {
+    let ok = match item_type {
+        ItemType::Typed { id, .. } => {
+            id == TypeId::of::<T>() || TypeId::of::<T>() == TypeId::of::<u8>()
+        }
+        ItemType::Raw => mem::size_of::<T>() != 0 && item_size % mem::size_of::<T>() == 0,
+    };
+    assert!(
+        ok,
+        "stream port {} has item type {}, cannot access it as {}",
+        port,
+        item_type.describe(item_size),
+        type_name::<T>()
+    );
+}
+
+/// Check that an output can be connected to an input.
+pub(crate) fn check_items(
+    output_size: usize,
+    output_type: ItemType,
+    input_size: usize,
+    input_type: ItemType,
+) -> Result<()> {
+    if output_size != input_size || !output_type.is_compatible(&input_type) {
+        bail!(
+            "item types do not match (output {}, input {})",
+            output_type.describe(output_size),
+            input_type.describe(input_size)
+        );
+    }
+    Ok(())
+}
 
 #[derive(Debug)]
 struct CurrentInput {
@@ -22,21 +95,42 @@ struct CurrentInput {
 pub struct StreamInput {
     name: String,
     item_size: usize,
+    item_type: ItemType,
     reader: Option<BufferReader>,
     current: Option<CurrentInput>,
     tags: Vec<ItemTag>,
+    items: u64,
+    fill: usize,
+    limit: Option<u64>,
+    min_items: usize,
+    truncated: bool,
 }
 
 unsafe impl Send for StreamInput {}
 
 impl StreamInput {
+    /// Create a raw input.
     pub fn new(name: &str, item_size: usize) -> StreamInput {
+        Self::with_type(name, item_size, ItemType::Raw)
+    }
+
+    pub fn typed<T: 'static>(name: &str) -> StreamInput {
+        Self::with_type(name, mem::size_of::<T>(), ItemType::of::<T>())
+    }
+
+    fn with_type(name: &str, item_size: usize, item_type: ItemType) -> StreamInput {
         StreamInput {
             name: name.to_string(),
             item_size,
+            item_type,
             reader: None,
             current: None,
             tags: Vec::new(),
+            items: 0,
+            fill: 0,
+            limit: None,
+            min_items: 1,
+            truncated: false,
         }
     }
 
@@ -44,10 +138,36 @@ impl StreamInput {
         self.item_size
     }
 
+    pub fn item_type(&self) -> ItemType {
+        self.item_type
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// Do not pass items beyond the given item index to the block, e.g., to deliver a scheduled
+    /// message exactly at this item.
+    ///
+    /// The block still gets at least `min_items` items, if available, since it might not be able
+    /// to make progress otherwise. In this case, the limit is only a split point and the message
+    /// is delivered after the block consumed past it.
+    pub(crate) fn set_limit(&mut self, limit: Option<u64>, min_items: usize) {
+        self.limit = limit;
+        self.min_items = min_items;
+        if limit.is_none() {
+            self.truncated = false;
+        }
+    }
+
+    /// The items passed to the block are limited, e.g., to deliver a scheduled message at an item.
+    ///
+    /// Blocks that access the buffer directly, bypassing [slice](Self::slice), should fall back
+    /// to the slices in this case.
+    pub fn limited(&self) -> bool {
+        self.limit.is_some()
+    }
+
     pub fn try_as<T: 'static>(&mut self) -> Option<&mut T> {
         self.reader.as_mut().unwrap().try_as::<T>()
     }
@@ -55,9 +175,8 @@ impl StreamInput {
     pub fn consume(&mut self, amount: usize) {
         debug_assert!(self.current.is_some());
         debug_assert!(
-            amount
-                <= self.current.as_mut().unwrap().len
-                    - self.current.as_mut().unwrap().index * self.item_size
+            amount * self.item_size
+                <= self.current.as_ref().unwrap().len - self.current.as_ref().unwrap().index
         );
 
         self.current.as_mut().unwrap().index += amount * self.item_size;
@@ -65,10 +184,22 @@ impl StreamInput {
         self.tags.iter_mut().for_each(|x| x.index -= amount);
     }
 
-    pub fn slice<T>(&mut self) -> &'static [T] {
+    fn load(&mut self) {
         if self.current.is_none() {
-            let (ptr, len, tags) = self.reader.as_mut().unwrap().bytes();
+            let (ptr, mut len, tags) = self.reader.as_mut().unwrap().bytes();
+            self.fill = len / self.item_size;
+            self.truncated = false;
+            if let Some(limit) = self.limit {
+                let max = std::cmp::max(limit.saturating_sub(self.items) as usize, self.min_items)
+                    * self.item_size;
+                if len > max {
+                    len = max;
+                    self.truncated = true;
+                }
+            }
+            let item_size = self.item_size;
             self.tags = tags;
+            self.tags.retain(|x| x.index * item_size < len);
             self.tags.sort_by_key(|x| x.index);
             self.current = Some(CurrentInput {
                 ptr,
@@ -77,17 +208,57 @@ impl StreamInput {
                 tags: self.tags.clone(),
             });
         }
+    }
 
+    /// Items that are not yet consumed.
+    unsafe fn items<'a, T>(&self) -> &'a [T] {
         let c = self.current.as_ref().unwrap();
-        unsafe { slice::from_raw_parts(c.ptr as *const T, c.len / mem::size_of::<T>()) }
+        // buffers without data may return a null pointer
+        if c.ptr.is_null() {
+            return &[];
+        }
+        slice::from_raw_parts(
+            c.ptr.add(c.index) as *const T,
+            (c.len - c.index) / mem::size_of::<T>(),
+        )
+    }
+
+    /// Returns the available input items.
+    ///
+    /// `T` has to be the item type of the port or `u8` to access the raw bytes. The slice borrows
+    /// the input, i.e., it has to be dropped before items are consumed.
+    pub fn slice<T: 'static>(&mut self) -> &[T] {
+        check_slice::<T>(&self.name, self.item_size, self.item_type);
+        self.load();
+        unsafe { self.items() }
+    }
+
+    /// Returns the available input items together with their tags.
+    pub fn slice_with_tags<T: 'static>(&mut self) -> (&[T], &Vec<ItemTag>) {
+        check_slice::<T>(&self.name, self.item_size, self.item_type);
+        self.load();
+        (unsafe { self.items() }, &self.tags)
+    }
+
+    /// Returns the available input items, without checking the item type or tying the slice to
+    /// the borrow of the input.
+    ///
+    /// # Safety
+    /// The slice is only valid during the current call to `work()` and must not be used after
+    /// the corresponding items were consumed. `T` has to match the type of the items in the
+    /// buffer.
+    pub unsafe fn slice_unchecked<T>(&mut self) -> &'static [T] {
+        self.load();
+        self.items()
     }
 
     /// Returns a mutable slice to the input buffer.
     ///
     /// # Safety
-    /// The block has to be the sole reader for the input buffer.
+    /// The block has to be the sole reader for the input buffer. The same restrictions as for
+    /// [`slice_unchecked`](Self::slice_unchecked) apply.
     pub unsafe fn slice_mut<T>(&mut self) -> &'static mut [T] {
-        let s = self.slice::<T>();
+        let s = self.slice_unchecked::<T>();
         slice::from_raw_parts_mut(s.as_ptr() as *mut T, s.len())
     }
 
@@ -100,6 +271,7 @@ impl StreamInput {
             let amount = c.index / self.item_size;
             if amount != 0 {
                 self.reader.as_mut().unwrap().consume(amount);
+                self.items += amount as u64;
             }
             self.current = None;
         }
@@ -113,21 +285,54 @@ impl StreamInput {
         }
     }
 
+    /// Total number of items consumed.
+    pub fn items_consumed(&self) -> u64 {
+        self.items
+    }
+
+    /// Accounts for items that the block took from the buffer directly, without
+    /// [consume](Self::consume), e.g., buffers of an
+    /// [in-place reader](crate::runtime::buffer::inplace::Reader).
+    pub fn add_consumed(&mut self, amount: usize) {
+        self.items += amount as u64;
+    }
+
+    /// Number of items in the input buffer, when the block last accessed it.
+    pub fn fill(&self) -> usize {
+        self.fill
+    }
+
     pub fn set_reader(&mut self, reader: BufferReader) {
         debug_assert!(self.reader.is_none());
         self.reader = Some(reader);
     }
 
+    /// Drops the reader of the input, e.g., when the upstream block is removed from a running flowgraph.
+    pub fn remove_reader(&mut self) -> Option<BufferReader> {
+        self.current = None;
+        self.tags.clear();
+        self.reader.take()
+    }
+
+    pub fn connected(&self) -> bool {
+        self.reader.is_some()
+    }
+
     pub async fn notify_finished(&mut self) {
-        self.reader.as_mut().unwrap().notify_finished().await;
+        if let Some(r) = self.reader.as_mut() {
+            r.notify_finished().await;
+        }
     }
 
     pub fn finish(&mut self) {
-        self.reader.as_mut().unwrap().finish();
+        if let Some(r) = self.reader.as_mut() {
+            r.finish();
+        }
     }
 
+    /// The upstream block finished. Items, held back for a scheduled message, are still pending.
     pub fn finished(&self) -> bool {
-        self.reader.as_ref().unwrap().finished()
+        self.reader.as_ref().unwrap().finished() && !self.truncated
     }
 }
 
@@ -135,19 +340,32 @@ impl StreamInput {
 pub struct StreamOutput {
     name: String,
     item_size: usize,
+    item_type: ItemType,
     writer: Option<BufferWriter>,
     tags: Vec<ItemTag>,
     offset: usize,
+    items: u64,
 }
 
 impl StreamOutput {
+    /// Create a raw output.
     pub fn new(name: &str, item_size: usize) -> StreamOutput {
+        Self::with_type(name, item_size, ItemType::Raw)
+    }
+
+    pub fn typed<T: 'static>(name: &str) -> StreamOutput {
+        Self::with_type(name, mem::size_of::<T>(), ItemType::of::<T>())
+    }
+
+    fn with_type(name: &str, item_size: usize, item_type: ItemType) -> StreamOutput {
         StreamOutput {
             name: name.to_string(),
             item_size,
+            item_type,
             writer: None,
             tags: Vec::new(),
             offset: 0,
+            items: 0,
         }
     }
 
@@ -155,6 +373,10 @@ impl StreamOutput {
         self.item_size
     }
 
+    pub fn item_type(&self) -> ItemType {
+        self.item_type
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -179,7 +401,7 @@ impl StreamOutput {
         &mut self,
         reader_inbox: Sender<BlockMessage>,
         reader_port: usize,
-    ) -> BufferReader {
+    ) -> Result<BufferReader> {
         debug_assert!(self.writer.is_some());
         self.writer
             .as_mut()
@@ -187,6 +409,21 @@ impl StreamOutput {
             .add_reader(reader_inbox, reader_port)
     }
 
+    pub fn remove_reader(
+        &mut self,
+        reader_inbox: &Sender<BlockMessage>,
+        reader_port: usize,
+    ) -> Result<()> {
+        self.writer
+            .as_mut()
+            .context("stream output not connected")?
+            .remove_reader(reader_inbox, reader_port)
+    }
+
+    pub fn connected(&self) -> bool {
+        self.writer.is_some()
+    }
+
     pub fn try_as<T: 'static>(&mut self) -> Option<&mut T> {
         self.writer.as_mut().unwrap().try_as::<T>()
     }
@@ -195,15 +432,33 @@ impl StreamOutput {
         self.offset += amount;
     }
 
-    pub fn slice<T>(&mut self) -> &'static mut [T] {
-        let (ptr, len) = self.writer.as_mut().unwrap().bytes();
+    /// Returns the free space of the output buffer.
+    ///
+    /// `T` has to be the item type of the port or `u8` to access the raw bytes. The slice borrows
+    /// the output, i.e., it has to be dropped before items are produced.
+    pub fn slice<T: 'static>(&mut self) -> &mut [T] {
+        check_slice::<T>(&self.name, self.item_size, self.item_type);
+        unsafe { self.slice_unchecked::<T>() }
+    }
 
-        unsafe {
-            slice::from_raw_parts_mut(
-                ptr.cast::<T>().add(self.offset),
-                (len / mem::size_of::<T>()) - self.offset,
-            )
+    /// Returns the free space of the output buffer, without checking the item type or tying the
+    /// slice to the borrow of the output.
+    ///
+    /// # Safety
+    /// The slice is only valid during the current call to `work()` and must not be used after
+    /// the corresponding items were produced. `T` has to match the type of the items in the
+    /// buffer.
+    pub unsafe fn slice_unchecked<T>(&mut self) -> &'static mut [T] {
+        let (ptr, len) = self.writer.as_mut().unwrap().bytes();
+        let offset = self.offset * self.item_size / mem::size_of::<T>();
+        if ptr.is_null() {
+            return &mut [];
         }
+
+        slice::from_raw_parts_mut(
+            ptr.cast::<T>().add(offset),
+            (len / mem::size_of::<T>()) - offset,
+        )
     }
 
     fn commit(&mut self) {
@@ -217,6 +472,7 @@ impl StreamOutput {
             .as_mut()
             .unwrap()
             .produce(self.offset, std::mem::take(&mut self.tags));
+        self.items += self.offset as u64;
         self.offset = 0;
     }
 
@@ -224,8 +480,22 @@ impl StreamOutput {
         self.offset
     }
 
+    /// Total number of items produced.
+    pub fn items_produced(&self) -> u64 {
+        self.items
+    }
+
+    /// Accounts for items that the block handed to the buffer directly, without
+    /// [produce](Self::produce), e.g., buffers submitted to an
+    /// [in-place writer](crate::runtime::buffer::inplace::Writer).
+    pub fn add_produced(&mut self, amount: usize) {
+        self.items += amount as u64;
+    }
+
     pub async fn notify_finished(&mut self) {
-        self.writer.as_mut().unwrap().notify_finished().await;
+        if let Some(w) = self.writer.as_mut() {
+            w.notify_finished().await;
+        }
     }
 
     pub fn finish(&mut self) {
@@ -336,6 +606,25 @@ impl StreamIo {
             .map(|(i, _)| i)
     }
 
+    /// Borrow the inputs and outputs at the same time, e.g., to hold slices of several ports.
+    pub fn ports_mut(&mut self) -> (&mut [StreamInput], &mut [StreamOutput]) {
+        (&mut self.inputs, &mut self.outputs)
+    }
+
+    /// Slices of an input and an output port.
+    ///
+    /// See [`StreamInput::slice`] and [`StreamOutput::slice`].
+    pub fn slices<I: 'static, O: 'static>(
+        &mut self,
+        input: usize,
+        output: usize,
+    ) -> (&[I], &mut [O]) {
+        (
+            self.inputs[input].slice::<I>(),
+            self.outputs[output].slice::<O>(),
+        )
+    }
+
     pub fn commmit(&mut self) {
         (self.tag_propagation)(&mut self.inputs, &mut self.outputs);
         for i in self.inputs_mut() {
@@ -371,14 +660,32 @@ impl StreamIoBuilder {
         }
     }
 
+    /// Add an input for items of type `T`.
+    #[must_use]
+    pub fn add_input<T: 'static>(mut self, name: &str) -> StreamIoBuilder {
+        self.inputs.push(StreamInput::typed::<T>(name));
+        self
+    }
+
+    /// Add an output for items of type `T`.
     #[must_use]
-    pub fn add_input(mut self, name: &str, item_size: usize) -> StreamIoBuilder {
+    pub fn add_output<T: 'static>(mut self, name: &str) -> StreamIoBuilder {
+        self.outputs.push(StreamOutput::typed::<T>(name));
+        self
+    }
+
+    /// Add an input that only defines the size of its items. It can be connected to any output
+    /// with the same item size.
+    #[must_use]
+    pub fn add_raw_input(mut self, name: &str, item_size: usize) -> StreamIoBuilder {
         self.inputs.push(StreamInput::new(name, item_size));
         self
     }
 
+    /// Add an output that only defines the size of its items. It can be connected to any input
+    /// with the same item size.
     #[must_use]
-    pub fn add_output(mut self, name: &str, item_size: usize) -> StreamIoBuilder {
+    pub fn add_raw_output(mut self, name: &str, item_size: usize) -> StreamIoBuilder {
         self.outputs.push(StreamOutput::new(name, item_size));
         self
     }
@@ -392,6 +699,14 @@ impl StreamIoBuilder {
         self
     }
 
+    /// Forward tags according to a standard policy.
+    #[must_use]
+    pub fn tag_propagation_policy(self, policy: TagPropagation) -> StreamIoBuilder {
+        self.tag_propagation(move |i: &mut [StreamInput], o: &mut [StreamOutput]| {
+            policy.propagate(i, o)
+        })
+    }
+
     pub fn build(self) -> StreamIo {
         StreamIo::new(self.inputs, self.outputs, self.tag_propagation)
     }
@@ -417,4 +732,34 @@ mod tests {
         assert_eq!(o.name(), "foo");
         assert_eq!(o.item_size(), 4);
     }
+
+    #[test]
+    fn item_type() {
+        let i = StreamInput::typed::<u32>("in");
+        assert_eq!(i.item_size(), 4);
+        assert_eq!(i.item_type().describe(4), "u32");
+        assert!(i.item_type().is_compatible(&ItemType::of::<u32>()));
+        assert!(!i.item_type().is_compatible(&ItemType::of::<f32>()));
+        assert!(i.item_type().is_compatible(&ItemType::Raw));
+        assert_eq!(ItemType::Raw.describe(8), "[u8; 8]");
+    }
+
+    #[test]
+    fn slice_types() {
+        check_slice::<u32>("in", 4, ItemType::of::<u32>());
+        check_slice::<u8>("in", 4, ItemType::of::<u32>());
+        check_slice::<u16>("in", 4, ItemType::Raw);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot access it as f32")]
+    fn slice_wrong_type() {
+        check_slice::<f32>("in", 4, ItemType::of::<u32>());
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot access it as ()")]
+    fn slice_zero_sized() {
+        check_slice::<()>("in", 4, ItemType::Raw);
+    }
 }
@@ -1,6 +1,8 @@
 use dyn_clone::DynClone;
+use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::fmt;
+use std::time::Duration;
 
 use crate::runtime::Pmt;
 use crate::runtime::StreamInput;
@@ -37,14 +39,29 @@ impl fmt::Debug for Box<dyn TagAny> {
 }
 
 #[non_exhaustive]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Tag {
     Id(u64),
     String(String),
     Data(Pmt),
     NamedUsize(String, usize),
     NamedF32(String, f32),
+    #[serde(skip)]
     NamedAny(String, Box<dyn TagAny>),
+    /// Time of the tagged sample, e.g., the hardware time of an SDR or the time since the UNIX
+    /// epoch.
+    RxTime(Duration),
+    /// Center frequency (in Hz) of the stream, starting with the tagged sample.
+    RxFreq(f64),
+    /// Sample rate of the stream, starting with the tagged sample.
+    RxRate(f64),
+    /// First sample of a burst.
+    TxSob,
+    /// Last sample of a burst.
+    TxEob,
+    /// Number of items that were dropped before the tagged item, e.g., by a lossy reader that
+    /// fell behind.
+    Dropped(usize),
 }
 
 #[derive(Clone, Debug)]
@@ -54,3 +71,172 @@ pub struct ItemTag {
 }
 
 pub fn default_tag_propagation(_inputs: &mut [StreamInput], _outputs: &mut [StreamOutput]) {}
+
+/// Policy to forward tags from the inputs of a block to its outputs.
+///
+/// Tags are forwarded once the tagged item is consumed. Policies are set with
+/// [`StreamIoBuilder::tag_propagation_policy`](crate::runtime::StreamIoBuilder::tag_propagation_policy)
+/// or [`Block::set_tag_propagation_policy`](crate::runtime::Block::set_tag_propagation_policy).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TagPropagation {
+    /// Drop all tags.
+    None,
+    /// Forward the tags of all inputs to all outputs.
+    AllToAll,
+    /// Forward the tags of input `i` to output `i`.
+    OneToOne,
+    /// Forward the tags of all inputs to all outputs of a block that produces `interp` output
+    /// items for `decim` input items, adjusting the index of the tags accordingly.
+    Rate { interp: usize, decim: usize },
+}
+
+impl TagPropagation {
+    pub fn propagate(&self, inputs: &mut [StreamInput], outputs: &mut [StreamOutput]) {
+        match *self {
+            TagPropagation::None => {}
+            TagPropagation::AllToAll => {
+                for input in inputs.iter() {
+                    forward(input, outputs.iter_mut(), 1, 1);
+                }
+            }
+            TagPropagation::OneToOne => {
+                for (input, output) in inputs.iter().zip(outputs.iter_mut()) {
+                    forward(input, std::iter::once(output), 1, 1);
+                }
+            }
+            TagPropagation::Rate { interp, decim } => {
+                for input in inputs.iter() {
+                    forward(input, outputs.iter_mut(), interp, decim);
+                }
+            }
+        }
+    }
+}
+
+fn forward<'a>(
+    input: &StreamInput,
+    outputs: impl Iterator<Item = &'a mut StreamOutput>,
+    interp: usize,
+    decim: usize,
+) {
+    let (n, tags) = input.consumed();
+    if n == 0 {
+        return;
+    }
+    let tags: Vec<&ItemTag> = tags.iter().filter(|x| x.index < n).collect();
+    for output in outputs {
+        for t in tags.iter() {
+            output.add_tag_abs(t.index * interp / decim, t.tag.clone());
+        }
+    }
+}
+
+/// Convert between absolute sample indices and time, based on [`Tag::RxTime`] and
+/// [`Tag::RxRate`] tags of a stream.
+#[derive(Clone, Debug, Default)]
+pub struct SampleClock {
+    reference: Option<(u64, Duration)>,
+    rate: Option<f64>,
+}
+
+impl SampleClock {
+    pub fn new() -> SampleClock {
+        SampleClock::default()
+    }
+
+    /// Update the clock with a tag of the sample with the absolute index `index`.
+    ///
+    /// Sample rates that are not positive and finite make the time of the following samples
+    /// unknown.
+    pub fn update(&mut self, index: u64, tag: &Tag) {
+        match tag {
+            Tag::RxTime(t) => self.reference = Some((index, *t)),
+            Tag::RxRate(r) => {
+                // keep the time of the sample, where the rate changes
+                if let Some(t) = self.time(index) {
+                    self.reference = Some((index, t));
+                }
+                self.rate = if r.is_finite() && *r > 0.0 {
+                    Some(*r)
+                } else {
+                    None
+                };
+            }
+            _ => {}
+        }
+    }
+
+    /// Current sample rate.
+    pub fn rate(&self) -> Option<f64> {
+        self.rate
+    }
+
+    /// Time of the sample with the absolute index `index`, if it can be represented.
+    pub fn time(&self, index: u64) -> Option<Duration> {
+        let (i, t) = self.reference?;
+        let rate = self.rate?;
+        if index >= i {
+            t.checked_add(samples_duration(index - i, rate)?)
+        } else {
+            t.checked_sub(samples_duration(i - index, rate)?)
+        }
+    }
+
+    /// Absolute index of the sample at `time`.
+    pub fn index(&self, time: Duration) -> Option<u64> {
+        let (i, t) = self.reference?;
+        let rate = self.rate?;
+        if time >= t {
+            i.checked_add(((time - t).as_secs_f64() * rate).round() as u64)
+        } else {
+            i.checked_sub(((t - time).as_secs_f64() * rate).round() as u64)
+        }
+    }
+}
+
+/// Duration of `n` samples at the sample rate `rate`, if it can be represented.
+fn samples_duration(n: u64, rate: f64) -> Option<Duration> {
+    // Duration::try_from_secs_f64 is not available with our MSRV
+    let secs = n as f64 / rate;
+    if secs.is_finite() && secs >= 0.0 && secs < u64::MAX as f64 {
+        Some(Duration::from_secs_f64(secs))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_clock() {
+        let mut c = SampleClock::new();
+        assert_eq!(c.time(0), None);
+
+        c.update(100, &Tag::RxTime(Duration::from_secs(10)));
+        c.update(100, &Tag::RxRate(1000.0));
+        assert_eq!(c.time(100), Some(Duration::from_secs(10)));
+        assert_eq!(c.time(1100), Some(Duration::from_secs(11)));
+        assert_eq!(c.time(0), Some(Duration::from_millis(9900)));
+        assert_eq!(c.index(Duration::from_secs(12)), Some(2100));
+        assert_eq!(c.index(Duration::from_secs(1)), None);
+
+        c.update(1100, &Tag::RxRate(2000.0));
+        assert_eq!(c.time(3100), Some(Duration::from_secs(12)));
+        assert_eq!(c.index(Duration::from_secs(11)), Some(1100));
+        assert_eq!(c.index(Duration::MAX), None);
+
+        c.update(3100, &Tag::RxRate(1e-30));
+        assert_eq!(c.time(3100), Some(Duration::from_secs(12)));
+        assert_eq!(c.time(u64::MAX), None);
+
+        for r in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            c.update(3100, &Tag::RxTime(Duration::from_secs(12)));
+            c.update(3100, &Tag::RxRate(r));
+            assert_eq!(c.rate(), None);
+            assert_eq!(c.time(4100), None);
+            assert_eq!(c.index(Duration::from_secs(13)), None);
+        }
+    }
+}
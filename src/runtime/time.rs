@@ -0,0 +1,165 @@
+//! Time of the runtime.
+//!
+//! Blocks should use these functions instead of [`std::time`] and [`async_io::Timer`]. Usually,
+//! they follow the wall-clock time. Under the
+//! [DeterministicScheduler](crate::runtime::scheduler::DeterministicScheduler), they follow a
+//! virtual clock that advances when all blocks are idle.
+use async_io::Timer;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+thread_local! {
+    static CLOCK: RefCell<Option<VirtualClock>> = const { RefCell::new(None) };
+}
+
+fn virtual_clock() -> Option<VirtualClock> {
+    CLOCK.with(|c| c.borrow().clone())
+}
+
+/// Current time.
+pub fn now() -> Instant {
+    match virtual_clock() {
+        Some(c) => c.now(),
+        None => Instant::now(),
+    }
+}
+
+/// Current system time. A virtual clock starts at the UNIX epoch.
+pub fn system_time() -> SystemTime {
+    match virtual_clock() {
+        Some(c) => UNIX_EPOCH + c.elapsed(),
+        None => SystemTime::now(),
+    }
+}
+
+/// Wait for the given duration.
+pub async fn sleep(duration: Duration) {
+    sleep_until(now() + duration).await;
+}
+
+/// Wait until the given deadline.
+pub async fn sleep_until(deadline: Instant) {
+    match virtual_clock() {
+        Some(clock) => {
+            VirtualSleep {
+                clock,
+                deadline,
+                id: None,
+            }
+            .await
+        }
+        None => {
+            Timer::at(deadline).await;
+        }
+    }
+}
+
+/// Virtual clock of a scheduler.
+#[derive(Clone, Debug)]
+pub(crate) struct VirtualClock {
+    inner: Arc<Mutex<ClockInner>>,
+}
+
+#[derive(Debug)]
+struct ClockInner {
+    start: Instant,
+    elapsed: Duration,
+    timers: BTreeMap<(Instant, u64), Waker>,
+    next_id: u64,
+}
+
+impl VirtualClock {
+    pub(crate) fn new() -> VirtualClock {
+        VirtualClock {
+            inner: Arc::new(Mutex::new(ClockInner {
+                start: Instant::now(),
+                elapsed: Duration::ZERO,
+                timers: BTreeMap::new(),
+                next_id: 0,
+            })),
+        }
+    }
+
+    pub(crate) fn now(&self) -> Instant {
+        let inner = self.inner.lock().unwrap();
+        inner.start + inner.elapsed
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.inner.lock().unwrap().elapsed
+    }
+
+    /// Use the clock on the current thread.
+    pub(crate) fn enter(&self) {
+        CLOCK.with(|c| *c.borrow_mut() = Some(self.clone()));
+    }
+
+    /// Advance to the next timer and wake all timers that are due. Returns `false` if no timer
+    /// is pending.
+    pub(crate) fn advance(&self) -> bool {
+        let due = {
+            let mut inner = self.inner.lock().unwrap();
+            let next = match inner.timers.keys().next() {
+                Some((t, _)) => *t,
+                None => return false,
+            };
+            let now = inner.start + inner.elapsed;
+            if next > now {
+                inner.elapsed = next - inner.start;
+            }
+            let later = inner.timers.split_off(&(next.max(now), u64::MAX));
+            std::mem::replace(&mut inner.timers, later)
+        };
+        for (_, waker) in due {
+            waker.wake();
+        }
+        true
+    }
+}
+
+struct VirtualSleep {
+    clock: VirtualClock,
+    deadline: Instant,
+    id: Option<u64>,
+}
+
+impl Future for VirtualSleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let deadline = self.deadline;
+        let mut inner = self.clock.inner.lock().unwrap();
+        if let Some(id) = self.id {
+            inner.timers.remove(&(deadline, id));
+        }
+        if inner.start + inner.elapsed >= deadline {
+            drop(inner);
+            self.id = None;
+            return Poll::Ready(());
+        }
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.timers.insert((deadline, id), cx.waker().clone());
+        drop(inner);
+        self.id = Some(id);
+        Poll::Pending
+    }
+}
+
+impl Drop for VirtualSleep {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.clock
+                .inner
+                .lock()
+                .unwrap()
+                .timers
+                .remove(&(self.deadline, id));
+        }
+    }
+}
@@ -1,9 +1,13 @@
 use futures::channel::mpsc::Sender;
 use std::collections::HashMap;
+use std::collections::HashSet;
 
 use crate::anyhow::{bail, Context, Result};
 use crate::runtime::buffer::BufferBuilder;
 use crate::runtime::buffer::BufferWriter;
+use crate::runtime::ctrl_port::BufferDescription;
+use crate::runtime::flowgraph::DefaultBuffer;
+use crate::runtime::stream_io::check_items;
 use crate::runtime::Block;
 use crate::runtime::BlockMessage;
 use slab::Slab;
@@ -49,14 +53,34 @@ pub struct BufferBuilderEntry {
 }
 
 impl BufferBuilderEntry {
+    pub(crate) fn new(item_size: usize, builder: Box<dyn BufferBuilderKey>) -> BufferBuilderEntry {
+        BufferBuilderEntry { item_size, builder }
+    }
+
     pub(crate) fn build(
         &self,
+        min_items: usize,
         writer_inbox: Sender<BlockMessage>,
         writer_output_id: usize,
-    ) -> BufferWriter {
-        self.builder
-            .builder()
-            .build(self.item_size, writer_inbox, writer_output_id)
+    ) -> Result<BufferWriter> {
+        self.builder.builder().build_with_min_items(
+            self.item_size,
+            min_items,
+            writer_inbox,
+            writer_output_id,
+        )
+    }
+
+    pub(crate) fn sole_reader(&self) -> bool {
+        self.builder.builder().sole_reader()
+    }
+
+    pub(crate) fn lossy_readers(&self) -> bool {
+        self.builder.builder().lossy_readers()
+    }
+
+    pub(crate) fn description(&self) -> BufferDescription {
+        self.builder.builder().description(self.item_size)
     }
 }
 
@@ -75,13 +99,159 @@ impl Hash for BufferBuilderEntry {
     }
 }
 
+/// Port type of a [HierBlock].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Port {
+    StreamInput,
+    StreamOutput,
+    MessageInput,
+    MessageOutput,
+}
+
+/// Stream edge from or to a [HierBlock], identified by port names.
+type HierStreamEdge = (
+    usize,
+    String,
+    usize,
+    String,
+    Box<dyn BufferBuilderKey>,
+    bool,
+);
+
+/// Exported ports of a flattened [HierBlock], mapped to the ids of the inner blocks.
+type Ports = HashMap<(Port, String), (usize, String)>;
+
+/// Group of blocks that is used like a single block.
+///
+/// A [HierBlock] has its own [Topology] and exports ports of its inner blocks under external
+/// names. It is added to a [Flowgraph](crate::runtime::Flowgraph) or to another [HierBlock]
+/// like a block and flattened, i.e., replaced by its inner blocks, when the flowgraph starts.
+/// Inner blocks are then named `<hier block>::<inner block>`.
+#[derive(Debug)]
+pub struct HierBlock {
+    name: String,
+    topology: Topology,
+    ports: Ports,
+}
+
+impl HierBlock {
+    pub fn new(name: impl Into<String>) -> HierBlock {
+        HierBlock {
+            name: name.into(),
+            topology: Topology::new(),
+            ports: HashMap::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn topology(&self) -> &Topology {
+        &self.topology
+    }
+
+    pub fn topology_mut(&mut self) -> &mut Topology {
+        &mut self.topology
+    }
+
+    pub fn add_block(&mut self, block: Block) -> usize {
+        self.topology.add_block(block)
+    }
+
+    pub fn add_hier_block(&mut self, block: HierBlock) -> usize {
+        self.topology.add_hier_block(block)
+    }
+
+    pub fn connect_stream(
+        &mut self,
+        src_block: usize,
+        src_port: &str,
+        dst_block: usize,
+        dst_port: &str,
+    ) -> Result<()> {
+        self.topology.connect_stream(
+            src_block,
+            src_port,
+            dst_block,
+            dst_port,
+            DefaultBuffer::new(),
+        )
+    }
+
+    pub fn connect_stream_with_type<B: BufferBuilder + Debug + Eq + Hash>(
+        &mut self,
+        src_block: usize,
+        src_port: &str,
+        dst_block: usize,
+        dst_port: &str,
+        buffer: B,
+    ) -> Result<()> {
+        self.topology
+            .connect_stream(src_block, src_port, dst_block, dst_port, buffer)
+    }
+
+    pub fn connect_message(
+        &mut self,
+        src_block: usize,
+        src_port: &str,
+        dst_block: usize,
+        dst_port: &str,
+    ) -> Result<()> {
+        self.topology
+            .connect_message(src_block, src_port, dst_block, dst_port)
+    }
+
+    /// Export the stream input `port` of inner block `block` as `name`.
+    pub fn stream_input(&mut self, name: &str, block: usize, port: &str) -> Result<()> {
+        self.export(Port::StreamInput, name, block, port)
+    }
+
+    /// Export the stream output `port` of inner block `block` as `name`.
+    pub fn stream_output(&mut self, name: &str, block: usize, port: &str) -> Result<()> {
+        self.export(Port::StreamOutput, name, block, port)
+    }
+
+    /// Export the message input `port` of inner block `block` as `name`.
+    pub fn message_input(&mut self, name: &str, block: usize, port: &str) -> Result<()> {
+        self.export(Port::MessageInput, name, block, port)
+    }
+
+    /// Export the message output `port` of inner block `block` as `name`.
+    pub fn message_output(&mut self, name: &str, block: usize, port: &str) -> Result<()> {
+        self.export(Port::MessageOutput, name, block, port)
+    }
+
+    fn export(&mut self, t: Port, name: &str, block: usize, port: &str) -> Result<()> {
+        if !self.topology.has_port(block, t, port) {
+            bail!("inner block {} has no {:?} port {}", block, t, port);
+        }
+        if self.ports.contains_key(&(t, name.to_string())) {
+            bail!("{:?} port {} already exported", t, name);
+        }
+        self.ports
+            .insert((t, name.to_string()), (block, port.to_string()));
+        Ok(())
+    }
+
+    fn has_port(&self, t: Port, name: &str) -> bool {
+        self.ports.contains_key(&(t, name.to_string()))
+    }
+}
+
 /// The actual graph that backs a [Flowgraph](crate::runtime::Flowgraph).
 #[derive(Debug)]
 pub struct Topology {
     pub(crate) blocks: Slab<Option<Block>>,
     pub(crate) stream_edges: HashMap<(usize, usize, BufferBuilderEntry), Vec<(usize, usize)>>,
+    // dst blk, dst port of stream edges with lossy readers
+    pub(crate) lossy_edges: HashSet<(usize, usize)>,
     // src blk, src port, dst blk, dst port
     pub(crate) message_edges: Vec<(usize, usize, usize, usize)>,
+    // hier blocks reserve their id in `blocks` until the topology is flattened
+    hier_blocks: HashMap<usize, HierBlock>,
+    hier_stream_edges: Vec<HierStreamEdge>,
+    hier_message_edges: Vec<(usize, String, usize, String)>,
 }
 
 impl Topology {
@@ -90,18 +260,27 @@ impl Topology {
         Topology {
             blocks: Slab::new(),
             stream_edges: HashMap::new(),
+            lossy_edges: HashSet::new(),
             message_edges: Vec::new(),
+            hier_blocks: HashMap::new(),
+            hier_stream_edges: Vec::new(),
+            hier_message_edges: Vec::new(),
         }
     }
 
     pub fn block_id(&self, name: &str) -> Option<usize> {
         for (i, b) in self.blocks.iter() {
-            if b.as_ref()?.instance_name()? == name {
-                return Some(i);
+            if let Some(b) = b {
+                if b.instance_name() == Some(name) {
+                    return Some(i);
+                }
             }
         }
 
-        None
+        self.hier_blocks
+            .iter()
+            .find(|(_, h)| h.name == name)
+            .map(|(i, _)| *i)
     }
 
     pub fn block_name(&self, id: usize) -> Option<&str> {
@@ -137,10 +316,107 @@ impl Topology {
         self.blocks.insert(Some(block))
     }
 
+    /// Adds a [HierBlock] to the [Topology] returning its `id`.
+    ///
+    /// The id can be used to connect the exported ports of the [HierBlock] until the
+    /// [Topology] is flattened.
+    pub fn add_hier_block(&mut self, mut block: HierBlock) -> usize {
+        let base_name = block.name.clone();
+        let mut i = 0;
+        while self.block_id(&block.name).is_some() {
+            block.name = format!("{}_{}", base_name, i);
+            i += 1;
+        }
+
+        let id = self.blocks.insert(None);
+        self.hier_blocks.insert(id, block);
+        id
+    }
+
+    fn has_port(&self, block: usize, t: Port, name: &str) -> bool {
+        if let Some(h) = self.hier_blocks.get(&block) {
+            return h.has_port(t, name);
+        }
+        self.block_ref(block).map_or(false, |b| match t {
+            Port::StreamInput => b.stream_input_name_to_id(name).is_some(),
+            Port::StreamOutput => b.stream_output_name_to_id(name).is_some(),
+            Port::MessageInput => b.message_input_name_to_id(name).is_some(),
+            Port::MessageOutput => b.message_output_name_to_id(name).is_some(),
+        })
+    }
+
+    /// Replaces all [HierBlock]s, recursively, by their inner blocks and edges.
+    pub(crate) fn flatten(&mut self) -> Result<()> {
+        self.flatten_ports().map(|_| ())
+    }
+
+    /// Flattens the topology, returning the exported ports of the removed [HierBlock]s.
+    fn flatten_ports(&mut self) -> Result<HashMap<usize, Ports>> {
+        let mut flattened = HashMap::new();
+        let ids: Vec<usize> = self.hier_blocks.keys().copied().collect();
+
+        for id in ids {
+            let mut h = self.hier_blocks.remove(&id).unwrap();
+            self.blocks.remove(id);
+            let inner = h
+                .topology
+                .flatten_ports()
+                .with_context(|| format!("cannot flatten {}", h.name))?;
+
+            let mut ids = HashMap::new();
+            let keys: Vec<usize> = h.topology.blocks.iter().map(|(i, _)| i).collect();
+            for i in keys {
+                let mut b = h.topology.blocks.remove(i).context("block not present")?;
+                let name = format!("{}::{}", h.name, b.instance_name().unwrap_or("block"));
+                b.set_instance_name(name);
+                ids.insert(i, self.add_block(b));
+            }
+
+            for ((src, src_port, entry), v) in h.topology.stream_edges.drain() {
+                self.stream_edges.insert(
+                    (ids[&src], src_port, entry),
+                    v.into_iter().map(|(d, p)| (ids[&d], p)).collect(),
+                );
+            }
+            for (dst, dst_port) in h.topology.lossy_edges.drain() {
+                self.lossy_edges.insert((ids[&dst], dst_port));
+            }
+            for (src, src_port, dst, dst_port) in h.topology.message_edges.drain(..) {
+                self.message_edges
+                    .push((ids[&src], src_port, ids[&dst], dst_port));
+            }
+
+            let mut ports = HashMap::new();
+            for ((t, name), (blk, port)) in h.ports.drain() {
+                let (blk, port) = resolve(&inner, blk, t, &port)?;
+                ports.insert((t, name), (ids[&blk], port));
+            }
+            flattened.insert(id, ports);
+        }
+
+        for (src, src_port, dst, dst_port, builder, lossy) in
+            std::mem::take(&mut self.hier_stream_edges)
+        {
+            let (src, src_port) = resolve(&flattened, src, Port::StreamOutput, &src_port)?;
+            let (dst, dst_port) = resolve(&flattened, dst, Port::StreamInput, &dst_port)?;
+            self.connect_stream_boxed(src, &src_port, dst, &dst_port, builder, lossy)?;
+        }
+        for (src, src_port, dst, dst_port) in std::mem::take(&mut self.hier_message_edges) {
+            let (src, src_port) = resolve(&flattened, src, Port::MessageOutput, &src_port)?;
+            let (dst, dst_port) = resolve(&flattened, dst, Port::MessageInput, &dst_port)?;
+            self.connect_message(src, &src_port, dst, &dst_port)?;
+        }
+
+        Ok(flattened)
+    }
+
     /// Removes a [Block] and all edges connected to the [Block] from the [Topology].
     pub fn delete_block(&mut self, id: usize) {
         // remove from registry
         self.blocks.remove(id);
+        self.hier_blocks.remove(&id);
+        self.hier_stream_edges.retain(|x| x.0 != id && x.2 != id);
+        self.hier_message_edges.retain(|x| x.0 != id && x.2 != id);
 
         // delete associated stream edges
         self.stream_edges.retain(|k, _| k.0 != id);
@@ -152,6 +428,7 @@ impl Topology {
             .drain()
             .filter(|(_, v)| !v.is_empty())
             .collect();
+        self.lossy_edges.retain(|x| x.0 != id);
 
         // delete associated message edges
         self.message_edges.retain(|x| x.0 != id && x.2 != id);
@@ -165,6 +442,62 @@ impl Topology {
         dst_port: &str,
         buffer_builder: B,
     ) -> Result<()> {
+        self.connect_stream_boxed(
+            src_block,
+            src_port,
+            dst_block,
+            dst_port,
+            Box::new(buffer_builder),
+            false,
+        )
+    }
+
+    /// Connects a stream input as lossy reader, which never blocks the writer.
+    pub fn connect_stream_lossy<B: BufferBuilder + Debug + Eq + Hash>(
+        &mut self,
+        src_block: usize,
+        src_port: &str,
+        dst_block: usize,
+        dst_port: &str,
+        buffer_builder: B,
+    ) -> Result<()> {
+        self.connect_stream_boxed(
+            src_block,
+            src_port,
+            dst_block,
+            dst_port,
+            Box::new(buffer_builder),
+            true,
+        )
+    }
+
+    fn connect_stream_boxed(
+        &mut self,
+        src_block: usize,
+        src_port: &str,
+        dst_block: usize,
+        dst_port: &str,
+        buffer_builder: Box<dyn BufferBuilderKey>,
+        lossy: bool,
+    ) -> Result<()> {
+        if self.hier_blocks.contains_key(&src_block) || self.hier_blocks.contains_key(&dst_block) {
+            if !self.has_port(src_block, Port::StreamOutput, src_port) {
+                bail!("invalid src port name");
+            }
+            if !self.has_port(dst_block, Port::StreamInput, dst_port) {
+                bail!("invalid dst port name");
+            }
+            self.hier_stream_edges.push((
+                src_block,
+                src_port.to_string(),
+                dst_block,
+                dst_port.to_string(),
+                buffer_builder,
+                lossy,
+            ));
+            return Ok(());
+        }
+
         let src = self
             .blocks
             .get(src_block)
@@ -195,23 +528,132 @@ impl Topology {
             .stream_input_name_to_id(dst_port)
             .context("invalid dst port name")?;
 
-        if sp.item_size() != dp.item_size() {
-            bail!("item sizes do not match");
+        check_items(
+            sp.item_size(),
+            sp.item_type(),
+            dp.item_size(),
+            dp.item_type(),
+        )
+        .with_context(|| {
+            format!(
+                "cannot connect {}.{} to {}.{}",
+                src.instance_name().unwrap_or("?"),
+                src_port,
+                dst.instance_name().unwrap_or("?"),
+                dst_port
+            )
+        })?;
+
+        if lossy && !buffer_builder.builder().lossy_readers() {
+            bail!("buffer does not support lossy readers");
         }
 
-        let buffer_entry = BufferBuilderEntry {
-            item_size: sp.item_size(),
-            builder: Box::new(buffer_builder),
-        };
-        let id = (src_block, src_port_id, buffer_entry);
-        if let Some(v) = self.stream_edges.get_mut(&id) {
-            v.push((dst_block, dst_port_id));
+        self.add_stream_edge(
+            src_block,
+            src_port_id,
+            dst_block,
+            dst_port_id,
+            BufferBuilderEntry::new(sp.item_size(), buffer_builder),
+        )?;
+        if lossy {
+            self.lossy_edges.insert((dst_block, dst_port_id));
+        }
+        Ok(())
+    }
+
+    /// Adds a stream edge between ports, identified by their ids.
+    ///
+    /// Returns `true`, if the output was not connected before, i.e., a new buffer has to be created.
+    pub(crate) fn add_stream_edge(
+        &mut self,
+        src_block: usize,
+        src_port: usize,
+        dst_block: usize,
+        dst_port: usize,
+        buffer_entry: BufferBuilderEntry,
+    ) -> Result<bool> {
+        if self
+            .stream_edges
+            .values()
+            .any(|v| v.contains(&(dst_block, dst_port)))
+        {
+            bail!("dst port already connected");
+        }
+
+        if let Some(k) = self
+            .stream_edges
+            .keys()
+            .find(|k| k.0 == src_block && k.1 == src_port)
+        {
+            if k.2 != buffer_entry {
+                bail!("src port already connected with a different buffer type");
+            }
+            if k.2.sole_reader() && !self.stream_edges[k].is_empty() {
+                bail!("buffer of src port supports only one reader");
+            }
+            let k = (src_block, src_port, buffer_entry);
+            self.stream_edges
+                .get_mut(&k)
+                .unwrap()
+                .push((dst_block, dst_port));
+            Ok(false)
         } else {
-            self.stream_edges.insert(id, vec![(dst_block, dst_port_id)]);
+            self.stream_edges.insert(
+                (src_block, src_port, buffer_entry),
+                vec![(dst_block, dst_port)],
+            );
+            Ok(true)
         }
+    }
+
+    /// Removes a stream edge between ports, identified by their ids.
+    ///
+    /// The buffer of the output is kept, even if it has no readers left.
+    pub(crate) fn remove_stream_edge(
+        &mut self,
+        src_block: usize,
+        src_port: usize,
+        dst_block: usize,
+        dst_port: usize,
+    ) -> Result<()> {
+        let v = self
+            .stream_edges
+            .iter_mut()
+            .find(|(k, _)| k.0 == src_block && k.1 == src_port)
+            .map(|(_, v)| v)
+            .context("src port not connected")?;
+        let len = v.len();
+        v.retain(|x| *x != (dst_block, dst_port));
+        if v.len() == len {
+            bail!("stream ports not connected");
+        }
+        self.lossy_edges.remove(&(dst_block, dst_port));
         Ok(())
     }
 
+    pub(crate) fn has_stream_edge(
+        &self,
+        src_block: usize,
+        src_port: usize,
+        dst_block: usize,
+        dst_port: usize,
+    ) -> bool {
+        self.stream_edges
+            .iter()
+            .any(|(k, v)| k.0 == src_block && k.1 == src_port && v.contains(&(dst_block, dst_port)))
+    }
+
+    pub(crate) fn stream_buffer(
+        &self,
+        src_block: usize,
+        src_port: usize,
+    ) -> Option<&BufferBuilderEntry> {
+        self.stream_edges
+            .keys()
+            .find(|k| k.0 == src_block && k.1 == src_port)
+            .map(|k| &k.2)
+    }
+
     pub fn connect_message(
         &mut self,
         src_block: usize,
@@ -219,6 +661,22 @@ impl Topology {
         dst_block: usize,
         dst_port: &str,
     ) -> Result<()> {
+        if self.hier_blocks.contains_key(&src_block) || self.hier_blocks.contains_key(&dst_block) {
+            if !self.has_port(src_block, Port::MessageOutput, src_port) {
+                bail!("invalid src port name");
+            }
+            if !self.has_port(dst_block, Port::MessageInput, dst_port) {
+                bail!("invalid dst port name");
+            }
+            self.hier_message_edges.push((
+                src_block,
+                src_port.to_string(),
+                dst_block,
+                dst_port.to_string(),
+            ));
+            return Ok(());
+        }
+
         let src = self
             .blocks
             .get(src_block)
@@ -280,16 +738,34 @@ impl Topology {
         }
 
         // check if all stream edges are valid
-        for ((src, src_port, _), v) in self.stream_edges.iter() {
+        for ((src, src_port, buffer), v) in self.stream_edges.iter() {
             let src_block = self.block_ref(*src).expect("src block not found");
             let output = src_block.stream_output(*src_port);
 
+            if buffer.sole_reader() && v.len() > 1 {
+                bail!(
+                    "stream output {} of {} supports only one reader",
+                    output.name(),
+                    src_block.instance_name().unwrap_or("block")
+                );
+            }
+
             for (dst, dst_port) in v.iter() {
                 let dst_block = self.block_ref(*dst).expect("dst block not found");
                 let input = dst_block.stream_input(*dst_port);
-                if output.item_size() != input.item_size() {
-                    bail!("item size of stream connection does not match");
+                if self.lossy_edges.contains(&(*dst, *dst_port)) && !buffer.lossy_readers() {
+                    bail!(
+                        "stream output {} of {} does not support lossy readers",
+                        output.name(),
+                        src_block.instance_name().unwrap_or("block")
+                    );
                 }
+                check_items(
+                    output.item_size(),
+                    output.item_type(),
+                    input.item_size(),
+                    input.item_type(),
+                )?;
             }
         }
 
@@ -321,6 +797,22 @@ impl Topology {
     }
 }
 
+/// Map a port of a block or a flattened [HierBlock] to the port of a block.
+fn resolve(
+    flattened: &HashMap<usize, Ports>,
+    block: usize,
+    t: Port,
+    port: &str,
+) -> Result<(usize, String)> {
+    match flattened.get(&block) {
+        Some(ports) => ports
+            .get(&(t, port.to_string()))
+            .cloned()
+            .with_context(|| format!("hier block has no {:?} port {}", t, port)),
+        None => Ok((block, port.to_string())),
+    }
+}
+
 impl Default for Topology {
     fn default() -> Self {
         Topology::new()
@@ -0,0 +1,104 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::Copy;
+use futuresdr::blocks::MessageSourceBuilder;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSource;
+use futuresdr::futures::StreamExt;
+use futuresdr::runtime::config;
+use futuresdr::runtime::ctrl_port::openmetrics;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use std::time::Duration;
+
+#[test]
+fn metrics() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let copy = fg.add_block(Copy::<f32>::new());
+    let snk = fg.add_block(NullSink::<f32>::new());
+
+    fg.connect_stream(src, "out", copy, "in")?;
+    fg.connect_stream(copy, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    let m = block_on(async move {
+        futuresdr::async_io::Timer::after(std::time::Duration::from_millis(100)).await;
+        let d = handle.description().await.unwrap();
+        handle.terminate().await.unwrap();
+        let _ = task.await;
+        openmetrics(&d)
+    });
+
+    assert!(m.contains("# TYPE futuresdr_blocks gauge\n"));
+    assert!(m.contains("futuresdr_blocks 3\n"));
+    assert!(m.contains("futuresdr_stream_edges 2\n"));
+    assert!(m.contains("# TYPE futuresdr_block_work_calls counter\n"));
+    assert!(m.contains("# UNIT futuresdr_block_work_seconds seconds\n"));
+    assert!(m.contains(
+        "futuresdr_block_items_consumed_total{id=\"1\",block=\"Copy_0\",type=\"Copy\",port=\"in\"} "
+    ));
+    assert!(m.ends_with("# EOF\n"));
+
+    for l in m.lines().filter(|l| !l.starts_with('#')) {
+        let v = l.rsplit(' ').next().unwrap();
+        assert!(v.parse::<f64>().is_ok(), "invalid sample {}", l);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn subscribe() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(
+        MessageSourceBuilder::new(Pmt::U32(123), Duration::from_millis(10))
+            .n_messages(20)
+            .build(),
+    );
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    let messages: Vec<Pmt> = block_on(async move {
+        assert!(handle.subscribe_message(src, "foo").await.is_err());
+        let messages = handle.subscribe_message(src, "out").await.unwrap();
+        let messages = messages.collect().await;
+        task.await.unwrap();
+        messages
+    });
+
+    assert!(!messages.is_empty());
+    assert!(messages.len() <= 20);
+    assert!(messages.iter().all(|m| matches!(m, Pmt::U32(123))));
+
+    Ok(())
+}
+
+#[test]
+fn slow_subscriber() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n_messages = 10 * config::config().queue_size;
+    let src = fg.add_block(
+        MessageSourceBuilder::new(Pmt::U32(123), Duration::from_millis(0))
+            .n_messages(n_messages)
+            .build(),
+    );
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    let messages: Vec<Pmt> = block_on(async move {
+        // the subscriber does not read, while the block posts its messages
+        let messages = handle.subscribe_message(src, "out").await.unwrap();
+        task.await.unwrap();
+        messages.collect().await
+    });
+
+    assert!(messages.len() < n_messages);
+
+    Ok(())
+}
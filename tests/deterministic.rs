@@ -0,0 +1,147 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::MessageSourceBuilder;
+use futuresdr::blocks::Throttle;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::FutureExt;
+use futuresdr::runtime::scheduler::DeterministicScheduler;
+use futuresdr::runtime::time;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIoBuilder;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::UNIX_EPOCH;
+
+/// Records the messages it receives together with the (virtual) time of their arrival.
+struct Recorder {
+    log: Vec<(Pmt, Duration)>,
+}
+
+impl Recorder {
+    fn block() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Recorder").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "in",
+                    |block: &mut Recorder,
+                     _mio: &mut MessageIo<Recorder>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            let t = time::system_time().duration_since(UNIX_EPOCH)?;
+                            block.log.push((p, t));
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            Recorder { log: Vec::new() },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Recorder {}
+
+#[test]
+fn message_source() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(
+        MessageSourceBuilder::new(Pmt::Null, Duration::from_secs(1))
+            .n_messages(10)
+            .build(),
+    );
+    let snk = fg.add_block(MessageSink::new());
+    fg.connect_message(src, "out", snk, "in")?;
+
+    let scheduler = DeterministicScheduler::new();
+    let start = Instant::now();
+    fg = Runtime::with_scheduler(scheduler.clone()).run(fg)?;
+
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(scheduler.elapsed(), Duration::from_secs(10));
+    assert_eq!(fg.kernel::<MessageSink>(snk).unwrap().received(), 10);
+
+    Ok(())
+}
+
+#[test]
+fn throttle() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<f32>::new(vec![1.0; 10_000]));
+    let throttle = fg.add_block(Throttle::<f32>::new(1000.0));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    fg.connect_stream(src, "out", throttle, "in")?;
+    fg.connect_stream(throttle, "out", snk, "in")?;
+
+    let scheduler = DeterministicScheduler::new();
+    let start = Instant::now();
+    fg = Runtime::with_scheduler(scheduler.clone()).run(fg)?;
+
+    assert!(start.elapsed() < Duration::from_secs(5));
+    let elapsed = scheduler.elapsed();
+    assert!(elapsed >= Duration::from_secs(10) && elapsed <= Duration::from_millis(10_100));
+    assert_eq!(
+        fg.kernel::<VectorSink<f32>>(snk).unwrap().items().len(),
+        10_000
+    );
+
+    Ok(())
+}
+
+fn interleave() -> Result<Vec<(Pmt, Duration)>> {
+    let mut fg = Flowgraph::new();
+    let a = fg.add_block(
+        MessageSourceBuilder::new(Pmt::from("a"), Duration::from_millis(300))
+            .n_messages(3)
+            .build(),
+    );
+    let b = fg.add_block(
+        MessageSourceBuilder::new(Pmt::from("b"), Duration::from_millis(400))
+            .n_messages(5)
+            .build(),
+    );
+    let rec = fg.add_block(Recorder::block());
+    fg.connect_message(a, "out", rec, "in")?;
+    fg.connect_message(b, "out", rec, "in")?;
+
+    fg = Runtime::with_scheduler(DeterministicScheduler::new()).run(fg)?;
+    Ok(fg.kernel::<Recorder>(rec).unwrap().log.clone())
+}
+
+#[test]
+fn reproducible() -> Result<()> {
+    let log = interleave()?;
+    // the recorder terminates, when the first source finishes
+    let ms = Duration::from_millis;
+    assert_eq!(
+        log,
+        vec![
+            (Pmt::from("a"), ms(300)),
+            (Pmt::from("b"), ms(400)),
+            (Pmt::from("a"), ms(600)),
+            (Pmt::from("b"), ms(800)),
+            (Pmt::from("a"), ms(900)),
+        ]
+    );
+
+    for _ in 0..5 {
+        assert_eq!(interleave()?, log);
+    }
+
+    Ok(())
+}
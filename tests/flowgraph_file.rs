@@ -0,0 +1,144 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::runtime::registry;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::path::PathBuf;
+
+fn write(name: &str, content: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("futuresdr-{}-{}", std::process::id(), name));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+fn register_vector_sink() {
+    registry::register("VectorSinkF32", |p| {
+        Ok(VectorSinkBuilder::<f32>::new()
+            .init_capacity(p.get_or("capacity", 1024)?)
+            .build())
+    });
+}
+
+fn run(path: PathBuf) -> Result<()> {
+    register_vector_sink();
+    let mut fg = Flowgraph::from_file(&path)?;
+    std::fs::remove_file(path)?;
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(2).unwrap();
+    assert_eq!(snk.items().len(), 1234);
+    Ok(())
+}
+
+#[test]
+fn toml() -> Result<()> {
+    let path = write(
+        "fg.toml",
+        r#"
+[[blocks]]
+name = "src"
+type = "NullSource"
+
+[[blocks]]
+name = "head"
+type = "Head"
+parameters = { n_items = 1234 }
+
+[[blocks]]
+name = "snk"
+type = "VectorSinkF32"
+
+[[stream_edges]]
+src = "src"
+src_port = "out"
+dst = "head"
+dst_port = "in"
+
+[[stream_edges]]
+src = "head"
+src_port = "out"
+dst = "snk"
+dst_port = "in"
+buffer = "circular"
+"#,
+    );
+    run(path)
+}
+
+#[test]
+fn yaml() -> Result<()> {
+    let path = write(
+        "fg.yaml",
+        r#"
+blocks:
+  - name: src
+    type: NullSource
+    parameters:
+      item: f32
+  - name: head
+    type: Head
+    parameters:
+      n_items: 1234
+  - name: snk
+    type: VectorSinkF32
+stream_edges:
+  - { src: src, src_port: out, dst: head, dst_port: in }
+  - { src: head, src_port: out, dst: snk, dst_port: in }
+"#,
+    );
+    run(path)
+}
+
+#[test]
+fn json() -> Result<()> {
+    let path = write(
+        "fg.json",
+        r#"{
+  "blocks": [
+    { "name": "src", "type": "NullSource" },
+    { "name": "head", "type": "Head", "parameters": { "n_items": 1234 } },
+    { "name": "snk", "type": "VectorSinkF32", "parameters": { "capacity": 2048 } }
+  ],
+  "stream_edges": [
+    { "src": "src", "src_port": "out", "dst": "head", "dst_port": "in" },
+    { "src": "head", "src_port": "out", "dst": "snk", "dst_port": "in" }
+  ]
+}"#,
+    );
+    run(path)
+}
+
+#[test]
+fn errors() {
+    let cases = [
+        (
+            "unknown_type.toml",
+            "[[blocks]]\nname = \"a\"\ntype = \"DoesNotExist\"\n",
+        ),
+        (
+            "missing_param.toml",
+            "[[blocks]]\nname = \"a\"\ntype = \"Head\"\n",
+        ),
+        (
+            "bad_item.toml",
+            "[[blocks]]\nname = \"a\"\ntype = \"Copy\"\nparameters = { item = \"foo\" }\n",
+        ),
+        (
+            "duplicate.toml",
+            "[[blocks]]\nname = \"a\"\ntype = \"Copy\"\n[[blocks]]\nname = \"a\"\ntype = \"Copy\"\n",
+        ),
+        (
+            "bad_port.toml",
+            "[[blocks]]\nname = \"a\"\ntype = \"Copy\"\n[[blocks]]\nname = \"b\"\ntype = \"Copy\"\n\
+             [[stream_edges]]\nsrc = \"a\"\nsrc_port = \"foo\"\ndst = \"b\"\ndst_port = \"in\"\n",
+        ),
+    ];
+
+    for (name, content) in cases {
+        let path = write(name, content);
+        assert!(Flowgraph::from_file(&path).is_err(), "{}", name);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,107 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::VectorSink;
+use futuresdr::runtime::grc::Grc;
+use futuresdr::runtime::Runtime;
+
+const GRC: &str = r#"
+options:
+  parameters:
+    id: test
+blocks:
+- name: n_items
+  id: variable
+  parameters:
+    value: '3'
+- name: src
+  id: blocks_vector_source_x
+  parameters:
+    repeat: 'False'
+    tags: '[]'
+    type: float
+    vector: (1.0, 2.0, 3.0, 4.0)
+    vlen: '1'
+  states:
+    state: enabled
+- name: head
+  id: blocks_head
+  parameters:
+    num_items: n_items
+    type: float
+    vlen: '1'
+  states:
+    state: enabled
+- name: fir
+  id: fir_filter_xxx
+  parameters:
+    decim: '1'
+    samp_delay: '0'
+    taps: '[2.0]'
+    type: fff
+  states:
+    state: enabled
+- name: snk
+  id: blocks_vector_sink_x
+  parameters:
+    reserve_items: '1024'
+    type: float
+    vlen: '1'
+  states:
+    state: true
+- name: disabled
+  id: blocks_null_sink
+  parameters:
+    type: float
+  states:
+    state: disabled
+connections:
+- [src, '0', head, '0']
+- [head, '0', fir, '0']
+- [fir, '0', snk, '0']
+- [fir, '0', disabled, '0']
+metadata:
+  file_format: 1
+"#;
+
+#[test]
+fn run() -> Result<()> {
+    let grc = Grc::parse(GRC)?;
+    assert!(grc.unsupported().is_empty());
+    assert_eq!(grc.flowgraph_file().blocks.len(), 4);
+    assert_eq!(grc.flowgraph_file().stream_edges.len(), 3);
+
+    let fg = Runtime::new().run(grc.build()?)?;
+    let snk = fg.kernel::<VectorSink<f32>>(3).unwrap();
+    assert_eq!(snk.items(), &vec![2.0, 4.0, 6.0]);
+    Ok(())
+}
+
+#[test]
+fn rust() -> Result<()> {
+    let code = Grc::parse(GRC)?.to_rust()?;
+    assert!(code.contains("use futuresdr::blocks::Head;\n"));
+    assert!(code.contains("let head = fg.add_block(Head::<f32>::new(3));\n"));
+    assert!(code.contains("FirBuilder::new::<f32, f32, f32, _>(vec![2.0])"));
+    assert!(code.contains("fg.connect_stream(fir, \"out\", snk, \"in\")?;\n"));
+    assert!(!code.contains("disabled"));
+    Ok(())
+}
+
+#[test]
+fn unsupported() -> Result<()> {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/examples/ssb-receiver/ssb-decoder.grc"
+    );
+    let grc = Grc::load(path)?;
+
+    let names: Vec<&str> = grc.unsupported().iter().map(|u| u.name.as_str()).collect();
+    assert!(names.contains(&"analog_sig_source_x_0"));
+    assert!(names.contains(&"freq_xlating_fir_filter_xxx_0"));
+    assert!(!names.contains(&"blocks_file_source_0"));
+    assert!(!names.contains(&"blocks_throttle_0"));
+
+    let e = grc.build().err().unwrap();
+    assert!(format!("{:?}", e).contains("audio_sink_0 (audio_sink): no FutureSDR equivalent"));
+    assert!(grc.to_rust().is_err());
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::Copy;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::MessageCopy;
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::MessageSourceBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::HierBlock;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use std::time::Duration;
+
+fn copy_head(n_items: u64) -> Result<HierBlock> {
+    let mut h = HierBlock::new("CopyHead");
+    let copy = h.add_block(Copy::<u32>::new());
+    let head = h.add_block(Head::<u32>::new(n_items));
+    h.connect_stream(copy, "out", head, "in")?;
+    h.stream_input("in", copy, "in")?;
+    h.stream_output("out", head, "out")?;
+    Ok(h)
+}
+
+#[test]
+fn hier_stream() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let orig: Vec<u32> = (0..1000).collect();
+    let src = fg.add_block(VectorSource::<u32>::new(orig.clone()));
+    let hier = fg.add_hier_block(copy_head(123)?);
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+
+    fg.connect_stream(src, "out", hier, "in")?;
+    fg.connect_stream(hier, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &orig[0..123]);
+    Ok(())
+}
+
+#[test]
+fn hier_nested() -> Result<()> {
+    let mut outer = HierBlock::new("Outer");
+    let a = outer.add_hier_block(copy_head(100)?);
+    let b = outer.add_hier_block(copy_head(10)?);
+    outer.connect_stream(a, "out", b, "in")?;
+    outer.stream_input("in", a, "in")?;
+    outer.stream_output("out", b, "out")?;
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<u32>::new((0..1000).collect()));
+    let hier = fg.add_hier_block(outer);
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", hier, "in")?;
+    fg.connect_stream(hier, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &(0..10).collect::<Vec<u32>>());
+    Ok(())
+}
+
+#[test]
+fn hier_message() -> Result<()> {
+    let mut h = HierBlock::new("Forward");
+    let copy = h.add_block(MessageCopy::new());
+    h.message_input("in", copy, "in")?;
+    h.message_output("out", copy, "out")?;
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(
+        MessageSourceBuilder::new(Pmt::Null, Duration::from_millis(1))
+            .n_messages(20)
+            .build(),
+    );
+    let hier = fg.add_hier_block(h);
+    let snk = fg.add_block(MessageSink::new());
+    fg.connect_message(src, "out", hier, "in")?;
+    fg.connect_message(hier, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<MessageSink>(snk).unwrap();
+    assert_eq!(snk.received(), 20);
+    Ok(())
+}
+
+#[test]
+fn hier_ports() -> Result<()> {
+    let mut h = copy_head(10)?;
+    let copy = h.add_block(Copy::<u32>::new());
+    assert!(h.stream_input("foo", copy, "bar").is_err());
+    assert!(h.stream_input("in", copy, "in").is_err());
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<u32>::new(vec![1, 2, 3]));
+    let hier = fg.add_hier_block(h);
+    assert!(fg.connect_stream(src, "out", hier, "foo").is_err());
+    assert!(fg.connect_message(src, "out", hier, "in").is_err());
+    Ok(())
+}
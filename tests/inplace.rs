@@ -0,0 +1,191 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSource;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::buffer::inplace::InPlace;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::WorkIo;
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Records the addresses of the memory it reads from or writes to.
+struct Addresses {
+    addresses: HashSet<usize>,
+    items: Vec<u16>,
+    index: usize,
+    n_items: usize,
+}
+
+impl Addresses {
+    fn source(n_items: usize) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("AddressSource").build(),
+            StreamIoBuilder::new().add_output::<f32>("out").build(),
+            MessageIoBuilder::new().build(),
+            Addresses {
+                addresses: HashSet::new(),
+                items: Vec::new(),
+                index: 0,
+                n_items,
+            },
+        )
+    }
+
+    fn sink() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("AddressSink").build(),
+            StreamIoBuilder::new().add_input::<u16>("in").build(),
+            MessageIoBuilder::new().build(),
+            Addresses {
+                addresses: HashSet::new(),
+                items: Vec::new(),
+                index: 0,
+                n_items: 0,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Addresses {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        if sio.inputs().is_empty() {
+            let o = sio.output(0).slice::<f32>();
+            let n = std::cmp::min(o.len(), self.n_items);
+            if n > 0 {
+                self.addresses.insert(o.as_ptr() as usize);
+            }
+            for (i, v) in o[..n].iter_mut().enumerate() {
+                *v = (self.index + i) as f32;
+            }
+            self.index += n;
+            self.n_items -= n;
+            sio.output(0).produce(n);
+            if self.n_items == 0 {
+                io.finished = true;
+            }
+        } else {
+            let i = sio.input(0).slice::<u16>();
+            let n = i.len();
+            if n > 0 {
+                self.addresses.insert(i.as_ptr() as usize);
+            }
+            self.items.extend_from_slice(i);
+            sio.input(0).consume(n);
+            if sio.input(0).finished() {
+                io.finished = true;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn apply_chain() -> Result<()> {
+    let n_items = 1_000_000;
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(Addresses::source(n_items));
+    let shift = fg.add_block(Apply::new(|i: &f32| i + 1.0));
+    let gain = fg.add_block(Apply::new(|i: &f32| i * 0.5));
+    let convert = fg.add_block(Apply::new(|i: &f32| *i as u16));
+    let snk = fg.add_block(Addresses::sink());
+    fg.connect_stream_with_type(src, "out", shift, "in", InPlace::new())?;
+    fg.connect_stream_with_type(shift, "out", gain, "in", InPlace::new())?;
+    fg.connect_stream_with_type(gain, "out", convert, "in", InPlace::new())?;
+    fg.connect_stream_with_type(convert, "out", snk, "in", InPlace::new())?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let src = fg.kernel::<Addresses>(src).unwrap();
+    let snk = fg.kernel::<Addresses>(snk).unwrap();
+    let expected: Vec<u16> = (0..n_items)
+        .map(|i| ((i as f32 + 1.0) * 0.5) as u16)
+        .collect();
+    assert_eq!(snk.items, expected);
+    // the sink reads the memory that the source wrote to
+    assert!(snk.addresses.is_subset(&src.addresses));
+
+    Ok(())
+}
+
+#[test]
+fn mixed_buffers() -> Result<()> {
+    let orig: Vec<f32> = (0..100_000).map(|i| i as f32).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<f32>::new(orig.clone()));
+    let gain = fg.add_block(Apply::new(|i: &f32| i * 2.0));
+    let to_f64 = fg.add_block(Apply::new(|i: &f32| *i as f64));
+    let snk = fg.add_block(VectorSinkBuilder::<f64>::new().build());
+    fg.connect_stream(src, "out", gain, "in")?;
+    fg.connect_stream_with_type(gain, "out", to_f64, "in", InPlace::new())?;
+    fg.connect_stream_with_type(to_f64, "out", snk, "in", InPlace::new())?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f64>>(snk).unwrap();
+    let expected: Vec<f64> = orig.iter().map(|i| (i * 2.0) as f64).collect();
+    assert_eq!(snk.items(), &expected);
+
+    Ok(())
+}
+
+#[test]
+fn sole_reader() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<f32>::new(vec![0.0; 10]));
+    let snk1 = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    let snk2 = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    fg.connect_stream_with_type(src, "out", snk1, "in", InPlace::new())?;
+    assert!(fg
+        .connect_stream_with_type(src, "out", snk2, "in", InPlace::new())
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn apply_stats() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(NullSource::<f32>::new());
+    let gain = fg.add_block(Apply::new(|i: &f32| i * 2.0));
+    let snk = fg.add_block(NullSink::<f32>::new());
+    fg.connect_stream_with_type(src, "out", gain, "in", InPlace::new())?;
+    fg.connect_stream_with_type(gain, "out", snk, "in", InPlace::new())?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async move {
+        Timer::after(Duration::from_millis(100)).await;
+        // the items of the in-place buffers are accounted for
+        let stats = handle.block_description(gain).await.unwrap().stats;
+        assert!(stats.items_consumed[0] > 0);
+        assert_eq!(stats.items_produced[0], stats.items_consumed[0]);
+        handle.terminate().await.unwrap();
+        let _ = task.await;
+    });
+
+    Ok(())
+}
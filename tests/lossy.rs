@@ -0,0 +1,157 @@
+use std::iter::repeat_with;
+use std::time::Duration;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::Timer;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::Copy;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::buffer::circular::Circular;
+use futuresdr::runtime::buffer::ring::Ring;
+use futuresdr::runtime::buffer::slab::Slab;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::Tag;
+use futuresdr::runtime::WorkIo;
+
+/// Slow sink that records the items and the gaps reported by dropped tags.
+struct Tap {
+    items: Vec<f32>,
+    // index of the first item after the gap, dropped items
+    dropped: Vec<(usize, usize)>,
+}
+
+impl Tap {
+    fn block() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Tap").build(),
+            StreamIoBuilder::new().add_input::<f32>("in").build(),
+            MessageIoBuilder::new().build(),
+            Tap {
+                items: Vec::new(),
+                dropped: Vec::new(),
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Tap {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        Timer::after(Duration::from_millis(1)).await;
+
+        let (i, tags) = sio.input(0).slice_with_tags::<f32>();
+        let n = std::cmp::min(i.len(), 64);
+        for t in tags.iter().filter(|t| t.index < n) {
+            if let Tag::Dropped(d) = t.tag {
+                self.dropped.push((self.items.len() + t.index, d));
+            }
+        }
+        self.items.extend_from_slice(&i[..n]);
+
+        // the input only finishes, once all queued items are consumed
+        sio.input(0).consume(n);
+        if sio.input(0).finished() {
+            io.finished = true;
+        } else if n > 0 {
+            io.call_again = true;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that the tap got all items, except for the ones reported as dropped.
+fn check_tap(tap: &Tap, orig: &[f32]) {
+    assert!(!tap.dropped.is_empty());
+
+    let mut index = 0;
+    let mut gaps = tap.dropped.iter().peekable();
+    for (i, x) in tap.items.iter().enumerate() {
+        while let Some((_, d)) = gaps.next_if(|g| g.0 == i) {
+            index += d;
+        }
+        assert_eq!(*x, orig[index]);
+        index += 1;
+    }
+    assert_eq!(index, orig.len());
+}
+
+#[test]
+fn lossy_circular() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n_items = 1_000_000;
+    let orig: Vec<f32> = repeat_with(rand::random::<f32>).take(n_items).collect();
+
+    let src = fg.add_block(VectorSource::<f32>::new(orig.clone()));
+    let copy = fg.add_block(Copy::<f32>::new());
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    let tap = fg.add_block(Tap::block());
+
+    fg.connect_stream(src, "out", copy, "in")?;
+    fg.connect_stream_with_type(copy, "out", snk, "in", Circular::with_size(4096))?;
+    fg.connect_stream_lossy_with_type(copy, "out", tap, "in", Circular::with_size(4096))?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+    assert_eq!(snk.items(), &orig);
+    check_tap(fg.kernel::<Tap>(tap).unwrap(), &orig);
+
+    Ok(())
+}
+
+#[test]
+fn lossy_slab() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n_items = 1_000_000;
+    let orig: Vec<f32> = repeat_with(rand::random::<f32>).take(n_items).collect();
+
+    let src = fg.add_block(VectorSource::<f32>::new(orig.clone()));
+    let copy = fg.add_block(Copy::<f32>::new());
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    let tap = fg.add_block(Tap::block());
+
+    fg.connect_stream(src, "out", copy, "in")?;
+    fg.connect_stream_with_type(copy, "out", snk, "in", Slab::with_items(1000))?;
+    fg.connect_stream_lossy_with_type(copy, "out", tap, "in", Slab::with_items(1000))?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+    assert_eq!(snk.items(), &orig);
+    check_tap(fg.kernel::<Tap>(tap).unwrap(), &orig);
+
+    Ok(())
+}
+
+#[test]
+fn lossy_unsupported() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<f32>::new(vec![1.0, 2.0, 3.0]));
+    let tap = fg.add_block(Tap::block());
+
+    assert!(fg
+        .connect_stream_lossy_with_type(src, "out", tap, "in", Ring::new())
+        .is_err());
+
+    Ok(())
+}
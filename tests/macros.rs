@@ -0,0 +1,148 @@
+use std::marker::PhantomData;
+
+use futuresdr::anyhow::{bail, Result};
+use futuresdr::async_io::block_on;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::macros::message_handler;
+use futuresdr::macros::Block;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::ItemType;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::WorkIo;
+
+#[derive(Block)]
+#[message_inputs(factor, r#in = count)]
+#[message_outputs(out)]
+#[type_name(Scaler)]
+#[blocking]
+struct Scale {
+    #[input(name = "in")]
+    input: PhantomData<f32>,
+    #[output]
+    output: PhantomData<f32>,
+    factor: f32,
+    n: u32,
+}
+
+impl Scale {
+    fn block(factor: f32) -> Block {
+        Scale {
+            input: PhantomData,
+            output: PhantomData,
+            factor,
+            n: 0,
+        }
+        .into_block()
+    }
+
+    #[message_handler]
+    async fn factor(
+        &mut self,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::F32(f) => self.factor = f,
+            _ => bail!("wrong pmt"),
+        }
+        Ok(Pmt::Null)
+    }
+
+    #[message_handler]
+    async fn count(
+        &mut self,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        self.n += 1;
+        mio.post(0, p).await;
+        Ok(Pmt::U32(self.n))
+    }
+}
+
+#[async_trait]
+impl Kernel for Scale {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let (i, o) = sio.slices::<f32, f32>(0, 0);
+        let i_len = i.len();
+
+        let m = std::cmp::min(i_len, o.len());
+        for (v, r) in i.iter().zip(o.iter_mut()) {
+            *r = *v * self.factor;
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i_len {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn derive_ports() {
+    let block = Scale::block(1.0);
+
+    assert_eq!(block.type_name(), "Scaler");
+    assert!(block.is_blocking());
+    assert_eq!(block.stream_input_name_to_id("in"), Some(0));
+    assert_eq!(block.stream_output_name_to_id("output"), Some(0));
+    assert_eq!(block.stream_input(0).item_type(), ItemType::of::<f32>());
+    assert_eq!(block.message_input_names(), vec!["factor", "in"]);
+    assert_eq!(block.message_output_name_to_id("out"), Some(0));
+}
+
+#[test]
+fn message_handler() -> Result<()> {
+    let mut block = Scale::block(1.0);
+
+    block_on(async {
+        assert_eq!(block.call_handler(0, Pmt::F32(2.0)).await?, Pmt::Null);
+        assert!(block.call_handler(0, Pmt::Null).await.is_err());
+        assert_eq!(block.call_handler(1, Pmt::Null).await?, Pmt::U32(1));
+        assert_eq!(block.call_handler(1, Pmt::Null).await?, Pmt::U32(2));
+        Ok::<(), futuresdr::anyhow::Error>(())
+    })?;
+
+    assert_eq!(block.kernel::<Scale>().unwrap().factor, 2.0);
+    Ok(())
+}
+
+#[test]
+fn derive_flowgraph() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<f32>::new(vec![1.0, 2.0, 3.0]));
+    let scale = fg.add_block(Scale::block(2.0));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(src, "out", scale, "in")?;
+    fg.connect_stream(scale, "output", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+    assert_eq!(snk.items(), &vec![2.0, 4.0, 6.0]);
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use config::Value;
+use futuresdr::anyhow::Result;
+use futuresdr::runtime::registry;
+use futuresdr::runtime::registry::BlockSchemaBuilder;
+use futuresdr::runtime::registry::Parameters;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::PmtKind;
+use std::collections::HashMap;
+
+fn params(values: &[(&str, Value)]) -> Parameters {
+    Parameters::new(
+        values
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect::<HashMap<String, Value>>(),
+    )
+}
+
+#[test]
+fn schemas() {
+    let schemas = registry::schemas();
+    let names: Vec<&str> = schemas.iter().map(|s| s.type_name.as_str()).collect();
+    for t in ["Copy", "Fft", "FileSource", "Fir", "Head", "Throttle"] {
+        assert!(names.contains(&t), "missing schema {}", t);
+    }
+
+    let fir = registry::schema("Fir").unwrap();
+    assert_eq!(fir.item_types, vec!["f32", "c32"]);
+    assert_eq!(fir.stream_inputs, vec!["in"]);
+    assert_eq!(fir.stream_outputs, vec!["out"]);
+    assert_eq!(fir.parameters[0].name, "taps");
+    assert_eq!(fir.parameters[0].kind, PmtKind::VecF32);
+
+    let throttle = registry::schema("Throttle").unwrap();
+    assert!(throttle.parameters[0].range.is_some());
+
+    let file_source = registry::schema("FileSource").unwrap();
+    let repeat = file_source
+        .parameters
+        .iter()
+        .find(|p| p.name == "repeat")
+        .unwrap();
+    assert_eq!(repeat.kind, PmtKind::Bool);
+    assert!(repeat.range.is_none());
+
+    assert!(registry::schema("Foo").is_none());
+}
+
+#[test]
+fn validate() -> Result<()> {
+    let b = registry::create("Head", &params(&[("n_items", Value::from(10u64))]))?;
+    assert_eq!(b.type_name(), "Head");
+
+    let b = registry::create(
+        "Fir",
+        &params(&[
+            ("taps", Value::from(vec![1.0, 2.0])),
+            ("item", Value::from("c32")),
+        ]),
+    )?;
+    assert_eq!(b.type_name(), "Fir");
+
+    let b = registry::create("Fft", &params(&[("len", Value::from(64u64))]))?;
+    assert_eq!(b.type_name(), "Fft");
+
+    // missing parameter
+    assert!(registry::create("Head", &params(&[])).is_err());
+    // unknown parameter
+    assert!(registry::create(
+        "Head",
+        &params(&[("n_items", Value::from(10u64)), ("foo", Value::from(1u64))])
+    )
+    .is_err());
+    // wrong kind
+    assert!(registry::create("Head", &params(&[("n_items", Value::from("many"))])).is_err());
+    // out of range
+    assert!(registry::create("Throttle", &params(&[("rate", Value::from(-1.0))])).is_err());
+    // unsupported item type
+    assert!(registry::create(
+        "Fir",
+        &params(&[
+            ("taps", Value::from(vec![1.0])),
+            ("item", Value::from("u8"))
+        ])
+    )
+    .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn register_with_schema() -> Result<()> {
+    registry::register_with_schema(
+        BlockSchemaBuilder::new("GainHead")
+            .item_types(&["f32"])
+            .stream_input("in")
+            .stream_output("out")
+            .parameter_with_default("n_items", PmtKind::U64, Pmt::U64(5))
+            .range(1.0, 10.0)
+            .build(),
+        |p| {
+            assert_eq!(p.get::<String>("item")?, "f32");
+            Ok(futuresdr::blocks::Head::<f32>::new(p.get("n_items")?))
+        },
+    );
+
+    assert!(registry::types().contains(&"GainHead".to_string()));
+    let s = registry::schema("GainHead").unwrap();
+    assert_eq!(s.parameters[0].default, Some(Pmt::U64(5)));
+
+    registry::create("GainHead", &params(&[]))?;
+    registry::create("GainHead", &params(&[("n_items", Value::from(3u64))]))?;
+    assert!(registry::create("GainHead", &params(&[("n_items", Value::from(11u64))])).is_err());
+
+    Ok(())
+}
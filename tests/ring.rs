@@ -0,0 +1,162 @@
+use std::iter::repeat_with;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::CopyRand;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::buffer::ring::Ring;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::WorkIo;
+
+/// Copies items in chunks of a fixed size.
+struct Chunks {
+    size: usize,
+}
+
+impl Chunks {
+    fn block(size: usize) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Chunks").min_items(size).build(),
+            StreamIoBuilder::new()
+                .add_input::<f32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::new().build(),
+            Chunks { size },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Chunks {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let (i, o) = sio.slices::<f32, f32>(0, 0);
+        let i_len = i.len();
+        let n = std::cmp::min(i_len, o.len()) / self.size * self.size;
+        o[..n].copy_from_slice(&i[..n]);
+
+        if n > 0 {
+            sio.input(0).consume(n);
+            sio.output(0).produce(n);
+        }
+        if sio.input(0).finished() && i_len - n < self.size {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn fg_rand_vec() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n_items = 1_000_000;
+    let orig: Vec<f32> = repeat_with(rand::random::<f32>).take(n_items).collect();
+
+    let src = fg.add_block(VectorSource::<f32>::new(orig.clone()));
+    let copy1 = fg.add_block(CopyRand::<f32>::new(997));
+    let copy2 = fg.add_block(CopyRand::<f32>::new(333));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream_with_type(src, "out", copy1, "in", Ring::with_size(4000))?;
+    fg.connect_stream_with_type(copy1, "out", copy2, "in", Ring::with_size(1234))?;
+    fg.connect_stream_with_type(copy2, "out", snk, "in", Ring::new())?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+    assert_eq!(snk.items(), &orig);
+
+    Ok(())
+}
+
+#[test]
+fn window() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n_items = 100_000;
+    let orig: Vec<f32> = repeat_with(rand::random::<f32>).take(n_items).collect();
+
+    // 1000 items do not fit an integer number of chunks, so chunks span the wraparound
+    let src = fg.add_block(VectorSource::<f32>::new(orig.clone()));
+    let chunks = fg.add_block(Chunks::block(64));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream_with_type(src, "out", chunks, "in", Ring::with_window(4000, 256))?;
+    fg.connect_stream_with_type(chunks, "out", snk, "in", Ring::with_window(4000, 256))?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+    let n = n_items / 64 * 64;
+    assert_eq!(snk.items(), &orig[..n]);
+
+    Ok(())
+}
+
+#[test]
+fn default_window() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n_items = 100_000;
+    let orig: Vec<f32> = repeat_with(rand::random::<f32>).take(n_items).collect();
+
+    // without a window for the chunks, the block would stall at the wraparound
+    let src = fg.add_block(VectorSource::<f32>::new(orig.clone()));
+    let chunks = fg.add_block(Chunks::block(64));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream_with_type(src, "out", chunks, "in", Ring::with_size(4000))?;
+    fg.connect_stream_with_type(chunks, "out", snk, "in", Ring::with_size(4000))?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+    let n = n_items / 64 * 64;
+    assert_eq!(snk.items(), &orig[..n]);
+
+    Ok(())
+}
+
+#[test]
+fn multiple_readers() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n_items = 1_000_000;
+    let orig: Vec<f32> = repeat_with(rand::random::<f32>).take(n_items).collect();
+
+    let src = fg.add_block(VectorSource::<f32>::new(orig.clone()));
+    let copy = fg.add_block(CopyRand::<f32>::new(512));
+    let snk1 = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    let snk2 = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream_with_type(src, "out", copy, "in", Ring::with_size(8192))?;
+    fg.connect_stream_with_type(copy, "out", snk1, "in", Ring::with_size(8192))?;
+    fg.connect_stream_with_type(copy, "out", snk2, "in", Ring::with_size(8192))?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk1 = fg.kernel::<VectorSink<f32>>(snk1).unwrap();
+    assert_eq!(snk1.items(), &orig);
+    let snk2 = fg.kernel::<VectorSink<f32>>(snk2).unwrap();
+    assert_eq!(snk2.items(), &orig);
+
+    Ok(())
+}
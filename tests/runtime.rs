@@ -1,17 +1,35 @@
 use std::iter::repeat_with;
 
-use futuresdr::anyhow::Result;
+use futuresdr::anyhow::{bail, Result};
 use futuresdr::async_io::block_on;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::Apply;
 use futuresdr::blocks::Copy;
 use futuresdr::blocks::Head;
+use futuresdr::blocks::MessageSink;
 use futuresdr::blocks::NullSink;
 use futuresdr::blocks::NullSource;
 use futuresdr::blocks::Throttle;
 use futuresdr::blocks::VectorSink;
 use futuresdr::blocks::VectorSinkBuilder;
 use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::buffer::circular::Circular;
+use futuresdr::runtime::buffer::slab::Slab;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::BlockPhase;
+use futuresdr::runtime::ErrorPolicy;
 use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::FlowgraphError;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Pmt;
 use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::WorkIo;
 
 #[test]
 fn flowgraph() -> Result<()> {
@@ -70,6 +88,105 @@ fn fg_terminate() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn fg_stats() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let copy = fg.add_block(Copy::<f32>::new());
+    let snk = fg.add_block(NullSink::<f32>::new());
+
+    fg.connect_stream(src, "out", copy, "in")?;
+    fg.connect_stream(copy, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async move {
+        futuresdr::async_io::Timer::after(std::time::Duration::from_millis(100)).await;
+        let stats = handle.block_description(copy).await.unwrap().stats;
+        assert!(stats.work_calls > 0);
+        assert!(stats.work_time > 0);
+        assert_eq!(stats.items_consumed.len(), 1);
+        assert_eq!(stats.items_produced.len(), 1);
+        assert!(stats.items_consumed[0] > 0);
+        assert!(stats.items_produced[0] <= stats.items_consumed[0]);
+        assert_eq!(stats.input_fill.len(), 1);
+        handle.terminate().await.unwrap();
+        let _ = task.await;
+    });
+
+    Ok(())
+}
+
+#[test]
+fn fg_buffer_sizes() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let copy = fg.add_block(Copy::<f32>::new());
+    let snk = fg.add_block(NullSink::<f32>::new());
+
+    let latency = std::time::Duration::from_millis(1);
+    fg.connect_stream_with_type(
+        src,
+        "out",
+        copy,
+        "in",
+        Circular::with_max_latency(latency, 1e6),
+    )?;
+    fg.connect_stream_with_type(copy, "out", snk, "in", Slab::with_items(1000))?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async move {
+        let d = handle.description().await.unwrap();
+        assert_eq!(d.stream_edge_buffers.len(), d.stream_edges.len());
+        for (e, b) in d.stream_edges.iter().zip(d.stream_edge_buffers.iter()) {
+            assert_eq!(b.item_size, 4);
+            if e.0 == src {
+                assert_eq!(b.type_name, "Circular");
+                assert_eq!(b.capacity, Some(1000));
+            } else {
+                assert_eq!(b.type_name, "Slab");
+                assert_eq!(b.capacity, Some(2000));
+            }
+        }
+        handle.terminate().await.unwrap();
+        let _ = task.await;
+    });
+
+    Ok(())
+}
+
+#[test]
+fn fg_max_latency() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n_items = 100_000;
+    let orig: Vec<f32> = repeat_with(rand::random::<f32>).take(n_items).collect();
+
+    let src = fg.add_block(VectorSource::<f32>::new(orig.clone()));
+    let copy = fg.add_block(Copy::<f32>::new());
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    let latency = std::time::Duration::from_micros(100);
+    fg.connect_stream_with_type(
+        src,
+        "out",
+        copy,
+        "in",
+        Circular::with_max_latency(latency, 1e6),
+    )?;
+    fg.connect_stream_with_type(copy, "out", snk, "in", Slab::with_max_latency(latency, 1e6))?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+    assert_eq!(snk.items(), &orig);
+
+    Ok(())
+}
+
 #[test]
 fn fg_rand_vec() -> Result<()> {
     let mut fg = Flowgraph::new();
@@ -138,3 +255,343 @@ fn fg_rand_vec_multi_snk() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn fg_reconfigure() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let copy = fg.add_block(Copy::<f32>::new());
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(src, "out", copy, "in")?;
+    fg.connect_stream(copy, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    let fg = block_on(async move {
+        let copy_id = copy;
+        let copy = handle.remove_block(copy_id).await?;
+        assert!(copy.kernel::<Copy<f32>>().is_some());
+
+        // the id is invalid, until it is reused by another block
+        assert!(handle.call(copy_id, 0, Pmt::Null).await.is_err());
+        assert!(handle.callback(copy_id, 0, Pmt::Null).await.is_err());
+        assert!(handle.block_description(copy_id).await.is_err());
+        assert!(handle.remove_block(copy_id).await.is_err());
+
+        let head = handle.add_block(Head::<f32>::new(1234)).await?;
+        let apply = handle.add_block(Apply::new(|i: &f32| i + 1.0)).await?;
+        assert!(handle
+            .connect_stream(src, "out", head, "foo")
+            .await
+            .is_err());
+
+        handle.connect_stream(src, "out", head, "in").await?;
+        handle.connect_stream(head, "out", apply, "in").await?;
+        handle.connect_stream(apply, "out", snk, "in").await?;
+        task.await
+    })?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+    let n = snk.items().iter().filter(|x| **x == 1.0).count();
+    assert_eq!(n, 1234);
+
+    Ok(())
+}
+
+/// Finishes right away, but takes its time to shut down.
+struct SlowDeinit;
+
+impl SlowDeinit {
+    #[allow(clippy::new_ret_no_self)]
+    fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("SlowDeinit").build(),
+            StreamIoBuilder::new().add_output::<f32>("out").build(),
+            MessageIoBuilder::new().build(),
+            SlowDeinit,
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for SlowDeinit {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _s: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        io.finished = true;
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _s: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        futuresdr::async_io::Timer::after(std::time::Duration::from_millis(300)).await;
+        Ok(())
+    }
+}
+
+#[test]
+fn fg_connect_terminating() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(SlowDeinit::new());
+    let snk = fg.add_block(NullSink::<f32>::new());
+    let null_src = fg.add_block(NullSource::<f32>::new());
+    let null_snk = fg.add_block(NullSink::<f32>::new());
+
+    fg.connect_stream(src, "out", snk, "in")?;
+    fg.connect_stream(null_src, "out", null_snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async move {
+        futuresdr::async_io::Timer::after(std::time::Duration::from_millis(100)).await;
+        // the source does not add the reader anymore, since it is shutting down
+        let late = handle.add_block(NullSink::<f32>::new()).await?;
+        assert!(handle.connect_stream(src, "out", late, "in").await.is_err());
+        handle.terminate().await?;
+        let _ = task.await;
+        Ok::<_, futuresdr::anyhow::Error>(())
+    })?;
+
+    Ok(())
+}
+
+struct Fail {
+    phase: BlockPhase,
+}
+
+impl Fail {
+    #[allow(clippy::new_ret_no_self)]
+    fn new(phase: BlockPhase) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Fail").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new().build(),
+            Fail { phase },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Fail {
+    async fn work(
+        &mut self,
+        _io: &mut WorkIo,
+        _s: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.phase == BlockPhase::Work {
+            bail!("work failed");
+        }
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _s: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.phase == BlockPhase::Init {
+            bail!("init failed");
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn fg_block_error() -> Result<()> {
+    for phase in [BlockPhase::Init, BlockPhase::Work] {
+        let mut fg = Flowgraph::new();
+
+        let src = fg.add_block(NullSource::<f32>::new());
+        let snk = fg.add_block(NullSink::<f32>::new());
+        let fail = fg.add_block(Fail::new(phase));
+        fg.connect_stream(src, "out", snk, "in")?;
+
+        let e = Runtime::new()
+            .run(fg)
+            .err()
+            .unwrap()
+            .downcast::<FlowgraphError>()?;
+        assert_eq!(e.block_id, fail);
+        assert_eq!(e.instance_name, "Fail_0");
+        assert_eq!(e.phase, phase);
+
+        let fg = e.take_flowgraph().unwrap();
+        assert!(fg.kernel::<Fail>(fail).is_some());
+        assert!(fg.kernel::<NullSink<f32>>(snk).is_some());
+    }
+
+    Ok(())
+}
+
+struct Flaky {
+    failures: usize,
+    inits: usize,
+}
+
+impl Flaky {
+    #[allow(clippy::new_ret_no_self)]
+    fn new(failures: usize, policy: ErrorPolicy) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Flaky").error_policy(policy).build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new().build(),
+            Flaky { failures, inits: 0 },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Flaky {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _s: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.failures > 0 {
+            self.failures -= 1;
+            io.call_again = true;
+            bail!("work failed");
+        }
+        io.finished = true;
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _s: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        self.inits += 1;
+        Ok(())
+    }
+}
+
+#[test]
+fn fg_error_policy() -> Result<()> {
+    for (policy, inits) in [(ErrorPolicy::Continue, 1), (ErrorPolicy::Restart(3), 4)] {
+        let mut fg = Flowgraph::new();
+        let flaky = fg.add_block(Flaky::new(3, policy));
+        fg = Runtime::new().run(fg)?;
+        assert_eq!(fg.kernel::<Flaky>(flaky).unwrap().inits, inits);
+    }
+
+    for policy in [ErrorPolicy::Terminate, ErrorPolicy::Restart(2)] {
+        let mut fg = Flowgraph::new();
+        fg.add_block(Flaky::new(3, policy));
+        let e = Runtime::new()
+            .run(fg)
+            .err()
+            .unwrap()
+            .downcast::<FlowgraphError>()?;
+        assert_eq!(e.phase, BlockPhase::Work);
+    }
+
+    Ok(())
+}
+
+struct RawSink;
+
+impl RawSink {
+    #[allow(clippy::new_ret_no_self)]
+    fn new(item_size: usize) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("RawSink").build(),
+            StreamIoBuilder::new()
+                .add_raw_input("in", item_size)
+                .build(),
+            MessageIoBuilder::new().build(),
+            RawSink,
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for RawSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let item_size = sio.input(0).item_size();
+        let n = sio.input(0).slice::<u8>().len() / item_size;
+        sio.input(0).consume(n);
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn fg_item_types() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<u32>::new(vec![1, 2, 3]));
+    let f32_snk = fg.add_block(NullSink::<f32>::new());
+    let raw8 = fg.add_block(RawSink::new(8));
+    let e = fg.connect_stream(src, "out", f32_snk, "in").unwrap_err();
+    assert!(format!("{:#}", e).contains("item types do not match (output u32, input f32)"));
+    let e = fg.connect_stream(src, "out", raw8, "in").unwrap_err();
+    assert!(format!("{:#}", e).contains("input [u8; 8]"));
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(NullSource::<u32>::new());
+    let copy = fg.add_block(Copy::<u32>::new());
+    let snk = fg.add_block(RawSink::new(4));
+    fg.connect_stream(src, "out", copy, "in")?;
+    fg.connect_stream(copy, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    let (copy, snk_desc) = block_on(async move {
+        let copy = handle.block_description(copy).await.unwrap();
+        let snk_desc = handle.block_description(snk).await.unwrap();
+        handle.terminate().await.unwrap();
+        let _ = task.await;
+        (copy, snk_desc)
+    });
+    assert_eq!(copy.stream_input_types, vec!["u32"]);
+    assert_eq!(copy.stream_output_types, vec!["u32"]);
+    assert_eq!(snk_desc.stream_input_types, vec!["[u8; 4]"]);
+
+    Ok(())
+}
+
+#[test]
+fn fg_invalid_handler() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let snk = fg.add_block(MessageSink::new());
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async move {
+        handle.call(snk, 1, Pmt::Null).await.unwrap();
+        assert!(handle.callback(snk, 1, Pmt::Null).await.is_err());
+        // the block is still running
+        handle.callback(snk, 0, Pmt::Null).await.unwrap();
+        let stats = handle.block_description(snk).await.unwrap().stats;
+        assert_eq!(stats.handler_calls, vec![1]);
+        handle.terminate().await.unwrap();
+        let _ = task.await;
+    });
+
+    Ok(())
+}
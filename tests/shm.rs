@@ -0,0 +1,278 @@
+#![cfg(target_os = "linux")]
+
+use std::iter::repeat_with;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::ShmSource;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::buffer::shm::Shm;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::Tag;
+use futuresdr::runtime::WorkIo;
+
+/// Outputs the items once the gate opens, tagging every 1000th item with its index.
+struct Gate {
+    items: Vec<f32>,
+    produced: usize,
+    open: Arc<AtomicBool>,
+}
+
+impl Gate {
+    fn block(items: Vec<f32>, open: Arc<AtomicBool>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Gate").build(),
+            StreamIoBuilder::new().add_output::<f32>("out").build(),
+            MessageIoBuilder::new().build(),
+            Gate {
+                items,
+                produced: 0,
+                open,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Gate {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        if !self.open.load(Ordering::Acquire) {
+            Timer::after(Duration::from_millis(1)).await;
+            io.call_again = true;
+            return Ok(());
+        }
+
+        let o = sio.output(0).slice::<f32>();
+        let n = std::cmp::min(o.len(), self.items.len() - self.produced);
+        o[..n].copy_from_slice(&self.items[self.produced..self.produced + n]);
+        for i in (self.produced..self.produced + n).filter(|i| i % 1000 == 0) {
+            sio.output(0)
+                .add_tag(i - self.produced, Tag::NamedUsize("index".to_string(), i));
+        }
+        sio.output(0).produce(n);
+        self.produced += n;
+
+        if self.produced == self.items.len() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+/// Records the items and the indices of the tags.
+struct Recorder {
+    items: Vec<f32>,
+    tags: Vec<(usize, usize)>,
+}
+
+impl Recorder {
+    fn block() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Recorder").build(),
+            StreamIoBuilder::new().add_input::<f32>("in").build(),
+            MessageIoBuilder::new().build(),
+            Recorder {
+                items: Vec::new(),
+                tags: Vec::new(),
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Recorder {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let (i, tags) = sio.input(0).slice_with_tags::<f32>();
+        for t in tags.iter() {
+            if let Tag::NamedUsize(_, index) = t.tag {
+                self.tags.push((self.items.len() + t.index, index));
+            }
+        }
+        self.items.extend_from_slice(i);
+        let n = i.len();
+
+        sio.input(0).consume(n);
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn local_reader() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n_items = 1_000_000;
+    let orig: Vec<f32> = repeat_with(rand::random::<f32>).take(n_items).collect();
+
+    let src = fg.add_block(VectorSource::<f32>::new(orig.clone()));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream_with_type(
+        src,
+        "out",
+        snk,
+        "in",
+        Shm::with_size("futuresdr-test-local", 8192),
+    )?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+    assert_eq!(snk.items(), &orig);
+
+    Ok(())
+}
+
+#[test]
+fn attached_reader() -> Result<()> {
+    let name = "futuresdr-test-attached";
+    let n_items = 1_000_000;
+    let orig: Vec<f32> = repeat_with(rand::random::<f32>).take(n_items).collect();
+    let open = Arc::new(AtomicBool::new(false));
+
+    let mut writer = Flowgraph::new();
+    let src = writer.add_block(Gate::block(orig.clone(), open.clone()));
+    let snk = writer.add_block(VectorSinkBuilder::<f32>::new().build());
+    writer.connect_stream_with_type(src, "out", snk, "in", Shm::with_size(name, 8192))?;
+
+    let mut reader = Flowgraph::new();
+    let shm = reader.add_block(ShmSource::<f32>::new(name));
+    let rec = reader.add_block(Recorder::block());
+    reader.connect_stream(shm, "out", rec, "in")?;
+
+    let rt = Runtime::new();
+    let (writer, reader) = block_on(async {
+        let (writer, _) = rt.start(writer).await;
+        let (reader, _) = rt.start(reader).await;
+        open.store(true, Ordering::Release);
+        (writer.await, reader.await)
+    });
+    let writer = writer?;
+    let reader = reader?;
+
+    let snk = writer.kernel::<VectorSink<f32>>(snk).unwrap();
+    assert_eq!(snk.items(), &orig);
+
+    let rec = reader.kernel::<Recorder>(rec).unwrap();
+    assert_eq!(rec.items, orig);
+    let tags: Vec<(usize, usize)> = (0..n_items).step_by(1000).map(|i| (i, i)).collect();
+    assert_eq!(rec.tags, tags);
+
+    Ok(())
+}
+
+#[test]
+fn detached_reader() -> Result<()> {
+    let name = "futuresdr-test-detached";
+    let n_items = 1_000_000;
+    let orig: Vec<f32> = repeat_with(rand::random::<f32>).take(n_items).collect();
+    let open = Arc::new(AtomicBool::new(false));
+
+    let mut writer = Flowgraph::new();
+    let src = writer.add_block(Gate::block(orig.clone(), open.clone()));
+    let snk = writer.add_block(VectorSinkBuilder::<f32>::new().build());
+    writer.connect_stream_with_type(src, "out", snk, "in", Shm::with_size(name, 8192))?;
+
+    // the reader stops early, the writer has to continue without it
+    let mut reader = Flowgraph::new();
+    let shm = reader.add_block(ShmSource::<f32>::new(name));
+    let head = reader.add_block(Head::<f32>::new(1000));
+    let rec = reader.add_block(VectorSinkBuilder::<f32>::new().build());
+    reader.connect_stream(shm, "out", head, "in")?;
+    reader.connect_stream(head, "out", rec, "in")?;
+
+    let rt = Runtime::new();
+    let (writer, reader) = block_on(async {
+        let (writer, _) = rt.start(writer).await;
+        let (reader, _) = rt.start(reader).await;
+        open.store(true, Ordering::Release);
+        (writer.await, reader.await)
+    });
+    let writer = writer?;
+    let reader = reader?;
+
+    let snk = writer.kernel::<VectorSink<f32>>(snk).unwrap();
+    assert_eq!(snk.items(), &orig);
+    let rec = reader.kernel::<VectorSink<f32>>(rec).unwrap();
+    assert_eq!(rec.items(), &orig[..1000]);
+
+    Ok(())
+}
+
+#[test]
+fn existing_object() -> Result<()> {
+    let name = "futuresdr-test-existing";
+    let open = Arc::new(AtomicBool::new(false));
+
+    let mut writer = Flowgraph::new();
+    let src = writer.add_block(Gate::block(vec![1.0; 1000], open.clone()));
+    let snk = writer.add_block(VectorSinkBuilder::<f32>::new().build());
+    // a test run that crashed might have left the object
+    writer.connect_stream_with_type(
+        src,
+        "out",
+        snk,
+        "in",
+        Shm::with_size(name, 8192).unlink_existing(),
+    )?;
+
+    let rt = Runtime::new();
+    let (writer, _) = block_on(rt.start(writer));
+
+    // the object of the running flowgraph is not replaced
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<f32>::new(vec![1.0, 2.0, 3.0]));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    fg.connect_stream_with_type(src, "out", snk, "in", Shm::with_size(name, 8192))?;
+    assert!(rt.run(fg).is_err());
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<f32>::new(vec![1.0, 2.0, 3.0]));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    fg.connect_stream_with_type(
+        src,
+        "out",
+        snk,
+        "in",
+        Shm::with_size(name, 8192).unlink_existing(),
+    )?;
+    let fg = rt.run(fg)?;
+    let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+    assert_eq!(snk.items(), &vec![1.0, 2.0, 3.0]);
+
+    open.store(true, Ordering::Release);
+    block_on(writer)?;
+
+    Ok(())
+}
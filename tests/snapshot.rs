@@ -0,0 +1,149 @@
+use futuresdr::anyhow::{bail, Result};
+use futuresdr::async_io::block_on;
+use futuresdr::async_trait::async_trait;
+use futuresdr::futures::FutureExt;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::FlowgraphSnapshot;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+
+/// Keeps a persistent `gain` parameter, which can only be set after `init()`.
+struct Gain {
+    gain: f64,
+    initialized: bool,
+}
+
+impl Gain {
+    fn block(gain: f64) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Gain").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_parameter(
+                    "gain",
+                    |block: &Gain| Pmt::F64(block.gain),
+                    |block: &mut Gain,
+                     _mio: &mut MessageIo<Gain>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            if !block.initialized {
+                                bail!("not initialized");
+                            }
+                            block.gain = f64::try_from(&p)?;
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .add_input(
+                    "reset",
+                    |block: &mut Gain,
+                     _mio: &mut MessageIo<Gain>,
+                     _meta: &mut BlockMeta,
+                     _p: Pmt| {
+                        async move {
+                            block.gain = 0.0;
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            Gain {
+                gain,
+                initialized: false,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Gain {
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.initialized = true;
+        Ok(())
+    }
+}
+
+fn snapshot(fg: Flowgraph, gain: Option<f64>) -> Result<FlowgraphSnapshot> {
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async move {
+        if let Some(g) = gain {
+            handle.callback(0, 0, Pmt::F64(g)).await?;
+        }
+        let s = handle.snapshot().await?;
+        handle.terminate().await?;
+        let _ = task.await;
+        Ok(s)
+    })
+}
+
+#[test]
+fn snapshot_parameters() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    fg.add_block(Gain::block(1.0));
+    let mut other = Gain::block(2.0);
+    other.set_instance_name("other");
+    fg.add_block(other);
+
+    let s = snapshot(fg, Some(3.0))?;
+    assert_eq!(s.get("Gain_0", "gain"), Some(&Pmt::F64(3.0)));
+    assert_eq!(s.get("other", "gain"), Some(&Pmt::F64(2.0)));
+    assert_eq!(s.get("Gain_0", "reset"), None);
+
+    Ok(())
+}
+
+#[test]
+fn restore_parameters() -> Result<()> {
+    let mut saved = FlowgraphSnapshot::new();
+    saved.set("Gain_0", "gain", Pmt::F64(5.0));
+    saved.set("Gain_0", "unknown", Pmt::Null);
+
+    let mut fg = Flowgraph::new();
+    fg.add_block(Gain::block(1.0));
+    fg.restore(saved);
+
+    let s = snapshot(fg, None)?;
+    assert_eq!(s.get("Gain_0", "gain"), Some(&Pmt::F64(5.0)));
+
+    Ok(())
+}
+
+#[test]
+fn save_load() -> Result<()> {
+    let mut s = FlowgraphSnapshot::new();
+    s.set("src", "freq", Pmt::F64(2.4e9));
+    s.set("src", "gain", Pmt::U32(30));
+    s.set("snk", "label", Pmt::String("foo".to_string()));
+
+    for ext in ["json", "toml"] {
+        let mut path = std::env::temp_dir();
+        path.push(format!("futuresdr-{}-snapshot.{}", std::process::id(), ext));
+        s.save(&path)?;
+        let loaded = FlowgraphSnapshot::load(&path)?;
+        std::fs::remove_file(path)?;
+        assert_eq!(loaded, s);
+    }
+
+    let toml = "[blocks.src]\nfreq = { F64 = 100e6 }\n";
+    let loaded = FlowgraphSnapshot::from_toml(toml)?;
+    assert_eq!(loaded.get("src", "freq"), Some(&Pmt::F64(100e6)));
+
+    Ok(())
+}
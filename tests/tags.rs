@@ -0,0 +1,209 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::ApplyNM;
+use futuresdr::blocks::Combine;
+use futuresdr::blocks::Copy;
+use futuresdr::blocks::Filter;
+use futuresdr::blocks::Head;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::Tag;
+use futuresdr::runtime::WorkIo;
+
+/// Produces `n_items` zeros and tags every `spacing`-th item with its index.
+struct TagSource {
+    n_items: usize,
+    spacing: usize,
+    produced: usize,
+}
+
+impl TagSource {
+    fn block(n_items: usize, spacing: usize) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TagSource").build(),
+            StreamIoBuilder::new().add_output::<f32>("out").build(),
+            MessageIoBuilder::new().build(),
+            TagSource {
+                n_items,
+                spacing,
+                produced: 0,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for TagSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<f32>();
+        let n = std::cmp::min(o.len(), self.n_items - self.produced);
+        o[..n].fill(0.0);
+
+        for i in 0..n {
+            let index = self.produced + i;
+            if index % self.spacing == 0 {
+                sio.output(0).add_tag(i, Tag::Id(index as u64));
+            }
+        }
+
+        self.produced += n;
+        sio.output(0).produce(n);
+        if self.produced == self.n_items {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+/// Records the absolute index of all `Tag::Id` tags.
+struct TagSink {
+    tags: Vec<(u64, u64)>,
+}
+
+impl TagSink {
+    fn block() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TagSink").build(),
+            StreamIoBuilder::new().add_input::<f32>("in").build(),
+            MessageIoBuilder::new().build(),
+            TagSink { tags: Vec::new() },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for TagSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let offset = sio.input(0).items_consumed();
+        let (i, tags) = sio.input(0).slice_with_tags::<f32>();
+        let n = i.len();
+        for t in tags.iter().filter(|t| t.index < n) {
+            if let Tag::Id(id) = t.tag {
+                self.tags.push((offset + t.index as u64, id));
+            }
+        }
+
+        sio.input(0).consume(n);
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+fn sink_tags(fg: &Flowgraph, id: usize) -> Vec<(u64, u64)> {
+    fg.kernel::<TagSink>(id).unwrap().tags.clone()
+}
+
+#[test]
+fn one_to_one() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(TagSource::block(100_000, 1000));
+    let apply = fg.add_block(Apply::new(|x: &f32| *x + 1.0));
+    let copy = fg.add_block(Copy::<f32>::new());
+    let head = fg.add_block(Head::<f32>::new(50_000));
+    let snk = fg.add_block(TagSink::block());
+
+    fg.connect_stream(src, "out", apply, "in")?;
+    fg.connect_stream(apply, "out", copy, "in")?;
+    fg.connect_stream(copy, "out", head, "in")?;
+    fg.connect_stream(head, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let expected: Vec<(u64, u64)> = (0..50_000).step_by(1000).map(|i| (i, i)).collect();
+    assert_eq!(sink_tags(&fg, snk), expected);
+
+    Ok(())
+}
+
+#[test]
+fn rate() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(TagSource::block(100_000, 1000));
+    let decim = fg.add_block(ApplyNM::<_, f32, f32, 2, 1>::new(
+        |i: &[f32], o: &mut [f32]| {
+            o[0] = i[0] + i[1];
+        },
+    ));
+    let snk = fg.add_block(TagSink::block());
+
+    fg.connect_stream(src, "out", decim, "in")?;
+    fg.connect_stream(decim, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let expected: Vec<(u64, u64)> = (0..100_000).step_by(1000).map(|i| (i / 2, i)).collect();
+    assert_eq!(sink_tags(&fg, snk), expected);
+
+    Ok(())
+}
+
+#[test]
+fn all_to_all() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src0 = fg.add_block(TagSource::block(10_000, 1000));
+    let src1 = fg.add_block(TagSource::block(10_000, 2500));
+    let combine = fg.add_block(Combine::new(|a: &f32, b: &f32| *a + *b));
+    let snk = fg.add_block(TagSink::block());
+
+    fg.connect_stream(src0, "out", combine, "in0")?;
+    fg.connect_stream(src1, "out", combine, "in1")?;
+    fg.connect_stream(combine, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let mut tags = sink_tags(&fg, snk);
+    tags.sort_unstable();
+    let mut expected: Vec<(u64, u64)> = (0..10_000)
+        .step_by(1000)
+        .chain((0..10_000).step_by(2500))
+        .map(|i| (i, i))
+        .collect();
+    expected.sort_unstable();
+    assert_eq!(tags, expected);
+
+    Ok(())
+}
+
+#[test]
+fn no_propagation() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(TagSource::block(10_000, 1000));
+    let filter = fg.add_block(Filter::new(|x: &f32| Some(*x)));
+    let snk = fg.add_block(TagSink::block());
+
+    fg.connect_stream(src, "out", filter, "in")?;
+    fg.connect_stream(filter, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    assert!(sink_tags(&fg, snk).is_empty());
+
+    Ok(())
+}
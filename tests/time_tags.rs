@@ -0,0 +1,212 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_trait::async_trait;
+use futuresdr::blocks::FileSink;
+use futuresdr::blocks::FileSource;
+use futuresdr::blocks::Throttle;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::SampleClock;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::Tag;
+use futuresdr::runtime::WorkIo;
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn path(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("futuresdr-{}-{}", std::process::id(), name));
+    path
+}
+
+/// Tracks the sample clock of the stream and records the time of the last sample.
+struct ClockSink {
+    clock: SampleClock,
+    n_items: u64,
+    last: Option<Duration>,
+}
+
+impl ClockSink {
+    fn block() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ClockSink").build(),
+            StreamIoBuilder::new().add_input::<f32>("in").build(),
+            MessageIoBuilder::new().build(),
+            ClockSink {
+                clock: SampleClock::new(),
+                n_items: 0,
+                last: None,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for ClockSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let (i, tags) = sio.input(0).slice_with_tags::<f32>();
+        let n = i.len();
+        for t in tags.iter().filter(|t| t.index < n) {
+            self.clock.update(self.n_items + t.index as u64, &t.tag);
+        }
+
+        self.n_items += n as u64;
+        if n > 0 {
+            self.last = self.clock.time(self.n_items - 1);
+        }
+
+        sio.input(0).consume(n);
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+/// Produces `n_items` samples with the value of their index and tags bursts.
+struct BurstSource {
+    n_items: usize,
+    bursts: Vec<(usize, usize)>,
+    produced: usize,
+}
+
+impl BurstSource {
+    fn block(n_items: usize, bursts: Vec<(usize, usize)>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("BurstSource").build(),
+            StreamIoBuilder::new().add_output::<f32>("out").build(),
+            MessageIoBuilder::new().build(),
+            BurstSource {
+                n_items,
+                bursts,
+                produced: 0,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for BurstSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let o = sio.output(0).slice::<f32>();
+        let n = std::cmp::min(o.len(), self.n_items - self.produced);
+        for (i, v) in o[..n].iter_mut().enumerate() {
+            *v = (self.produced + i) as f32;
+        }
+
+        let window = self.produced..self.produced + n;
+        for (sob, eob) in self.bursts.iter() {
+            if window.contains(sob) {
+                sio.output(0).add_tag(sob - self.produced, Tag::TxSob);
+            }
+            if window.contains(eob) {
+                sio.output(0).add_tag(eob - self.produced, Tag::TxEob);
+            }
+        }
+
+        self.produced += n;
+        sio.output(0).produce(n);
+        if self.produced == self.n_items {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn file_source_start_time() -> Result<()> {
+    let file = path("start-time.f32");
+    let data: Vec<u8> = (0..1000).flat_map(|i| (i as f32).to_ne_bytes()).collect();
+    std::fs::write(&file, data)?;
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(FileSource::<f32>::with_start_time(
+        file.to_str().unwrap(),
+        false,
+        Duration::from_secs(100),
+        1000.0,
+    ));
+    let snk = fg.add_block(ClockSink::block());
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+    std::fs::remove_file(file)?;
+
+    let snk = fg.kernel::<ClockSink>(snk).unwrap();
+    assert_eq!(snk.n_items, 1000);
+    assert_eq!(snk.clock.rate(), Some(1000.0));
+    assert_eq!(snk.last, Some(Duration::from_millis(100_999)));
+    assert_eq!(snk.clock.index(Duration::from_millis(100_500)), Some(500));
+
+    Ok(())
+}
+
+#[test]
+fn throttle_timestamps() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<f32>::new(vec![0.0; 10_000]));
+    let throttle = fg.add_block(Throttle::<f32>::with_timestamps(100_000.0, true));
+    let snk = fg.add_block(ClockSink::block());
+    fg.connect_stream(src, "out", throttle, "in")?;
+    fg.connect_stream(throttle, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<ClockSink>(snk).unwrap();
+    assert_eq!(snk.n_items, 10_000);
+    assert_eq!(snk.clock.rate(), Some(100_000.0));
+    let start = snk.clock.time(0).unwrap();
+    assert!(start > Duration::ZERO);
+    assert_eq!(
+        snk.clock.index(start + Duration::from_millis(50)),
+        Some(5000)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn file_sink_bursts() -> Result<()> {
+    let file = path("bursts.f32");
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(BurstSource::block(
+        100_000,
+        vec![(10, 19), (50_000, 70_000)],
+    ));
+    let snk = fg.add_block(FileSink::<f32>::new(file.to_str().unwrap()));
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    let data = std::fs::read(&file)?;
+    std::fs::remove_file(file)?;
+    let items: Vec<f32> = data
+        .chunks_exact(4)
+        .map(|b| f32::from_ne_bytes(b.try_into().unwrap()))
+        .collect();
+    // samples before the first burst tag are written
+    let expected: Vec<f32> = (0..20).chain(50_000..70_001).map(|i| i as f32).collect();
+    assert_eq!(items, expected);
+
+    Ok(())
+}
@@ -0,0 +1,352 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::async_trait::async_trait;
+use futuresdr::futures::FutureExt;
+use futuresdr::runtime::buffer::inplace;
+use futuresdr::runtime::buffer::inplace::InPlace;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::CallTime;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::WorkIo;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Produces `n_items` samples and, before the first one, schedules a message for each mark.
+///
+/// The source does not finish, since this would terminate the receiver of the messages. It is
+/// shut down by the [Recorder].
+struct MarkSource {
+    n_items: usize,
+    marks: Vec<u64>,
+    produced: usize,
+}
+
+impl MarkSource {
+    fn block(n_items: usize, marks: Vec<u64>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("MarkSource").build(),
+            StreamIoBuilder::new().add_output::<f32>("out").build(),
+            MessageIoBuilder::new().add_output("marks").build(),
+            MarkSource {
+                n_items,
+                marks,
+                produced: 0,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for MarkSource {
+    async fn work(
+        &mut self,
+        _io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        for m in self.marks.drain(..) {
+            mio.post_at(0, Pmt::U64(m), CallTime::Item { input: 0, index: m })
+                .await;
+        }
+
+        let o = sio.output(0).slice::<f32>();
+        let n = std::cmp::min(o.len(), self.n_items - self.produced);
+        self.produced += n;
+        sio.output(0).produce(n);
+        Ok(())
+    }
+}
+
+/// Records the messages it receives, together with the number of items consumed at that time.
+/// Consumes items in chunks of `chunk` samples and finishes after `n_items` samples.
+struct Recorder {
+    n_items: u64,
+    chunk: usize,
+    consumed: u64,
+    received: Vec<(Pmt, u64)>,
+}
+
+impl Recorder {
+    fn block(n_items: u64, chunk: usize) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Recorder").min_items(chunk).build(),
+            StreamIoBuilder::new().add_input::<f32>("in").build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "mark",
+                    |block: &mut Recorder,
+                     _mio: &mut MessageIo<Recorder>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            block.received.push((p, block.consumed));
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            Recorder {
+                n_items,
+                chunk,
+                consumed: 0,
+                received: Vec::new(),
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Recorder {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let n = sio.input(0).slice::<f32>().len() / self.chunk * self.chunk;
+        self.consumed += n as u64;
+        sio.input(0).consume(n);
+        if self.consumed == self.n_items {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+/// Like the [Recorder], but takes the buffers of an in-place input as a whole, unless the input
+/// is limited by a scheduled message.
+struct InPlaceRecorder {
+    n_items: u64,
+    consumed: u64,
+    received: Vec<(Pmt, u64)>,
+}
+
+impl InPlaceRecorder {
+    fn block(n_items: u64) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("InPlaceRecorder").build(),
+            StreamIoBuilder::new().add_input::<f32>("in").build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "mark",
+                    |block: &mut InPlaceRecorder,
+                     _mio: &mut MessageIo<InPlaceRecorder>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            block.received.push((p, block.consumed));
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            InPlaceRecorder {
+                n_items,
+                consumed: 0,
+                received: Vec::new(),
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for InPlaceRecorder {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        if sio.input(0).limited() {
+            let n = sio.input(0).slice::<f32>().len();
+            self.consumed += n as u64;
+            sio.input(0).consume(n);
+        } else {
+            while let Some(b) = sio.input(0).try_as::<inplace::Reader>().unwrap().take() {
+                let n = b.len::<f32>();
+                self.consumed += n as u64;
+                sio.input(0).add_consumed(n);
+            }
+        }
+        if self.consumed == self.n_items {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+/// Records the messages it receives and the time of their arrival.
+struct MessageRecorder {
+    received: Arc<Mutex<Vec<(Pmt, Instant)>>>,
+}
+
+impl MessageRecorder {
+    fn block(received: Arc<Mutex<Vec<(Pmt, Instant)>>>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("MessageRecorder").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input(
+                    "mark",
+                    |block: &mut MessageRecorder,
+                     _mio: &mut MessageIo<MessageRecorder>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        async move {
+                            block.received.lock().unwrap().push((p, Instant::now()));
+                            Ok(Pmt::Null)
+                        }
+                        .boxed()
+                    },
+                )
+                .build(),
+            MessageRecorder { received },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for MessageRecorder {}
+
+#[test]
+fn post_at_item() -> Result<()> {
+    let marks = vec![0, 1000, 4096, 4097, 9999];
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(MarkSource::block(10_000, marks.clone()));
+    let snk = fg.add_block(Recorder::block(10_000, 1));
+    fg.connect_stream(src, "out", snk, "in")?;
+    fg.connect_message(src, "marks", snk, "mark")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<Recorder>(snk).unwrap();
+    assert_eq!(snk.consumed, 10_000);
+    let expected: Vec<(Pmt, u64)> = marks.iter().map(|m| (Pmt::U64(*m), *m)).collect();
+    assert_eq!(snk.received, expected);
+
+    Ok(())
+}
+
+#[test]
+fn post_at_item_chunks() -> Result<()> {
+    let marks = vec![0, 64, 100, 4096, 4097];
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(MarkSource::block(10_240, marks.clone()));
+    let snk = fg.add_block(Recorder::block(10_240, 64));
+    fg.connect_stream(src, "out", snk, "in")?;
+    fg.connect_message(src, "marks", snk, "mark")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    // marks within a chunk are delivered after the chunk
+    let snk = fg.kernel::<Recorder>(snk).unwrap();
+    assert_eq!(snk.consumed, 10_240);
+    let expected: Vec<(Pmt, u64)> = vec![0, 64, 128, 4096, 4160]
+        .into_iter()
+        .zip(marks.iter())
+        .map(|(c, m)| (Pmt::U64(*m), c))
+        .collect();
+    assert_eq!(snk.received, expected);
+
+    Ok(())
+}
+
+#[test]
+fn post_at_item_inplace() -> Result<()> {
+    let marks = vec![0, 1000, 4096, 4097, 9999];
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(MarkSource::block(10_000, marks.clone()));
+    let snk = fg.add_block(InPlaceRecorder::block(10_000));
+    // the source does not finish, i.e., it only hands over full buffers of 1000 items
+    fg.connect_stream_with_type(src, "out", snk, "in", InPlace::with_size(4000))?;
+    fg.connect_message(src, "marks", snk, "mark")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<InPlaceRecorder>(snk).unwrap();
+    assert_eq!(snk.consumed, 10_000);
+    let expected: Vec<(Pmt, u64)> = marks.iter().map(|m| (Pmt::U64(*m), *m)).collect();
+    assert_eq!(snk.received, expected);
+
+    Ok(())
+}
+
+#[test]
+fn call_at_instant() -> Result<()> {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut fg = Flowgraph::new();
+    let rec = fg.add_block(MessageRecorder::block(received.clone()));
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    let deadline = block_on(async move {
+        let deadline = Instant::now() + Duration::from_millis(50);
+        handle
+            .call_at(rec, 0, Pmt::U32(1), CallTime::Instant(deadline))
+            .await?;
+        handle.call(rec, 0, Pmt::U32(0)).await?;
+        Timer::after(Duration::from_millis(100)).await;
+        handle.terminate().await?;
+        let _ = task.await;
+        Ok::<_, futuresdr::anyhow::Error>(deadline)
+    })?;
+
+    let received = received.lock().unwrap();
+    let messages: Vec<Pmt> = received.iter().map(|(p, _)| p.clone()).collect();
+    assert_eq!(messages, vec![Pmt::U32(0), Pmt::U32(1)]);
+    assert!(received[1].1 >= deadline);
+
+    Ok(())
+}
+
+#[test]
+fn call_at_invalid() -> Result<()> {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut fg = Flowgraph::new();
+    let rec = fg.add_block(MessageRecorder::block(received.clone()));
+
+    let rt = Runtime::new();
+    let (task, mut handle) = block_on(rt.start(fg));
+    block_on(async move {
+        let now = Instant::now();
+        assert!(handle
+            .call_at(rec, 1, Pmt::U32(1), CallTime::Instant(now))
+            .await
+            .is_err());
+        assert!(handle
+            .call_at(rec, 0, Pmt::U32(2), CallTime::Item { input: 0, index: 0 })
+            .await
+            .is_err());
+        // the block is still running
+        handle.callback(rec, 0, Pmt::U32(0)).await?;
+        handle.terminate().await?;
+        let _ = task.await;
+        Ok::<_, futuresdr::anyhow::Error>(())
+    })?;
+
+    let received = received.lock().unwrap();
+    let messages: Vec<Pmt> = received.iter().map(|(p, _)| p.clone()).collect();
+    assert_eq!(messages, vec![Pmt::U32(0)]);
+
+    Ok(())
+}